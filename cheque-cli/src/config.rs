@@ -0,0 +1,120 @@
+use super::error::Error;
+use cheque_sdk::{ChequeScript, LiveCell};
+use ckb_jsonrpc_types::{CellDep, CellOutput, JsonBytes, OutPoint, ScriptHashType};
+use ckb_tool::ckb_crypto::secp::Privkey;
+use ckb_tool::ckb_types::{core, packed, prelude::*, H256};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const SHANNONS_PER_CKB: u64 = 1_0000_0000;
+const CAPACITY_DECIMALS: usize = 8;
+const PRIVKEY_LEN: usize = 32;
+
+/// The deployment file of the cheque lock script, in the JSON types of the CKB RPC
+#[derive(Serialize, Deserialize)]
+pub struct Deployment {
+    pub code_hash:          H256,
+    pub hash_type:          ScriptHashType,
+    pub cell_dep:           CellDep,
+    pub secp256k1_data_dep: CellDep,
+    // The cell deps of the lock scripts of the other inputs, like the dep group of
+    // secp256k1_blake160_sighash_all, which are added to every built transaction
+    #[serde(default)]
+    pub lock_deps:          Vec<CellDep>,
+}
+
+impl Deployment {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn cheque_script(&self) -> ChequeScript {
+        ChequeScript {
+            code_hash:          self.code_hash.pack(),
+            hash_type:          self.hash_type.clone().into(),
+            cell_dep:           self.cell_dep.clone().into(),
+            secp256k1_data_dep: self.secp256k1_data_dep.clone().into(),
+        }
+    }
+
+    pub fn lock_deps(&self) -> Vec<packed::CellDep> {
+        self.lock_deps.iter().cloned().map(Into::into).collect()
+    }
+
+    pub fn is_cheque_lock(&self, lock_script: &packed::Script) -> bool {
+        let hash_type: core::ScriptHashType = self.hash_type.clone().into();
+        let hash_type: packed::Byte = hash_type.into();
+        lock_script.code_hash().as_slice() == self.code_hash.as_bytes()
+            && lock_script.hash_type().as_slice() == hash_type.as_slice()
+    }
+}
+
+/// A live cell in the same fields as the cells of the `get_cells` RPC of the CKB indexer, whose
+/// other fields are ignored
+#[derive(Serialize, Deserialize)]
+pub struct JsonLiveCell {
+    pub out_point:   OutPoint,
+    pub output:      CellOutput,
+    pub output_data: JsonBytes,
+}
+
+impl From<JsonLiveCell> for LiveCell {
+    fn from(cell: JsonLiveCell) -> Self {
+        LiveCell::new(
+            cell.out_point.into(),
+            cell.output.into(),
+            cell.output_data.into_bytes(),
+        )
+    }
+}
+
+/// Loads a JSON array of live cells
+pub fn load_live_cells<P: AsRef<Path>>(path: P) -> Result<Vec<LiveCell>, Error> {
+    let cells: Vec<JsonLiveCell> = serde_json::from_slice(&fs::read(path)?)?;
+    Ok(cells.into_iter().map(Into::into).collect())
+}
+
+/// Loads the private key from the first line of the file in hex, like the key files of ckb-cli
+pub fn load_privkey<P: AsRef<Path>>(path: P) -> Result<Privkey, Error> {
+    let content = fs::read_to_string(path)?;
+    let line = content.lines().next().unwrap_or_default().trim();
+    let key = hex::decode(line.strip_prefix("0x").unwrap_or(line))
+        .map_err(|_| Error::InvalidArgument("private key is not hex".to_owned()))?;
+    if key.len() != PRIVKEY_LEN {
+        return Err(Error::InvalidArgument(
+            "private key is not 32 bytes".to_owned(),
+        ));
+    }
+    Ok(Privkey::from_slice(&key))
+}
+
+/// Parses the capacity in CKB with at most 8 decimals, like `100` or `61.5`, into shannons
+pub fn parse_capacity(capacity: &str) -> Result<u64, Error> {
+    let invalid = || Error::InvalidArgument(format!("invalid capacity {}", capacity));
+    let (integer, fraction) = match capacity.find('.') {
+        Some(index) => (&capacity[..index], &capacity[index + 1..]),
+        None => (capacity, ""),
+    };
+    if integer.is_empty()
+        || fraction.len() > CAPACITY_DECIMALS
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let integer: u64 = integer.parse().map_err(|_| invalid())?;
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<width$}", fraction, width = CAPACITY_DECIMALS)
+            .parse()
+            .map_err(|_| invalid())?
+    };
+    integer
+        .checked_mul(SHANNONS_PER_CKB)
+        .and_then(|shannons| shannons.checked_add(fraction))
+        .ok_or_else(invalid)
+}
@@ -0,0 +1,59 @@
+use core::fmt;
+
+/// Error
+#[derive(Debug)]
+pub enum Error {
+    // The SDK refused to build or sign the transaction
+    Sdk(cheque_sdk::Error),
+    // An input file cannot be read or an output file cannot be written
+    Io(std::io::Error),
+    // An input file is not valid JSON of its format
+    Json(serde_json::Error),
+    // A command line argument is missing or invalid
+    InvalidArgument(String),
+    // An input of the transaction is not found in the given live cells
+    CellNotFound,
+    // The simulator is killed or exits with a code which is not an error of the cheque lock
+    // script
+    SimulatorFailed(Option<i32>),
+}
+
+impl From<cheque_sdk::Error> for Error {
+    fn from(err: cheque_sdk::Error) -> Self {
+        Self::Sdk(err)
+    }
+}
+
+impl From<cheque_core::error::Error> for Error {
+    fn from(err: cheque_core::error::Error) -> Self {
+        Self::Sdk(err.into())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Sdk(err) => write!(f, "{}", err),
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Json(err) => write!(f, "json error: {}", err),
+            Self::InvalidArgument(msg) => write!(f, "invalid argument: {}", msg),
+            Self::CellNotFound => write!(f, "input cell not found in the live cells"),
+            Self::SimulatorFailed(Some(code)) => write!(f, "simulator exited with code {}", code),
+            Self::SimulatorFailed(None) => write!(f, "simulator killed by signal"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
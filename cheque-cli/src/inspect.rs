@@ -0,0 +1,70 @@
+use cheque_core::args::ChequeArgs;
+use cheque_sdk::lifecycle::{ChequeLifecycle, Deadline};
+use serde_json::{json, Value};
+
+fn hex_string(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn since_string(since: u64) -> String {
+    format!("{:#018x}", since)
+}
+
+/// The fields of the lock args, where the absent fields are null
+pub fn args_json(cheque_args: &ChequeArgs) -> Value {
+    json!({
+        "receiver_lock_hash": hex_string(&cheque_args.receiver_lock_hash),
+        "sender_lock_hash": hex_string(&cheque_args.sender_lock_hash),
+        "flags": format!("{:#06x}", cheque_args.flags),
+        "withdraw_since": cheque_args.withdraw_since.map(since_string),
+        "oracle": cheque_args.oracle.map(|(type_hash, milestone)| json!({
+            "type_hash": hex_string(&type_hash),
+            "milestone": milestone,
+        })),
+        "claim_since": cheque_args.claim_since.map(since_string),
+        "vesting": cheque_args.vesting.map(|vesting| json!({
+            "start_epoch": since_string(vesting.start_epoch),
+            "end_epoch": since_string(vesting.end_epoch),
+            "total_capacity": vesting.total_capacity,
+        })),
+        "receiver_group": cheque_args.receiver_group.as_ref().map(|receiver_group| json!({
+            "threshold": receiver_group.threshold,
+            "receiver_lock_hashes": receiver_group
+                .receiver_lock_hashes
+                .iter()
+                .map(|lock_hash| hex_string(lock_hash))
+                .collect::<Vec<_>>(),
+        })),
+        "arbiter_lock_hash": cheque_args.arbiter_lock_hash.map(|lock_hash| hex_string(&lock_hash)),
+        "refund_lock_hash": cheque_args.refund_lock_hash.map(|lock_hash| hex_string(&lock_hash)),
+        "subscription": cheque_args.subscription.map(|subscription| json!({
+            "start_epoch": since_string(subscription.start_epoch),
+            "period_epochs": subscription.period_epochs,
+            "amount": subscription.amount,
+        })),
+    })
+}
+
+fn deadline_json(deadline: &Deadline) -> Value {
+    match deadline {
+        Deadline::BlockNumber(number) => json!({ "block_number": number }),
+        Deadline::Epoch {
+            number,
+            index,
+            length,
+        } => json!({ "epoch": { "number": number, "index": index, "length": length } }),
+        Deadline::Timestamp(timestamp) => json!({ "timestamp": timestamp }),
+        Deadline::Never => json!("never"),
+    }
+}
+
+/// The state of the cheque cell at the tip and when the claim and the withdrawal open
+pub fn lifecycle_json(lifecycle: &ChequeLifecycle) -> Value {
+    json!({
+        "state": format!("{:?}", lifecycle.state),
+        "receiver_can_claim": lifecycle.receiver_can_claim(),
+        "sender_can_withdraw": lifecycle.sender_can_withdraw(),
+        "claim_opens_at": lifecycle.claim_opens_at.as_ref().map(deadline_json),
+        "withdraw_opens_at": deadline_json(&lifecycle.withdraw_opens_at),
+    })
+}
@@ -0,0 +1,24 @@
+//! The command line tool of the cheque lock script, which builds the cheque transactions with the
+//! builders of `cheque-sdk` and verifies them with the native simulator of the `natives` crate.
+//!
+//! The live cells are given as JSON files in the format of the CKB indexer, and the transactions
+//! are written in the transaction file format of ckb-cli, so the ckb-cli can sign the other inputs
+//! and send them.
+//!
+//! See `main.rs` for the subcommands.
+//! See `config.rs` for the deployment file, the live cells, the private keys and the capacities.
+//! See `tx_file.rs` for the ckb-cli transaction file.
+//! See `inspect.rs` for the JSON of the lock args and the lifecycle.
+//! See `verify.rs` for running the simulator against a mock transaction.
+//! See `error.rs` for the `Error` type.
+
+pub mod config;
+pub mod error;
+pub mod inspect;
+pub mod tx_file;
+pub mod verify;
+
+pub use config::Deployment;
+pub use error::Error;
+pub use tx_file::TxFile;
+pub use verify::{GroupVerification, Simulator};
@@ -0,0 +1,438 @@
+use cheque_cli::config::{self, Deployment};
+use cheque_cli::inspect::{args_json, lifecycle_json};
+use cheque_cli::{Error, Simulator, TxFile};
+use cheque_core::args::ChequeArgs;
+use cheque_sdk::address::parse_address;
+use cheque_sdk::{
+    ChequeArgsExt, ChequeLifecycle, ClaimBuilder, ClaimMode, CreateChequeBuilder, LiveCell, Signer,
+    WithdrawBuilder, WithdrawMode,
+};
+use ckb_jsonrpc_types::HeaderView as JsonHeaderView;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{HeaderView, TransactionView},
+    packed::{CellOutput, Script},
+    prelude::*,
+};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use serde_json::json;
+use std::fs;
+use std::process;
+
+const DEFAULT_TX_FEE: &str = "0.001";
+const DEFAULT_SIMULATOR: &str = "build/debug/ckb-cheque-script-sim";
+
+fn main() {
+    let matches = build_app().get_matches();
+    let result = match matches.subcommand() {
+        ("create", Some(matches)) => create(matches),
+        ("claim", Some(matches)) => claim(matches),
+        ("withdraw", Some(matches)) => withdraw(matches),
+        ("inspect", Some(matches)) => inspect(matches),
+        ("verify", Some(matches)) => verify(matches),
+        _ => unreachable!("the subcommand is required"),
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn build_app() -> App<'static, 'static> {
+    let deployment = Arg::with_name("deployment")
+        .long("deployment")
+        .takes_value(true)
+        .required(true)
+        .help("The JSON file of the deployed cheque lock script");
+    let tx_fee = Arg::with_name("tx-fee")
+        .long("tx-fee")
+        .takes_value(true)
+        .default_value(DEFAULT_TX_FEE)
+        .help("The transaction fee in CKB");
+    let privkey_path = Arg::with_name("privkey-path")
+        .long("privkey-path")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .help("The private key file to sign the cheque and the secp256k1_blake160 inputs");
+    let output = Arg::with_name("output")
+        .long("output")
+        .takes_value(true)
+        .help("The ckb-cli transaction file to write, or else the standard output");
+    let cheque_cells = Arg::with_name("cheque-cells")
+        .long("cheque-cells")
+        .takes_value(true)
+        .required(true)
+        .help("The JSON file of the cheque cells of the same lock args");
+
+    App::new("cheque-cli")
+        .about("Builds, inspects and verifies the cheque transactions")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("create")
+                .about("Create a cheque cell of the capacity from the sender to the receiver")
+                .arg(deployment.clone())
+                .arg(address_arg("sender", "The address of the sender").required(true))
+                .arg(address_arg("receiver", "The address of the receiver").required(true))
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The capacity of the cheque cell in CKB"),
+                )
+                .arg(inputs_arg("The JSON file of the live cells of the sender").required(true))
+                .arg(tx_fee.clone())
+                .arg(privkey_path.clone())
+                .arg(output.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("claim")
+                .about("Claim the cheque cells to the receiver")
+                .arg(deployment.clone())
+                .arg(address_arg("receiver", "The address of the receiver").required(true))
+                .arg(
+                    address_arg(
+                        "sender",
+                        "The address of the sender, or of the refund lock hash",
+                    )
+                    .required(true),
+                )
+                .arg(cheque_cells.clone())
+                .arg(inputs_arg(
+                    "The JSON file of the other live cells paying the fee",
+                ))
+                .arg(
+                    Arg::with_name("receiver-input")
+                        .long("receiver-input")
+                        .help("The first of the inputs is a receiver cell instead of a signature"),
+                )
+                .arg(tx_fee.clone())
+                .arg(privkey_path.clone())
+                .arg(output.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw")
+                .about("Withdraw the cheque cells to the sender after the withdraw since")
+                .arg(deployment.clone())
+                .arg(
+                    address_arg(
+                        "sender",
+                        "The address of the sender, or of the refund lock hash",
+                    )
+                    .required(true),
+                )
+                .arg(cheque_cells)
+                .arg(inputs_arg(
+                    "The JSON file of the other live cells paying the fee",
+                ))
+                .arg(
+                    Arg::with_name("sender-input")
+                        .long("sender-input")
+                        .help("The first of the inputs is a sender cell instead of a signature"),
+                )
+                .arg(tx_fee)
+                .arg(privkey_path)
+                .arg(output),
+        )
+        .subcommand(
+            SubCommand::with_name("inspect")
+                .about("Decode the cheque lock args and the state of a cheque cell")
+                .arg(
+                    Arg::with_name("lock-args")
+                        .long("lock-args")
+                        .takes_value(true)
+                        .required_unless("address")
+                        .help("The cheque lock args in hex"),
+                )
+                .arg(address_arg(
+                    "address",
+                    "The address of the cheque lock script",
+                ))
+                .arg(
+                    Arg::with_name("created-header")
+                        .long("created-header")
+                        .takes_value(true)
+                        .requires("tip-header")
+                        .help("The JSON file of the header in which the cheque cell was created"),
+                )
+                .arg(
+                    Arg::with_name("tip-header")
+                        .long("tip-header")
+                        .takes_value(true)
+                        .requires("created-header")
+                        .help("The JSON file of the tip header"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Run the cheque lock script natively against a mock transaction")
+                .arg(deployment)
+                .arg(
+                    Arg::with_name("tx-file")
+                        .long("tx-file")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The JSON file of the mock transaction of the ckb-debugger"),
+                )
+                .arg(
+                    Arg::with_name("simulator")
+                        .long("simulator")
+                        .takes_value(true)
+                        .default_value(DEFAULT_SIMULATOR)
+                        .help("The ckb-cheque-script-sim binary of the natives crate"),
+                ),
+        )
+}
+
+fn address_arg(name: &'static str, help: &'static str) -> Arg<'static, 'static> {
+    Arg::with_name(name).long(name).takes_value(true).help(help)
+}
+
+fn inputs_arg(help: &'static str) -> Arg<'static, 'static> {
+    Arg::with_name("inputs")
+        .long("inputs")
+        .takes_value(true)
+        .help(help)
+}
+
+fn lock_script(matches: &ArgMatches, name: &str) -> Result<Script, Error> {
+    let address = matches.value_of(name).expect("required");
+    Ok(parse_address(address)?.1)
+}
+
+fn live_cells(matches: &ArgMatches, name: &str) -> Result<Vec<LiveCell>, Error> {
+    match matches.value_of(name) {
+        Some(path) => config::load_live_cells(path),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn tx_fee(matches: &ArgMatches) -> Result<u64, Error> {
+    config::parse_capacity(matches.value_of("tx-fee").expect("default"))
+}
+
+// The lock args of the cheque cells, which the builders check against every cheque cell
+fn cheque_args(cheque_cells: &[LiveCell]) -> Result<ChequeArgs, Error> {
+    let cheque_cell = cheque_cells
+        .first()
+        .ok_or(cheque_sdk::Error::NoChequeCells)?;
+    Ok(ChequeArgs::from_slice(
+        &cheque_cell.output.lock().args().raw_data(),
+    )?)
+}
+
+// Signs the transaction with the private keys if any, whose inputs are all in the given cells
+fn sign(
+    matches: &ArgMatches,
+    deployment: &Deployment,
+    tx: TransactionView,
+    cells: &[LiveCell],
+) -> Result<TransactionView, Error> {
+    let privkey_paths = match matches.values_of("privkey-path") {
+        Some(privkey_paths) => privkey_paths,
+        None => return Ok(tx),
+    };
+    let mut signer = Signer::new(deployment.cheque_script());
+    for path in privkey_paths {
+        signer = signer.key(config::load_privkey(path)?)?;
+    }
+    let input_cells = tx
+        .inputs()
+        .into_iter()
+        .map(|input| {
+            cells
+                .iter()
+                .find(|cell| cell.out_point == input.previous_output())
+                .map(|cell| cell.output.clone())
+                .ok_or(Error::CellNotFound)
+        })
+        .collect::<Result<Vec<CellOutput>, Error>>()?;
+    Ok(signer.sign(tx, &input_cells)?)
+}
+
+fn write_tx(matches: &ArgMatches, tx: &TransactionView) -> Result<bool, Error> {
+    let json = TxFile::new(tx).to_json()?;
+    match matches.value_of("output") {
+        Some(path) => fs::write(path, json)?,
+        None => println!("{}", json),
+    }
+    Ok(true)
+}
+
+fn create(matches: &ArgMatches) -> Result<bool, Error> {
+    let deployment = Deployment::load(matches.value_of("deployment").expect("required"))?;
+    let cheque_args = ChequeArgs::from_addresses(
+        matches.value_of("receiver").expect("required"),
+        matches.value_of("sender").expect("required"),
+    )?;
+    let inputs = live_cells(matches, "inputs")?;
+
+    let mut builder = CreateChequeBuilder::new(
+        deployment.cheque_script(),
+        cheque_args,
+        lock_script(matches, "sender")?,
+    )
+    .capacity(config::parse_capacity(
+        matches.value_of("amount").expect("required"),
+    )?)
+    .fee(tx_fee(matches)?);
+    for cell in inputs.iter() {
+        builder = builder.input(cell.clone());
+    }
+    for cell_dep in deployment.lock_deps() {
+        builder = builder.cell_dep(cell_dep);
+    }
+
+    let tx = sign(matches, &deployment, builder.build()?, &inputs)?;
+    write_tx(matches, &tx)
+}
+
+fn claim(matches: &ArgMatches) -> Result<bool, Error> {
+    let deployment = Deployment::load(matches.value_of("deployment").expect("required"))?;
+    let cheque_cells =
+        config::load_live_cells(matches.value_of("cheque-cells").expect("required"))?;
+    let inputs = live_cells(matches, "inputs")?;
+    let (mode, other_inputs) = if matches.is_present("receiver-input") {
+        let (receiver_cell, other_inputs) = inputs
+            .split_first()
+            .ok_or_else(|| Error::InvalidArgument("no receiver input".to_owned()))?;
+        (ClaimMode::LockInput(receiver_cell.clone()), other_inputs)
+    } else {
+        (ClaimMode::Signature, &inputs[..])
+    };
+
+    let mut builder = ClaimBuilder::new(
+        deployment.cheque_script(),
+        cheque_args(&cheque_cells)?,
+        lock_script(matches, "receiver")?,
+        lock_script(matches, "sender")?,
+        mode,
+    )
+    .fee(tx_fee(matches)?);
+    for cell in cheque_cells.iter() {
+        builder = builder.cheque_cell(cell.clone());
+    }
+    for cell in other_inputs.iter() {
+        builder = builder.input(cell.clone());
+    }
+    for cell_dep in deployment.lock_deps() {
+        builder = builder.cell_dep(cell_dep);
+    }
+
+    let mut cells = cheque_cells;
+    cells.extend(inputs);
+    let tx = sign(matches, &deployment, builder.build()?, &cells)?;
+    write_tx(matches, &tx)
+}
+
+fn withdraw(matches: &ArgMatches) -> Result<bool, Error> {
+    let deployment = Deployment::load(matches.value_of("deployment").expect("required"))?;
+    let cheque_cells =
+        config::load_live_cells(matches.value_of("cheque-cells").expect("required"))?;
+    let inputs = live_cells(matches, "inputs")?;
+    let (mode, other_inputs) = if matches.is_present("sender-input") {
+        let (sender_cell, other_inputs) = inputs
+            .split_first()
+            .ok_or_else(|| Error::InvalidArgument("no sender input".to_owned()))?;
+        (WithdrawMode::LockInput(sender_cell.clone()), other_inputs)
+    } else {
+        (WithdrawMode::Signature, &inputs[..])
+    };
+
+    let mut builder = WithdrawBuilder::new(
+        deployment.cheque_script(),
+        cheque_args(&cheque_cells)?,
+        lock_script(matches, "sender")?,
+        mode,
+    )
+    .fee(tx_fee(matches)?);
+    for cell in cheque_cells.iter() {
+        builder = builder.cheque_cell(cell.clone());
+    }
+    for cell in other_inputs.iter() {
+        builder = builder.input(cell.clone());
+    }
+    for cell_dep in deployment.lock_deps() {
+        builder = builder.cell_dep(cell_dep);
+    }
+
+    let mut cells = cheque_cells;
+    cells.extend(inputs);
+    let tx = sign(matches, &deployment, builder.build()?, &cells)?;
+    write_tx(matches, &tx)
+}
+
+fn load_header(path: &str) -> Result<HeaderView, Error> {
+    let header: JsonHeaderView = serde_json::from_slice(&fs::read(path)?)?;
+    Ok(header.into())
+}
+
+fn inspect(matches: &ArgMatches) -> Result<bool, Error> {
+    let lock_args: Bytes = match matches.value_of("lock-args") {
+        Some(lock_args) => {
+            let lock_args = lock_args.strip_prefix("0x").unwrap_or(lock_args);
+            hex::decode(lock_args)
+                .map_err(|_| Error::InvalidArgument("lock args is not hex".to_owned()))?
+                .into()
+        }
+        None => lock_script(matches, "address")?.args().raw_data(),
+    };
+    let cheque_args = ChequeArgs::from_slice(&lock_args)?;
+
+    let lifecycle = match (
+        matches.value_of("created-header"),
+        matches.value_of("tip-header"),
+    ) {
+        (Some(created_header), Some(tip_header)) => {
+            // The lifecycle only reads the lock args of the cheque cell
+            let cheque_cell = CellOutput::new_builder()
+                .lock(Script::new_builder().args(lock_args.pack()).build())
+                .build();
+            let lifecycle = ChequeLifecycle::calculate(
+                &cheque_cell,
+                &load_header(created_header)?,
+                &load_header(tip_header)?,
+            )?;
+            Some(lifecycle_json(&lifecycle))
+        }
+        _ => None,
+    };
+
+    let json = json!({
+        "lock_args": args_json(&cheque_args),
+        "lifecycle": lifecycle,
+    });
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(true)
+}
+
+fn verify(matches: &ArgMatches) -> Result<bool, Error> {
+    let deployment = Deployment::load(matches.value_of("deployment").expect("required"))?;
+    let simulator = Simulator::new(matches.value_of("simulator").expect("default"));
+    let verifications =
+        simulator.verify(&deployment, matches.value_of("tx-file").expect("required"))?;
+
+    let json = verifications
+        .iter()
+        .map(|verification| match verification.result {
+            Ok(()) => json!({
+                "input_index": verification.input_index,
+                "result": "success",
+            }),
+            Err(err) => json!({
+                "input_index": verification.input_index,
+                "result": format!("{:?}", err),
+                "exit_code": err as i8,
+            }),
+        })
+        .collect::<Vec<_>>();
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(verifications
+        .iter()
+        .all(|verification| verification.result.is_ok()))
+}
@@ -0,0 +1,43 @@
+use super::error::Error;
+use ckb_jsonrpc_types::Transaction;
+use ckb_tool::ckb_types::{core::TransactionView, packed, prelude::*};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The transaction file of the `ckb-cli tx` subcommands, so the ckb-cli can sign the inputs of the
+/// other lock scripts and send the transaction.
+///
+/// The multisig configs and the signatures of the ckb-cli are kept as they are.
+#[derive(Serialize, Deserialize)]
+pub struct TxFile {
+    pub transaction:      Transaction,
+    #[serde(default)]
+    pub multisig_configs: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub signatures:       BTreeMap<String, Value>,
+}
+
+impl TxFile {
+    pub fn new(tx: &TransactionView) -> Self {
+        TxFile {
+            transaction:      tx.data().into(),
+            multisig_configs: BTreeMap::new(),
+            signatures:       BTreeMap::new(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn tx(&self) -> TransactionView {
+        packed::Transaction::from(self.transaction.clone()).into_view()
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
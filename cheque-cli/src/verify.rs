@@ -0,0 +1,95 @@
+use super::config::Deployment;
+use super::error::Error;
+use cheque_core::error::Error as ChequeError;
+use cheque_sdk::signer::lock_script_groups;
+use ckb_standalone_debugger::transaction::{MockTransaction, ReprMockTransaction};
+use ckb_tool::ckb_types::packed::CellOutput;
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// The result of the cheque lock script for one cheque group of the transaction
+#[derive(Debug)]
+pub struct GroupVerification {
+    // The first input of the group, which the simulator runs as the script index
+    pub input_index: usize,
+    pub result:      Result<(), ChequeError>,
+}
+
+/// Runs the native simulator of the cheque lock script, the `ckb-cheque-script-sim` binary of the
+/// `natives` crate, like the `cmd` files dumped by the tests
+pub struct Simulator {
+    binary: PathBuf,
+}
+
+impl Simulator {
+    pub fn new<P: Into<PathBuf>>(binary: P) -> Self {
+        Simulator {
+            binary: binary.into(),
+        }
+    }
+
+    /// Verifies every cheque group of the mock transaction file, which is the `ReprMockTransaction`
+    /// JSON of the ckb-debugger
+    pub fn verify<P: AsRef<Path>>(
+        &self,
+        deployment: &Deployment,
+        tx_file: P,
+    ) -> Result<Vec<GroupVerification>, Error> {
+        let repr_tx: ReprMockTransaction = serde_json::from_slice(&fs::read(tx_file.as_ref())?)?;
+        let mock_tx: MockTransaction = repr_tx.into();
+        let input_cells: Vec<CellOutput> = mock_tx
+            .mock_info
+            .inputs
+            .iter()
+            .map(|input| input.output.clone())
+            .collect();
+
+        lock_script_groups(&input_cells)
+            .into_iter()
+            .filter(|group| deployment.is_cheque_lock(&group.lock_script))
+            .map(|group| {
+                let input_index = group.input_indices[0];
+                let result = self.run(tx_file.as_ref(), input_index)?;
+                Ok(GroupVerification {
+                    input_index,
+                    result,
+                })
+            })
+            .collect()
+    }
+
+    fn run(&self, tx_file: &Path, input_index: usize) -> Result<Result<(), ChequeError>, Error> {
+        let setup = RunningSetup {
+            is_lock_script:  true,
+            is_output:       false,
+            script_index:    input_index as u64,
+            native_binaries: HashMap::default(),
+        };
+        let setup_file = std::env::temp_dir().join(format!(
+            "cheque-cli-setup-{}-{}.json",
+            std::process::id(),
+            input_index
+        ));
+        fs::write(&setup_file, serde_json::to_string_pretty(&setup)?)?;
+        let status = Command::new(&self.binary)
+            .env("CKB_TX_FILE", tx_file)
+            .env("CKB_RUNNING_SETUP", &setup_file)
+            .stdout(Stdio::null())
+            .status();
+        fs::remove_file(&setup_file)?;
+
+        match status?.code() {
+            Some(0) => Ok(Ok(())),
+            Some(code) => i8::try_from(code)
+                .ok()
+                .and_then(|code| ChequeError::try_from(code).ok())
+                .map(Err)
+                .ok_or(Error::SimulatorFailed(Some(code))),
+            None => Err(Error::SimulatorFailed(None)),
+        }
+    }
+}
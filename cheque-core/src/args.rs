@@ -0,0 +1,447 @@
+use crate::error::Error;
+use crate::hash;
+use crate::since;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+pub const LOCK_HASH_LEN: usize = 20;
+const LEGACY_ARGS_LEN: usize = 40;
+const FLAGS_LEN: usize = 2;
+const SINCE_LEN: usize = 8;
+const TYPE_HASH_LEN: usize = 32;
+pub const MAX_RECEIVER_GROUP_LEN: usize = 16;
+pub const SALT_LEN: usize = 32;
+
+// The capacity of cheque cells without type script is paid to the receiver
+pub const FLAG_CAPACITY: u16 = 0x0001;
+
+// The cheque cells with type script carry non-fungible assets which must be kept as they are
+pub const FLAG_NFT: u16 = 0x0002;
+
+// The since of withdrawing is given by the lock args instead of the default lock-up period
+pub const FLAG_WITHDRAW_SINCE: u16 = 0x0004;
+
+// The receiver can only claim when the milestone of the oracle cell in the cell deps is reached
+pub const FLAG_ORACLE: u16 = 0x0008;
+
+// The since of claiming must not be earlier than the claim since given by the lock args
+pub const FLAG_CLAIM_SINCE: u16 = 0x0010;
+
+// The receiver_lock_hash of the lock args is blake2b_160(receiver_lock_hash[0..20] | salt)
+pub const FLAG_SALTED: u16 = 0x0020;
+
+// The capacity of cheque cells without type script vests linearly from the start to the end epoch
+pub const FLAG_VESTING: u16 = 0x0040;
+
+// Any threshold of the receiver lock hashes of the group can jointly claim
+pub const FLAG_RECEIVER_GROUP: u16 = 0x0080;
+
+// Any two of the sender, the receiver and the arbiter can release or refund the cheque cells
+pub const FLAG_ARBITER: u16 = 0x0100;
+
+// The refunds of the sender go to the refund lock hash while the sender still authorizes
+pub const FLAG_REFUND: u16 = 0x0200;
+
+// The cheque cells with the Nervos DAO type script are deposits which start withdrawing to the
+// receiver, so the receiver keeps the interest
+pub const FLAG_DAO: u16 = 0x0400;
+
+// The receiver pulls a fixed amount of capacity once per period and the rest stays in the cheque cell
+pub const FLAG_SUBSCRIPTION: u16 = 0x0800;
+
+const KNOWN_FLAGS: u16 = FLAG_CAPACITY
+    | FLAG_NFT
+    | FLAG_WITHDRAW_SINCE
+    | FLAG_ORACLE
+    | FLAG_CLAIM_SINCE
+    | FLAG_SALTED
+    | FLAG_VESTING
+    | FLAG_RECEIVER_GROUP
+    | FLAG_ARBITER
+    | FLAG_REFUND
+    | FLAG_DAO
+    | FLAG_SUBSCRIPTION;
+
+// Cheque cell lock args:
+// receiver_lock_hash[0..20] | sender_lock_hash[20..40] | flags[40..42](optional, u16 little endian) | fields
+//
+// The fields of the flags are appended after the flags in the order of the flag bits:
+//   FLAG_WITHDRAW_SINCE: withdraw_since(u64 little endian)
+//   FLAG_ORACLE: oracle_type_hash(32 bytes) | milestone(u8)
+//   FLAG_CLAIM_SINCE: claim_since(u64 little endian)
+//   FLAG_VESTING: start_epoch(u64 little endian) | end_epoch(u64 little endian) | total_capacity(u64 little endian)
+//   FLAG_RECEIVER_GROUP: threshold(u8) | count(u8) | receiver_lock_hashes(count * 20 bytes)
+//     and the receiver_lock_hash of the lock args is blake2b_160 of the group field
+//   FLAG_ARBITER: arbiter_lock_hash(20 bytes)
+//   FLAG_REFUND: refund_lock_hash(20 bytes)
+//   FLAG_SUBSCRIPTION: start_epoch(u64 little endian) | period_epochs(u64 little endian) | amount(u64 little endian)
+// The other flags have no fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChequeArgs {
+    pub receiver_lock_hash: [u8; 20],
+    pub sender_lock_hash:   [u8; 20],
+    pub flags:              u16,
+    pub withdraw_since:     Option<u64>,
+    pub oracle:             Option<([u8; 32], u8)>,
+    pub claim_since:        Option<u64>,
+    pub vesting:            Option<Vesting>,
+    pub receiver_group:     Option<ReceiverGroup>,
+    pub arbiter_lock_hash:  Option<[u8; 20]>,
+    pub refund_lock_hash:   Option<[u8; 20]>,
+    pub subscription:       Option<Subscription>,
+}
+
+// The start and end epochs are epoch numbers with fraction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vesting {
+    pub start_epoch:    u64,
+    pub end_epoch:      u64,
+    pub total_capacity: u64,
+}
+
+// The start epoch is an epoch number with fraction, the period is a number of whole epochs and the
+// amount is the capacity of every pull in shannons
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Subscription {
+    pub start_epoch:   u64,
+    pub period_epochs: u64,
+    pub amount:        u64,
+}
+
+// Every pull recreates the subscription cheque cell and restarts a relative withdraw since, so the
+// relative withdraw since must be counted in epochs and fit in one period, or the receiver can keep
+// pulling before the sender can ever withdraw
+fn is_notice_within_period(withdraw_since: u64, period_epochs: u64) -> bool {
+    if withdraw_since & since::SINCE_RELATIVE_FLAG == 0 {
+        return true;
+    }
+    if !since::is_relative_epoch(withdraw_since) {
+        return false;
+    }
+    let notice = since::epoch_to_fixed_point(withdraw_since & since::SINCE_VALUE_MASK);
+    period_epochs
+        .checked_mul(1 << 16)
+        .map_or(true, |period| period >= notice)
+}
+
+// The receiver lock hashes of the group are distinct, and the threshold is between 1 and their count
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiverGroup {
+    pub threshold:            u8,
+    pub receiver_lock_hashes: Vec<[u8; 20]>,
+}
+
+struct FieldReader<'a> {
+    args:   &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn read(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.args.len() < self.offset + len {
+            return Err(Error::InvalidArgument);
+        }
+        let field = &self.args[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(field)
+    }
+
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut buf = [0u8; SINCE_LEN];
+        buf.copy_from_slice(self.read(SINCE_LEN)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+}
+
+impl ChequeArgs {
+    // The lock args without flags from the full 32 bytes lock script hashes of the receiver and
+    // the sender, whose first 20 bytes are kept
+    pub fn from_lock_hashes(receiver_lock_hash: &[u8; 32], sender_lock_hash: &[u8; 32]) -> Self {
+        let mut receiver = [0u8; LOCK_HASH_LEN];
+        let mut sender = [0u8; LOCK_HASH_LEN];
+        receiver.copy_from_slice(&receiver_lock_hash[0..LOCK_HASH_LEN]);
+        sender.copy_from_slice(&sender_lock_hash[0..LOCK_HASH_LEN]);
+        ChequeArgs {
+            receiver_lock_hash: receiver,
+            sender_lock_hash:   sender,
+            flags:              0,
+            withdraw_since:     None,
+            oracle:             None,
+            claim_since:        None,
+            vesting:            None,
+            receiver_group:     None,
+            arbiter_lock_hash:  None,
+            refund_lock_hash:   None,
+            subscription:       None,
+        }
+    }
+
+    pub fn from_slice(args: &[u8]) -> Result<Self, Error> {
+        if args.len() != LEGACY_ARGS_LEN && args.len() < LEGACY_ARGS_LEN + FLAGS_LEN {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut receiver_lock_hash = [0u8; 20];
+        let mut sender_lock_hash = [0u8; 20];
+        receiver_lock_hash.copy_from_slice(&args[0..LOCK_HASH_LEN]);
+        sender_lock_hash.copy_from_slice(&args[LOCK_HASH_LEN..LEGACY_ARGS_LEN]);
+
+        let mut cheque_args = ChequeArgs {
+            receiver_lock_hash,
+            sender_lock_hash,
+            flags: 0,
+            withdraw_since: None,
+            oracle: None,
+            claim_since: None,
+            vesting: None,
+            receiver_group: None,
+            arbiter_lock_hash: None,
+            refund_lock_hash: None,
+            subscription: None,
+        };
+        if args.len() == LEGACY_ARGS_LEN {
+            return Ok(cheque_args);
+        }
+
+        cheque_args.flags = u16::from_le_bytes([args[LEGACY_ARGS_LEN], args[LEGACY_ARGS_LEN + 1]]);
+        // The flags must be omitted rather than zero to keep the lock args unique
+        if cheque_args.flags == 0 || cheque_args.flags & !KNOWN_FLAGS != 0 {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut reader = FieldReader {
+            args,
+            offset: LEGACY_ARGS_LEN + FLAGS_LEN,
+        };
+        if cheque_args.has_flag(FLAG_WITHDRAW_SINCE) {
+            cheque_args.withdraw_since = Some(reader.read_u64()?);
+        }
+        if cheque_args.has_flag(FLAG_ORACLE) {
+            let mut oracle_type_hash = [0u8; 32];
+            oracle_type_hash.copy_from_slice(reader.read(TYPE_HASH_LEN)?);
+            let milestone = reader.read(1)?[0];
+            cheque_args.oracle = Some((oracle_type_hash, milestone));
+        }
+        if cheque_args.has_flag(FLAG_CLAIM_SINCE) {
+            cheque_args.claim_since = Some(reader.read_u64()?);
+        }
+        if cheque_args.has_flag(FLAG_VESTING) {
+            let vesting = Vesting {
+                start_epoch:    reader.read_u64()?,
+                end_epoch:      reader.read_u64()?,
+                total_capacity: reader.read_u64()?,
+            };
+            // The vesting capacity is paid to the receiver and the vesting period must not be empty
+            if !cheque_args.has_flag(FLAG_CAPACITY)
+                || cheque_args.has_flag(FLAG_NFT)
+                || since::epoch_to_fixed_point(vesting.start_epoch)
+                    >= since::epoch_to_fixed_point(vesting.end_epoch)
+            {
+                return Err(Error::InvalidArgument);
+            }
+            cheque_args.vesting = Some(vesting);
+        }
+        if cheque_args.has_flag(FLAG_RECEIVER_GROUP) {
+            let group_offset = reader.offset;
+            let threshold = reader.read(1)?[0];
+            let count = reader.read(1)?[0] as usize;
+            let mut receiver_lock_hashes: Vec<[u8; 20]> = Vec::new();
+            for _ in 0..count {
+                let mut lock_hash = [0u8; 20];
+                lock_hash.copy_from_slice(reader.read(LOCK_HASH_LEN)?);
+                if receiver_lock_hashes.contains(&lock_hash) {
+                    return Err(Error::InvalidArgument);
+                }
+                receiver_lock_hashes.push(lock_hash);
+            }
+            // The receiver lock hash of the lock args commits to the group for indexing
+            if threshold == 0
+                || threshold as usize > count
+                || count > MAX_RECEIVER_GROUP_LEN
+                || cheque_args.has_flag(FLAG_SALTED)
+                || hash::blake2b_160(&args[group_offset..reader.offset]) != receiver_lock_hash
+            {
+                return Err(Error::InvalidArgument);
+            }
+            cheque_args.receiver_group = Some(ReceiverGroup {
+                threshold,
+                receiver_lock_hashes,
+            });
+        }
+        if cheque_args.has_flag(FLAG_ARBITER) {
+            // The escrow needs the receiver lock hash itself to release the cheque cells without the receiver
+            if cheque_args.has_flag(FLAG_SALTED)
+                || cheque_args.has_flag(FLAG_VESTING)
+                || cheque_args.has_flag(FLAG_RECEIVER_GROUP)
+            {
+                return Err(Error::InvalidArgument);
+            }
+            let mut arbiter_lock_hash = [0u8; 20];
+            arbiter_lock_hash.copy_from_slice(reader.read(LOCK_HASH_LEN)?);
+            // Every party of the escrow must authorize on its own
+            if arbiter_lock_hash == receiver_lock_hash
+                || arbiter_lock_hash == sender_lock_hash
+                || receiver_lock_hash == sender_lock_hash
+            {
+                return Err(Error::InvalidArgument);
+            }
+            cheque_args.arbiter_lock_hash = Some(arbiter_lock_hash);
+        }
+        if cheque_args.has_flag(FLAG_REFUND) {
+            let mut refund_lock_hash = [0u8; 20];
+            refund_lock_hash.copy_from_slice(reader.read(LOCK_HASH_LEN)?);
+            // The refund lock hash must be omitted rather than the same as the sender lock hash
+            if refund_lock_hash == sender_lock_hash {
+                return Err(Error::InvalidArgument);
+            }
+            cheque_args.refund_lock_hash = Some(refund_lock_hash);
+        }
+        if cheque_args.has_flag(FLAG_SUBSCRIPTION) {
+            let subscription = Subscription {
+                start_epoch:   reader.read_u64()?,
+                period_epochs: reader.read_u64()?,
+                amount:        reader.read_u64()?,
+            };
+            // The pulls are paid from the capacity of the cheque cell like the vesting capacity
+            if !cheque_args.has_flag(FLAG_CAPACITY)
+                || cheque_args.has_flag(FLAG_NFT)
+                || cheque_args.has_flag(FLAG_VESTING)
+                || cheque_args.has_flag(FLAG_ARBITER)
+                || cheque_args.has_flag(FLAG_DAO)
+                || subscription.period_epochs == 0
+                || subscription.amount == 0
+            {
+                return Err(Error::InvalidArgument);
+            }
+            let withdraw_since = cheque_args
+                .withdraw_since
+                .unwrap_or(since::CHEQUE_CELL_SINCE);
+            if !is_notice_within_period(withdraw_since, subscription.period_epochs) {
+                return Err(Error::InvalidArgument);
+            }
+            cheque_args.subscription = Some(subscription);
+        }
+        if reader.offset != args.len() {
+            return Err(Error::InvalidArgument);
+        }
+
+        Ok(cheque_args)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut args = Vec::new();
+        args.extend_from_slice(&self.receiver_lock_hash);
+        args.extend_from_slice(&self.sender_lock_hash);
+        if self.flags == 0 {
+            return args;
+        }
+
+        args.extend_from_slice(&self.flags.to_le_bytes());
+        if let Some(withdraw_since) = self.withdraw_since {
+            args.extend_from_slice(&withdraw_since.to_le_bytes());
+        }
+        if let Some((oracle_type_hash, milestone)) = self.oracle {
+            args.extend_from_slice(&oracle_type_hash);
+            args.push(milestone);
+        }
+        if let Some(claim_since) = self.claim_since {
+            args.extend_from_slice(&claim_since.to_le_bytes());
+        }
+        if let Some(vesting) = self.vesting {
+            args.extend_from_slice(&vesting.start_epoch.to_le_bytes());
+            args.extend_from_slice(&vesting.end_epoch.to_le_bytes());
+            args.extend_from_slice(&vesting.total_capacity.to_le_bytes());
+        }
+        if let Some(receiver_group) = &self.receiver_group {
+            args.push(receiver_group.threshold);
+            args.push(receiver_group.receiver_lock_hashes.len() as u8);
+            for lock_hash in receiver_group.receiver_lock_hashes.iter() {
+                args.extend_from_slice(lock_hash);
+            }
+        }
+        if let Some(arbiter_lock_hash) = self.arbiter_lock_hash {
+            args.extend_from_slice(&arbiter_lock_hash);
+        }
+        if let Some(refund_lock_hash) = self.refund_lock_hash {
+            args.extend_from_slice(&refund_lock_hash);
+        }
+        if let Some(subscription) = self.subscription {
+            args.extend_from_slice(&subscription.start_epoch.to_le_bytes());
+            args.extend_from_slice(&subscription.period_epochs.to_le_bytes());
+            args.extend_from_slice(&subscription.amount.to_le_bytes());
+        }
+        args
+    }
+
+    pub fn has_flag(&self, flag: u16) -> bool {
+        self.flags & flag == flag
+    }
+
+    // The refunds of the sender go to the refund lock hash if given, otherwise to the sender lock hash
+    pub fn sender_refund_lock_hash(&self) -> &[u8; 20] {
+        self.refund_lock_hash.as_ref().unwrap_or(&self.sender_lock_hash)
+    }
+}
+
+impl ReceiverGroup {
+    // At least the threshold of distinct receivers of the group must authorize the claim, and the
+    // first of them in the group receives the assets
+    pub fn match_authorized<F: Fn(&[u8; 20]) -> bool>(&self, authorized: F) -> Option<[u8; 20]> {
+        let authorized_lock_hashes = self
+            .receiver_lock_hashes
+            .iter()
+            .filter(|lock_hash| authorized(lock_hash))
+            .collect::<Vec<_>>();
+        if authorized_lock_hashes.len() >= self.threshold as usize {
+            authorized_lock_hashes.first().map(|lock_hash| **lock_hash)
+        } else {
+            None
+        }
+    }
+}
+
+// The salted receiver lock hash of the cheque lock args hides the receiver until claiming
+pub fn salted_lock_hash(lock_hash: &[u8; 20], salt: &[u8; SALT_LEN]) -> [u8; 20] {
+    let mut preimage = [0u8; LOCK_HASH_LEN + SALT_LEN];
+    preimage[0..LOCK_HASH_LEN].copy_from_slice(lock_hash);
+    preimage[LOCK_HASH_LEN..].copy_from_slice(salt);
+    hash::blake2b_160(&preimage[..])
+}
+
+// The lock args are displayed as the 0x prefixed hex string of the bytes, which is how they are
+// written in the lock script of the RPC and the deployment configurations
+impl fmt::Display for ChequeArgs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x")?;
+        for byte in self.to_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ChequeArgs {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+        if hex.len() % 2 != 0 {
+            return Err(Error::InvalidArgument);
+        }
+        let args = hex
+            .chunks(2)
+            .map(|pair| Ok(hex_value(pair[0])? << 4 | hex_value(pair[1])?))
+            .collect::<Result<Vec<u8>, Error>>()?;
+        Self::from_slice(&args)
+    }
+}
+
+fn hex_value(c: u8) -> Result<u8, Error> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(Error::InvalidArgument),
+    }
+}
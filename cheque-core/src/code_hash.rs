@@ -0,0 +1,14 @@
+// The hash type of the system scripts which are referenced by type id
+pub const HASH_TYPE_TYPE: u8 = 1;
+
+// The official secp256k1_blake160_sighash_all lock script of the receiver and the sender
+pub const CODE_HASH_SECP256K1_BLAKE160: [u8; 32] = [
+    155, 215, 224, 111, 62, 207, 75, 224, 242, 252, 210, 24, 139, 35, 241, 185, 252, 200, 142, 93,
+    75, 101, 168, 99, 123, 23, 114, 59, 189, 163, 204, 232,
+];
+
+// The type script of the Nervos DAO is the same on the mainnet and the testnet
+pub const DAO_TYPE_CODE_HASH: [u8; 32] = [
+    130, 215, 109, 27, 117, 254, 47, 217, 162, 125, 251, 170, 101, 160, 57, 34, 26, 56, 13, 118,
+    201, 38, 243, 120, 211, 248, 28, 243, 231, 225, 63, 46,
+];
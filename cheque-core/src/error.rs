@@ -0,0 +1,75 @@
+#[cfg(feature = "ckb-std")]
+use ckb_std::error::SysError;
+use core::convert::TryFrom;
+
+/// Error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i8)]
+pub enum Error {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    InvalidArgument = 5,
+    NoMatchedInputs,
+    WitnessSignatureWrong,
+    SenderCapacityNotSame,
+    WrongPubKey,
+    ClaimChequeInputSinceNotZero = 10,
+    WithdrawChequeInputSinceError,
+    Secp256k1,
+    NoMatchedSignature,
+    ChequeAssetNotMatched,
+    ForwardedChequeNotMatched,
+    OracleMilestoneNotReached,
+    ClaimChequeInputSinceTooEarly,
+    VestingCapacityNotMatched,
+    SubscriptionNotMatched,
+}
+
+// The exit code of the cheque lock script, so the off-chain callers get the error back from the
+// script runners
+impl TryFrom<i8> for Error {
+    type Error = i8;
+
+    fn try_from(code: i8) -> Result<Self, Self::Error> {
+        use Error::*;
+        let err = match code {
+            1 => IndexOutOfBound,
+            2 => ItemMissing,
+            3 => LengthNotEnough,
+            4 => Encoding,
+            5 => InvalidArgument,
+            6 => NoMatchedInputs,
+            7 => WitnessSignatureWrong,
+            8 => SenderCapacityNotSame,
+            9 => WrongPubKey,
+            10 => ClaimChequeInputSinceNotZero,
+            11 => WithdrawChequeInputSinceError,
+            12 => Secp256k1,
+            13 => NoMatchedSignature,
+            14 => ChequeAssetNotMatched,
+            15 => ForwardedChequeNotMatched,
+            16 => OracleMilestoneNotReached,
+            17 => ClaimChequeInputSinceTooEarly,
+            18 => VestingCapacityNotMatched,
+            19 => SubscriptionNotMatched,
+            _ => return Err(code),
+        };
+        Ok(err)
+    }
+}
+
+#[cfg(feature = "ckb-std")]
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        use SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            Encoding => Self::Encoding,
+            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! The on-chain rules of the cheque lock script shared by the contract, the simulator and the
+//! off-chain code, so they can never drift from each other.
+//!
+//! See `args.rs` for the lock args codec.
+//! See `error.rs` for the `Error` type.
+//! See `since.rs` for the since constants and the epoch helpers.
+//! See `code_hash.rs` for the code hashes of the scripts the cheque lock script depends on.
+//! See `witness.rs` for the claim actions and the layout of the cheque witness.
+//! See `otx.rs` for the OTX signature and its signing message.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod args;
+pub mod code_hash;
+pub mod error;
+pub mod hash;
+pub mod otx;
+pub mod since;
+pub mod witness;
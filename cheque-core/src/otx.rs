@@ -0,0 +1,74 @@
+use crate::args::ChequeArgs;
+use crate::hash;
+
+// The witness lock of an OTX signature is the mode byte followed by the 65 bytes signature
+pub const SIGHASH_OTX: u8 = 1;
+pub const OTX_SIGNATURE_LEN: usize = 66;
+
+pub fn is_otx_signature(witness_lock: &[u8]) -> bool {
+    witness_lock.len() == OTX_SIGNATURE_LEN && witness_lock[0] == SIGHASH_OTX
+}
+
+// The receiver binds the outputs of the receiver lock hash
+pub fn receiver_bound_lock_hashes(cheque_args: &ChequeArgs) -> [[u8; 20]; 1] {
+    [cheque_args.receiver_lock_hash]
+}
+
+// The sender binds the outputs of the sender and the refund lock hash
+pub fn sender_bound_lock_hashes(cheque_args: &ChequeArgs) -> [[u8; 20]; 2] {
+    [
+        cheque_args.sender_lock_hash,
+        *cheque_args.sender_refund_lock_hash(),
+    ]
+}
+
+// The OTX signature only signs the cheque inputs and the outputs bound to the signer, so the signed
+// cheque cells can be merged with other inputs and outputs into one transaction:
+//
+// message = blake2b_256(
+//     cheque_lock_script_hash(32 bytes)
+//     | group_inputs_count(u64 little endian)
+//     | for every cheque input: previous_output(36 bytes) | since(u64 little endian)
+//     | bound_outputs_count(u64 little endian)
+//     | for every bound output in order: cell_output(molecule) | data_len(u64 little endian) | data
+//     | input_type_len(u64 little endian) | input_type of the cheque witness
+// )
+//
+// The bound outputs are the outputs locked by the bound lock hashes of the signer, and the outputs
+// locked by the cheque script code with any lock args. The claims of several cheque cells of the
+// same sender do not bind the outputs of the sender, whose capacity is checked per cheque cell
+// instead, so they can be merged into one transaction. Likewise the assets and the capacity paid to
+// a bound lock hash are summed over every merged cheque cell paid to it, so the same bound output
+// cannot satisfy two merged groups.
+//
+// The inputs are the molecule previous_output and since of the group inputs, and the outputs are
+// the molecule cell_output and the data of the bound outputs.
+pub fn signing_message(
+    script_hash: &[u8],
+    inputs: &[(&[u8], &[u8])],
+    bound_outputs: &[(&[u8], &[u8])],
+    input_type: &[u8],
+) -> [u8; 32] {
+    let mut blake2b = hash::new_blake2b();
+    blake2b.update(script_hash);
+
+    blake2b.update(&(inputs.len() as u64).to_le_bytes());
+    for (previous_output, since) in inputs.iter() {
+        blake2b.update(previous_output);
+        blake2b.update(since);
+    }
+
+    blake2b.update(&(bound_outputs.len() as u64).to_le_bytes());
+    for (output, data) in bound_outputs.iter() {
+        blake2b.update(output);
+        blake2b.update(&(data.len() as u64).to_le_bytes());
+        blake2b.update(data);
+    }
+
+    blake2b.update(&(input_type.len() as u64).to_le_bytes());
+    blake2b.update(input_type);
+
+    let mut message = [0u8; 32];
+    blake2b.finalize(&mut message);
+    message
+}
@@ -0,0 +1,80 @@
+// The highest bit of since is the relative flag, and the next two bits are the metric flag
+pub const SINCE_RELATIVE_FLAG: u64 = 0x8000_0000_0000_0000;
+pub const SINCE_METRIC_MASK: u64 = 0x6000_0000_0000_0000;
+pub const SINCE_METRIC_EPOCH: u64 = 0x2000_0000_0000_0000;
+pub const SINCE_VALUE_MASK: u64 = 0x00FF_FFFF_FFFF_FFFF;
+
+// The default withdraw since of the cheque cells is the lock-up period of 6 relative epochs
+pub const CHEQUE_CELL_SINCE: u64 = 0xA000000000000006;
+
+use core::cmp::Ordering;
+
+// Epoch with fraction: number[0..24] | index[24..40] | length[40..56]
+const EPOCH_NUMBER_MASK: u64 = 0x00FF_FFFF;
+const EPOCH_INDEX_MASK: u64 = 0xFFFF;
+const EPOCH_LENGTH_MASK: u64 = 0xFFFF;
+
+pub fn epoch_number(epoch: u64) -> u64 {
+    epoch & EPOCH_NUMBER_MASK
+}
+
+fn epoch_index(epoch: u64) -> u64 {
+    (epoch >> 24) & EPOCH_INDEX_MASK
+}
+
+fn epoch_length(epoch: u64) -> u64 {
+    // The length of a whole epoch without fraction can be zero
+    match (epoch >> 40) & EPOCH_LENGTH_MASK {
+        0 => 1,
+        length => length,
+    }
+}
+
+// The epoch with fraction as a fixed point number in units of 1/65536 epoch
+pub fn epoch_to_fixed_point(epoch: u64) -> u64 {
+    (epoch_number(epoch) << 16) + (epoch_index(epoch) << 16) / epoch_length(epoch)
+}
+
+pub fn compare_epoch(epoch: u64, other: u64) -> Ordering {
+    match epoch_number(epoch).cmp(&epoch_number(other)) {
+        Ordering::Equal => (epoch_index(epoch) * epoch_length(other))
+            .cmp(&(epoch_index(other) * epoch_length(epoch))),
+        ordering => ordering,
+    }
+}
+
+// The since is not earlier than the required since when they have the same flags, so they are
+// measured by the same metric from the same starting point.
+pub fn is_since_not_earlier(since: u64, required_since: u64) -> bool {
+    if since & !SINCE_VALUE_MASK != required_since & !SINCE_VALUE_MASK {
+        return false;
+    }
+    let value = since & SINCE_VALUE_MASK;
+    let required_value = required_since & SINCE_VALUE_MASK;
+    if since & SINCE_METRIC_MASK == SINCE_METRIC_EPOCH {
+        compare_epoch(value, required_value) != Ordering::Less
+    } else {
+        value >= required_value
+    }
+}
+
+pub fn is_relative_epoch(since: u64) -> bool {
+    since & SINCE_RELATIVE_FLAG != 0 && since & SINCE_METRIC_MASK == SINCE_METRIC_EPOCH
+}
+
+// Convert a relative epoch since, whose fraction must be zero, into the absolute epoch since
+// counted from the epoch in which the cell was created.
+pub fn relative_epoch_to_absolute(since: u64, created_epoch: u64) -> Option<u64> {
+    if !is_relative_epoch(since) {
+        return None;
+    }
+    let epochs = since & SINCE_VALUE_MASK;
+    if epochs & !EPOCH_NUMBER_MASK != 0 {
+        return None;
+    }
+    let number = epoch_number(created_epoch).checked_add(epochs)?;
+    if number & !EPOCH_NUMBER_MASK != 0 {
+        return None;
+    }
+    Some(SINCE_METRIC_EPOCH | (created_epoch & !EPOCH_NUMBER_MASK & SINCE_VALUE_MASK) | number)
+}
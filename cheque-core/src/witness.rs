@@ -0,0 +1,23 @@
+use crate::args::SALT_LEN;
+
+// The first byte of the cheque witness input_type is the claim action
+pub const CLAIM_ACTION_NONE: u8 = 0;
+// The receiver forwards the cheque cells to a new receiver instead of unlocking them
+pub const CLAIM_ACTION_FORWARD: u8 = 1;
+// Two parties of the escrow release the cheque cells to the receiver
+pub const ESCROW_ACTION_RELEASE: u8 = 2;
+// Two parties of the escrow refund the cheque cells to the sender
+pub const ESCROW_ACTION_REFUND: u8 = 3;
+
+// The secp256k1 library of the cheque lock script recovers at most 16 signatures in one witness lock
+pub const MAX_SIGNATURES_COUNT: usize = 16;
+
+// The salt of the receiver follows the claim action in the input_type of the cheque witness
+pub fn parse_receiver_salt(input_type: &[u8]) -> Option<[u8; SALT_LEN]> {
+    if input_type.len() < 1 + SALT_LEN {
+        return None;
+    }
+    let mut salt = [0u8; SALT_LEN];
+    salt.copy_from_slice(&input_type[1..1 + SALT_LEN]);
+    Some(salt)
+}
@@ -0,0 +1,86 @@
+use cheque_core::args::ChequeArgs;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use cheque_core::witness::{CLAIM_ACTION_FORWARD, ESCROW_ACTION_REFUND, ESCROW_ACTION_RELEASE};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{Byte32, CellOutput, OutPoint, WitnessArgs},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+/// A cheque cell created on the indexed chain
+#[derive(Debug, Clone)]
+pub struct ChequeCell {
+    pub out_point:    OutPoint,
+    pub output:       CellOutput,
+    pub data:         Bytes,
+    pub cheque_args:  ChequeArgs,
+    pub block_number: u64,
+    // None while the cheque cell is live
+    pub spend:        Option<Spend>,
+}
+
+impl ChequeCell {
+    pub fn is_live(&self) -> bool {
+        self.spend.is_none()
+    }
+
+    // The receivers of a receiver group cheque are indexed one by one besides the commitment
+    pub(crate) fn receiver_lock_hashes(&self) -> Vec<[u8; 20]> {
+        let mut lock_hashes = vec![self.cheque_args.receiver_lock_hash];
+        if let Some(receiver_group) = &self.cheque_args.receiver_group {
+            lock_hashes.extend(receiver_group.receiver_lock_hashes.iter().copied());
+        }
+        lock_hashes
+    }
+}
+
+/// The transaction consuming a cheque cell
+#[derive(Debug, Clone)]
+pub struct Spend {
+    pub tx_hash:      Byte32,
+    pub block_number: u64,
+    pub kind:         SpendKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpendKind {
+    // The receiver claimed or forwarded the cheque cell, or the escrow released it to the
+    // receiver
+    Claim,
+    // The sender withdrew the cheque cell, or the escrow refunded it to the sender
+    Withdraw,
+}
+
+/// Classifies the spend of a cheque group by the claim action of the cheque witness, which is the
+/// witness of the first input of the group, or else by the since of the input, as the withdraw
+/// needs exactly the withdraw since of the lock args and the claim needs the since zero unless the
+/// claim since, vesting or subscription is set
+pub fn classify_spend(
+    tx: &TransactionView,
+    group_first_index: usize,
+    cheque_args: &ChequeArgs,
+) -> SpendKind {
+    let action = tx
+        .witnesses()
+        .get(group_first_index)
+        .and_then(|witness| WitnessArgs::from_slice(&witness.raw_data()).ok())
+        .and_then(|witness_args| witness_args.input_type().to_opt())
+        .and_then(|input_type| input_type.raw_data().first().copied());
+    match action {
+        Some(CLAIM_ACTION_FORWARD) | Some(ESCROW_ACTION_RELEASE) => SpendKind::Claim,
+        Some(ESCROW_ACTION_REFUND) => SpendKind::Withdraw,
+        _ => {
+            let since: u64 = tx
+                .inputs()
+                .get(group_first_index)
+                .map_or(0, |input| input.since().unpack());
+            if since == cheque_args.withdraw_since.unwrap_or(CHEQUE_CELL_SINCE) {
+                SpendKind::Withdraw
+            } else {
+                SpendKind::Claim
+            }
+        }
+    }
+}
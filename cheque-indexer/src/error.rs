@@ -0,0 +1,45 @@
+use core::fmt;
+
+/// Error
+#[derive(Debug)]
+pub enum Error {
+    // The embedded store cannot be read or written
+    Store(sled::Error),
+    // A block dump cannot be read
+    Io(std::io::Error),
+    // A block dump or a stored record is not valid JSON
+    Json(serde_json::Error),
+    // The block does not follow the tip of the indexer, so the indexer must roll back first
+    BlockNotLinked,
+}
+
+impl From<sled::Error> for Error {
+    fn from(err: sled::Error) -> Self {
+        Self::Store(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Store(err) => write!(f, "store error: {}", err),
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Json(err) => write!(f, "json error: {}", err),
+            Self::BlockNotLinked => write!(f, "block not linked to the tip"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,296 @@
+use super::cheque::{classify_spend, ChequeCell, Spend};
+use super::error::Error;
+use super::source::BlockSource;
+use super::store::{
+    block_key, cheque_key, decode, encode, lock_hash_key, StoredBlock, StoredCheque, PREFIX_BLOCK,
+    PREFIX_RECEIVER, PREFIX_SENDER,
+};
+use cheque_core::args::ChequeArgs;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{BlockView, ScriptHashType},
+    packed::{Byte, Byte32, OutPoint},
+    prelude::*,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Tracks the cheque cells of the cheque lock script on a chain of blocks in a sled store.
+///
+/// The blocks are appended one by one from the start number, and the undo record of every block is
+/// kept, so the indexer can roll back to any block after a reorg. The cells of the cheque lock
+/// script whose lock args cannot be parsed are never unlocked and not indexed.
+pub struct Indexer {
+    db:               sled::Db,
+    cheque_code_hash: Byte32,
+    cheque_hash_type: Byte,
+    start_number:     u64,
+}
+
+impl Indexer {
+    pub fn new(db: sled::Db, cheque_code_hash: Byte32, cheque_hash_type: ScriptHashType) -> Self {
+        Indexer {
+            db,
+            cheque_code_hash,
+            cheque_hash_type: cheque_hash_type.into(),
+            start_number: 0,
+        }
+    }
+
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        cheque_code_hash: Byte32,
+        cheque_hash_type: ScriptHashType,
+    ) -> Result<Self, Error> {
+        Ok(Self::new(
+            sled::open(path)?,
+            cheque_code_hash,
+            cheque_hash_type,
+        ))
+    }
+
+    // The block before the deployment of the cheque lock script has no cheque cells
+    pub fn start_number(mut self, start_number: u64) -> Self {
+        self.start_number = start_number;
+        self
+    }
+
+    /// The number and the hash of the last indexed block
+    pub fn tip(&self) -> Result<Option<(u64, Byte32)>, Error> {
+        match self.db.scan_prefix([PREFIX_BLOCK]).next_back() {
+            Some(entry) => {
+                let (key, value) = entry?;
+                let mut number = [0u8; 8];
+                number.copy_from_slice(&key[1..]);
+                let block: StoredBlock = decode(&value)?;
+                Ok(Some((u64::from_be_bytes(number), block.block_hash())))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Indexes the next block, which must be the child of the tip
+    pub fn append(&self, block: &BlockView) -> Result<(), Error> {
+        match self.tip()? {
+            Some((number, hash)) => {
+                if block.number() != number + 1 || block.parent_hash() != hash {
+                    return Err(Error::BlockNotLinked);
+                }
+            }
+            None => {
+                if block.number() != self.start_number {
+                    return Err(Error::BlockNotLinked);
+                }
+            }
+        }
+
+        // The cheque cells created in this block can be spent by the later transactions of it
+        let mut created: HashMap<OutPoint, ChequeCell> = HashMap::new();
+        let mut created_out_points = Vec::new();
+        let mut spent: HashMap<OutPoint, ChequeCell> = HashMap::new();
+        let mut spent_out_points = Vec::new();
+        for tx in block.transactions() {
+            let mut groups: Vec<(Bytes, usize)> = Vec::new();
+            for (index, input) in tx.inputs().into_iter().enumerate() {
+                let out_point = input.previous_output();
+                let cheque = match created.get_mut(&out_point) {
+                    Some(cheque) => cheque,
+                    None => match self.cheque(&out_point)? {
+                        Some(cheque) if cheque.is_live() => {
+                            spent_out_points.push(out_point.clone());
+                            spent.entry(out_point.clone()).or_insert(cheque)
+                        }
+                        _ => continue,
+                    },
+                };
+                let lock_args = cheque.output.lock().args().raw_data();
+                let group_first_index = match groups.iter().find(|(args, _)| *args == lock_args) {
+                    Some((_, first_index)) => *first_index,
+                    None => {
+                        groups.push((lock_args, index));
+                        index
+                    }
+                };
+                cheque.spend = Some(Spend {
+                    tx_hash:      tx.hash(),
+                    block_number: block.number(),
+                    kind:         classify_spend(&tx, group_first_index, &cheque.cheque_args),
+                });
+            }
+
+            for (index, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+                let lock = output.lock();
+                if lock.code_hash().as_slice() != self.cheque_code_hash.as_slice()
+                    || lock.hash_type().as_slice() != self.cheque_hash_type.as_slice()
+                {
+                    continue;
+                }
+                let cheque_args = match ChequeArgs::from_slice(&lock.args().raw_data()) {
+                    Ok(cheque_args) => cheque_args,
+                    Err(_) => continue,
+                };
+                let out_point = OutPoint::new(tx.hash(), index as u32);
+                created_out_points.push(out_point.clone());
+                created.insert(out_point.clone(), ChequeCell {
+                    out_point,
+                    output,
+                    data,
+                    cheque_args,
+                    block_number: block.number(),
+                    spend: None,
+                });
+            }
+        }
+
+        let mut batch = sled::Batch::default();
+        for cheque in created.values() {
+            self.insert_cheque(&mut batch, cheque)?;
+        }
+        for cheque in spent.values() {
+            batch.insert(
+                cheque_key(&cheque.out_point),
+                encode(&StoredCheque::from(cheque))?,
+            );
+        }
+        let stored_block = StoredBlock {
+            hash:    block.hash().unpack(),
+            created: created_out_points.into_iter().map(Into::into).collect(),
+            spent:   spent_out_points.into_iter().map(Into::into).collect(),
+        };
+        batch.insert(block_key(block.number()), encode(&stored_block)?);
+        self.db.apply_batch(batch)?;
+        Ok(())
+    }
+
+    /// Removes the tip block, deleting the cheque cells it created and reviving the cheque cells it
+    /// spent, and returns the number of the removed block
+    pub fn rollback(&self) -> Result<Option<u64>, Error> {
+        let number = match self.tip()? {
+            Some((number, _)) => number,
+            None => return Ok(None),
+        };
+        let stored_block: StoredBlock = match self.db.get(block_key(number))? {
+            Some(value) => decode(&value)?,
+            None => return Ok(None),
+        };
+
+        let mut batch = sled::Batch::default();
+        for out_point in stored_block.created {
+            if let Some(cheque) = self.cheque(&out_point.into())? {
+                self.remove_cheque(&mut batch, &cheque);
+            }
+        }
+        for out_point in stored_block.spent {
+            if let Some(mut cheque) = self.cheque(&out_point.into())? {
+                cheque.spend = None;
+                batch.insert(
+                    cheque_key(&cheque.out_point),
+                    encode(&StoredCheque::from(&cheque))?,
+                );
+            }
+        }
+        batch.remove(block_key(number));
+        self.db.apply_batch(batch)?;
+        Ok(Some(number))
+    }
+
+    /// Follows the source to its tip, rolling back the blocks which are no longer in the source,
+    /// and returns the number of the tip
+    pub fn sync<S: BlockSource>(&self, source: &mut S) -> Result<Option<u64>, Error> {
+        loop {
+            match self.tip()? {
+                Some((number, hash)) => match source.block(number + 1)? {
+                    Some(block) if block.parent_hash() == hash => self.append(&block)?,
+                    Some(_) => {
+                        self.rollback()?;
+                    }
+                    // The source may also be reorganized to a shorter chain
+                    None => match source.block(number)? {
+                        Some(block) if block.hash() == hash => return Ok(Some(number)),
+                        _ => {
+                            self.rollback()?;
+                        }
+                    },
+                },
+                None => match source.block(self.start_number)? {
+                    Some(block) => self.append(&block)?,
+                    None => return Ok(None),
+                },
+            }
+        }
+    }
+
+    pub fn cheque(&self, out_point: &OutPoint) -> Result<Option<ChequeCell>, Error> {
+        match self.db.get(cheque_key(out_point))? {
+            Some(value) => Ok(Some(decode::<StoredCheque>(&value)?.into_cheque()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The cheque cells sent to the lock hash, including the members of the receiver groups,
+    /// in the order of their out points
+    pub fn cheques_by_receiver(&self, lock_hash: &[u8; 20]) -> Result<Vec<ChequeCell>, Error> {
+        self.cheques_by_lock_hash(PREFIX_RECEIVER, lock_hash)
+    }
+
+    /// The cheque cells sent from the lock hash in the order of their out points
+    pub fn cheques_by_sender(&self, lock_hash: &[u8; 20]) -> Result<Vec<ChequeCell>, Error> {
+        self.cheques_by_lock_hash(PREFIX_SENDER, lock_hash)
+    }
+
+    fn cheques_by_lock_hash(
+        &self,
+        prefix: u8,
+        lock_hash: &[u8; 20],
+    ) -> Result<Vec<ChequeCell>, Error> {
+        let key_prefix = lock_hash_key(prefix, lock_hash, None);
+        let mut cheques = Vec::new();
+        for entry in self.db.scan_prefix(&key_prefix) {
+            let (key, _) = entry?;
+            let out_point =
+                OutPoint::new_unchecked(Bytes::copy_from_slice(&key[key_prefix.len()..]));
+            if let Some(cheque) = self.cheque(&out_point)? {
+                cheques.push(cheque);
+            }
+        }
+        Ok(cheques)
+    }
+
+    fn insert_cheque(&self, batch: &mut sled::Batch, cheque: &ChequeCell) -> Result<(), Error> {
+        batch.insert(
+            cheque_key(&cheque.out_point),
+            encode(&StoredCheque::from(cheque))?,
+        );
+        for lock_hash in cheque.receiver_lock_hashes() {
+            batch.insert(
+                lock_hash_key(PREFIX_RECEIVER, &lock_hash, Some(&cheque.out_point)),
+                Vec::new(),
+            );
+        }
+        batch.insert(
+            lock_hash_key(
+                PREFIX_SENDER,
+                &cheque.cheque_args.sender_lock_hash,
+                Some(&cheque.out_point),
+            ),
+            Vec::new(),
+        );
+        Ok(())
+    }
+
+    fn remove_cheque(&self, batch: &mut sled::Batch, cheque: &ChequeCell) {
+        batch.remove(cheque_key(&cheque.out_point));
+        for lock_hash in cheque.receiver_lock_hashes() {
+            batch.remove(lock_hash_key(
+                PREFIX_RECEIVER,
+                &lock_hash,
+                Some(&cheque.out_point),
+            ));
+        }
+        batch.remove(lock_hash_key(
+            PREFIX_SENDER,
+            &cheque.cheque_args.sender_lock_hash,
+            Some(&cheque.out_point),
+        ));
+    }
+}
@@ -0,0 +1,21 @@
+//! A local indexer of the cheque cells, which follows a chain of blocks from a block source and
+//! keeps the cheque cells sent to or from every lock hash, live or spent, in an embedded store.
+//!
+//! The spends are classified as claims or withdrawals, and the blocks are rolled back on reorgs.
+//!
+//! See `source.rs` for the block sources, including the JSON block dumps.
+//! See `indexer.rs` for the `Indexer`.
+//! See `cheque.rs` for the indexed cheque cells and the spend classification.
+//! See `store.rs` for the key layout of the store.
+//! See `error.rs` for the `Error` type.
+
+pub mod cheque;
+pub mod error;
+pub mod indexer;
+pub mod source;
+mod store;
+
+pub use cheque::{ChequeCell, Spend, SpendKind};
+pub use error::Error;
+pub use indexer::Indexer;
+pub use source::{BlockSource, JsonBlockSource};
@@ -0,0 +1,43 @@
+use super::error::Error;
+use ckb_jsonrpc_types::BlockView as JsonBlockView;
+use ckb_tool::ckb_types::core::BlockView;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// The blocks of the canonical chain by number, which may change after a reorg
+pub trait BlockSource {
+    // None if the block is beyond the tip of the source
+    fn block(&mut self, number: u64) -> Result<Option<BlockView>, Error>;
+}
+
+/// The block dumps of a directory, where the file `<number>.json` holds the result of the
+/// `get_block_by_number` RPC
+pub struct JsonBlockSource {
+    dir: PathBuf,
+}
+
+impl JsonBlockSource {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        JsonBlockSource { dir: dir.into() }
+    }
+}
+
+impl BlockSource for JsonBlockSource {
+    fn block(&mut self, number: u64) -> Result<Option<BlockView>, Error> {
+        let path = self.dir.join(format!("{}.json", number));
+        let json = match fs::read(path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let block: JsonBlockView = serde_json::from_slice(&json)?;
+        Ok(Some(block.into()))
+    }
+}
+
+impl BlockSource for Vec<BlockView> {
+    fn block(&mut self, number: u64) -> Result<Option<BlockView>, Error> {
+        Ok(self.iter().find(|block| block.number() == number).cloned())
+    }
+}
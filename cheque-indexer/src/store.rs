@@ -0,0 +1,119 @@
+use super::cheque::{ChequeCell, Spend, SpendKind};
+use super::error::Error;
+use cheque_core::args::ChequeArgs;
+use ckb_jsonrpc_types::{
+    CellOutput as JsonCellOutput, JsonBytes, OutPoint as JsonOutPoint, Uint64,
+};
+use ckb_tool::ckb_types::{
+    packed::{Byte32, OutPoint},
+    prelude::*,
+    H256,
+};
+use serde::{Deserialize, Serialize};
+
+// All the records share one tree under the key prefixes, so every block is applied or rolled back
+// by one atomic batch:
+//   cheque:   PREFIX_CHEQUE | out_point => StoredCheque
+//   receiver: PREFIX_RECEIVER | receiver_lock_hash | out_point => empty
+//   sender:   PREFIX_SENDER | sender_lock_hash | out_point => empty
+//   block:    PREFIX_BLOCK | block_number(big endian) => StoredBlock
+pub const PREFIX_CHEQUE: u8 = 0;
+pub const PREFIX_RECEIVER: u8 = 1;
+pub const PREFIX_SENDER: u8 = 2;
+pub const PREFIX_BLOCK: u8 = 3;
+
+pub fn cheque_key(out_point: &OutPoint) -> Vec<u8> {
+    let mut key = vec![PREFIX_CHEQUE];
+    key.extend_from_slice(out_point.as_slice());
+    key
+}
+
+pub fn lock_hash_key(prefix: u8, lock_hash: &[u8; 20], out_point: Option<&OutPoint>) -> Vec<u8> {
+    let mut key = vec![prefix];
+    key.extend_from_slice(lock_hash);
+    if let Some(out_point) = out_point {
+        key.extend_from_slice(out_point.as_slice());
+    }
+    key
+}
+
+pub fn block_key(number: u64) -> Vec<u8> {
+    let mut key = vec![PREFIX_BLOCK];
+    key.extend_from_slice(&number.to_be_bytes());
+    key
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StoredSpend {
+    tx_hash:      H256,
+    block_number: Uint64,
+    kind:         SpendKind,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StoredCheque {
+    out_point:    JsonOutPoint,
+    output:       JsonCellOutput,
+    output_data:  JsonBytes,
+    block_number: Uint64,
+    spend:        Option<StoredSpend>,
+}
+
+/// The undo record of a block, which keeps the cheque cells created and spent by the block
+#[derive(Serialize, Deserialize)]
+pub struct StoredBlock {
+    pub hash:    H256,
+    pub created: Vec<JsonOutPoint>,
+    pub spent:   Vec<JsonOutPoint>,
+}
+
+impl StoredBlock {
+    pub fn block_hash(&self) -> Byte32 {
+        self.hash.pack()
+    }
+}
+
+impl From<&ChequeCell> for StoredCheque {
+    fn from(cheque: &ChequeCell) -> Self {
+        StoredCheque {
+            out_point:    cheque.out_point.clone().into(),
+            output:       cheque.output.clone().into(),
+            output_data:  JsonBytes::from_bytes(cheque.data.clone()),
+            block_number: cheque.block_number.into(),
+            spend:        cheque.spend.as_ref().map(|spend| StoredSpend {
+                tx_hash:      spend.tx_hash.unpack(),
+                block_number: spend.block_number.into(),
+                kind:         spend.kind,
+            }),
+        }
+    }
+}
+
+impl StoredCheque {
+    // The lock args were parsed before the cheque cell was stored
+    pub fn into_cheque(self) -> Result<ChequeCell, Error> {
+        let output: ckb_tool::ckb_types::packed::CellOutput = self.output.into();
+        let cheque_args = ChequeArgs::from_slice(&output.lock().args().raw_data())
+            .map_err(|_| Error::Json(serde::de::Error::custom("invalid cheque lock args")))?;
+        Ok(ChequeCell {
+            out_point: self.out_point.into(),
+            output,
+            data: self.output_data.into_bytes(),
+            cheque_args,
+            block_number: self.block_number.value(),
+            spend: self.spend.map(|spend| Spend {
+                tx_hash:      spend.tx_hash.pack(),
+                block_number: spend.block_number.value(),
+                kind:         spend.kind,
+            }),
+        })
+    }
+}
+
+pub fn encode<T: Serialize>(record: &T) -> Result<Vec<u8>, Error> {
+    Ok(serde_json::to_vec(record)?)
+}
+
+pub fn decode<'a, T: Deserialize<'a>>(value: &'a [u8]) -> Result<T, Error> {
+    Ok(serde_json::from_slice(value)?)
+}
@@ -0,0 +1,118 @@
+use super::error::Error;
+use bech32::{FromBase32, ToBase32, Variant};
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{Byte, Script},
+    prelude::*,
+};
+
+const PREFIX_MAINNET: &str = "ckb";
+const PREFIX_TESTNET: &str = "ckt";
+
+// The payload of the address starts with its format, see the RFC of the CKB address format
+const ADDRESS_FORMAT_FULL: u8 = 0x00;
+const ADDRESS_FORMAT_SHORT: u8 = 0x01;
+const ADDRESS_FORMAT_FULL_DATA: u8 = 0x02;
+const ADDRESS_FORMAT_FULL_TYPE: u8 = 0x04;
+
+// The short address only refers to the system lock scripts which are the same on the mainnet and
+// the testnet
+const CODE_HASH_INDEX_SECP256K1_BLAKE160: u8 = 0x00;
+const CODE_HASH_INDEX_SECP256K1_MULTISIG: u8 = 0x01;
+const CODE_HASH_SECP256K1_MULTISIG: [u8; 32] = [
+    92, 80, 105, 235, 8, 87, 239, 198, 94, 27, 202, 12, 7, 223, 52, 195, 22, 99, 179, 98, 47, 211,
+    135, 108, 135, 99, 32, 252, 150, 52, 226, 168,
+];
+const SHORT_ARGS_LEN: usize = 20;
+
+const CODE_HASH_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    fn prefix(self) -> &'static str {
+        match self {
+            Network::Mainnet => PREFIX_MAINNET,
+            Network::Testnet => PREFIX_TESTNET,
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            PREFIX_MAINNET => Some(Network::Mainnet),
+            PREFIX_TESTNET => Some(Network::Testnet),
+            _ => None,
+        }
+    }
+}
+
+fn build_script(code_hash: &[u8], hash_type: u8, args: &[u8]) -> Script {
+    let mut code_hash_buf = [0u8; CODE_HASH_LEN];
+    code_hash_buf.copy_from_slice(code_hash);
+    Script::new_builder()
+        .code_hash(code_hash_buf.pack())
+        .hash_type(Byte::new(hash_type))
+        .args(Bytes::copy_from_slice(args).pack())
+        .build()
+}
+
+/// Parses the lock script of a full or short CKB address of the mainnet or the testnet
+pub fn parse_address(address: &str) -> Result<(Network, Script), Error> {
+    let (prefix, data, variant) = bech32::decode(address).map_err(|_| Error::InvalidAddress)?;
+    let network = Network::from_prefix(&prefix).ok_or(Error::InvalidAddress)?;
+    let payload = Vec::<u8>::from_base32(&data).map_err(|_| Error::InvalidAddress)?;
+    let (format, body) = payload.split_first().ok_or(Error::InvalidAddress)?;
+
+    let script = match (*format, variant) {
+        // code_hash | hash_type | args
+        (ADDRESS_FORMAT_FULL, Variant::Bech32m) if body.len() > CODE_HASH_LEN => {
+            let hash_type = body[CODE_HASH_LEN];
+            if hash_type > HASH_TYPE_TYPE {
+                return Err(Error::InvalidAddress);
+            }
+            build_script(
+                &body[0..CODE_HASH_LEN],
+                hash_type,
+                &body[CODE_HASH_LEN + 1..],
+            )
+        }
+        // code_hash_index | args
+        (ADDRESS_FORMAT_SHORT, Variant::Bech32) if body.len() == 1 + SHORT_ARGS_LEN => {
+            let code_hash = match body[0] {
+                CODE_HASH_INDEX_SECP256K1_BLAKE160 => CODE_HASH_SECP256K1_BLAKE160,
+                CODE_HASH_INDEX_SECP256K1_MULTISIG => CODE_HASH_SECP256K1_MULTISIG,
+                _ => return Err(Error::InvalidAddress),
+            };
+            build_script(&code_hash, HASH_TYPE_TYPE, &body[1..])
+        }
+        // code_hash | args
+        (ADDRESS_FORMAT_FULL_DATA, Variant::Bech32) if body.len() >= CODE_HASH_LEN => build_script(
+            &body[0..CODE_HASH_LEN],
+            ScriptHashType::Data as u8,
+            &body[CODE_HASH_LEN..],
+        ),
+        (ADDRESS_FORMAT_FULL_TYPE, Variant::Bech32) if body.len() >= CODE_HASH_LEN => build_script(
+            &body[0..CODE_HASH_LEN],
+            ScriptHashType::Type as u8,
+            &body[CODE_HASH_LEN..],
+        ),
+        _ => return Err(Error::InvalidAddress),
+    };
+    Ok((network, script))
+}
+
+/// Encodes the lock script as a full CKB address, which every lock script has
+pub fn encode_full_address(network: Network, lock_script: &Script) -> String {
+    let mut payload = vec![ADDRESS_FORMAT_FULL];
+    payload.extend_from_slice(lock_script.code_hash().as_slice());
+    payload.extend_from_slice(lock_script.hash_type().as_slice());
+    payload.extend_from_slice(&lock_script.args().raw_data());
+    bech32::encode(network.prefix(), payload.to_base32(), Variant::Bech32m)
+        .expect("the prefix is valid")
+}
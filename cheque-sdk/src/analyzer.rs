@@ -0,0 +1,468 @@
+use super::error::Error;
+use super::helper;
+use super::script::ChequeScript;
+use super::signer::{
+    load_receiver_salt, lock_script_groups, parse_witness_args, secp256k1_lock_script,
+    sighash_all_message, ScriptGroup,
+};
+use super::witness::SIGNATURE_LEN;
+use cheque_core::args::{salted_lock_hash, ChequeArgs, FLAG_CAPACITY, FLAG_SALTED, SALT_LEN};
+use cheque_core::error::Error as ChequeError;
+use cheque_core::hash;
+use cheque_core::otx::{
+    self, is_otx_signature, receiver_bound_lock_hashes, sender_bound_lock_hashes,
+};
+use cheque_core::witness::{
+    CLAIM_ACTION_NONE, ESCROW_ACTION_REFUND, ESCROW_ACTION_RELEASE, MAX_SIGNATURES_COUNT,
+};
+use ckb_tool::ckb_crypto::secp::Signature;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{Byte, CellOutput},
+    prelude::*,
+    H256,
+};
+
+/// The branch of `entry::main` which unlocks a cheque group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnlockBranch {
+    ReceiverInput,
+    ReceiverSignature,
+    SenderInput,
+    SenderSignature,
+    // Two parties of the escrow release the cheque cells by their inputs or signatures
+    EscrowRelease,
+    // Two parties of the escrow refund the cheque cells by their inputs or signatures
+    EscrowRefund,
+}
+
+/// The capacities compared by `claim::check_sender_cells_capacity_same`, where the sender gets
+/// back exactly the capacity of the cheque cells not paid to the receiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SenderCapacityFlow {
+    pub refund_lock_hash:        [u8; 20],
+    pub sender_inputs_capacity:  u64,
+    // The capacity returned to the sender by every cheque input of the refund lock hash, which
+    // excludes the capacity paid to the receiver with the FLAG_CAPACITY and the DAO deposits
+    pub cheque_inputs_capacity:  u64,
+    pub sender_outputs_capacity: u64,
+}
+
+impl SenderCapacityFlow {
+    pub fn is_same(&self) -> bool {
+        self.sender_inputs_capacity
+            .checked_add(self.cheque_inputs_capacity)
+            .map_or(false, |capacity| capacity == self.sender_outputs_capacity)
+    }
+}
+
+/// How a cheque group of a transaction was unlocked
+#[derive(Debug, Clone)]
+pub struct ChequeSpend {
+    pub cheque_args:            ChequeArgs,
+    pub input_indices:          Vec<usize>,
+    // The first byte of the input_type of the cheque witness
+    pub action:                 Option<u8>,
+    // The error of `entry::main` if neither party authorized the cheque group
+    pub branch:                 Result<UnlockBranch, ChequeError>,
+    // The revealed receiver of a salted cheque, the first authorizing receiver of a receiver
+    // group, the sender, or the authorizing parties of the escrow
+    pub authorized_lock_hashes: Vec<[u8; 20]>,
+    // Only the claims with the action none and the escrow releases check the sender capacity
+    pub capacity_flow:          Option<SenderCapacityFlow>,
+}
+
+/// Reproduces the authorization of `entry::main` for every cheque group of a transaction whose
+/// input cells are given in the order of the inputs.
+///
+/// Only the branch and the capacity flow are analyzed, so the spend of a committed transaction is
+/// labeled, while the other rules such as the since and the assets are left to the contract.
+pub fn analyze_cheque_spends(
+    cheque_script: &ChequeScript,
+    tx: &TransactionView,
+    input_cells: &[CellOutput],
+) -> Result<Vec<ChequeSpend>, Error> {
+    if input_cells.len() != tx.inputs().len() {
+        return Err(Error::InputsNotMatched);
+    }
+    let cheque_hash_type: Byte = cheque_script.hash_type.into();
+    let input_lock_hashes = input_cells
+        .iter()
+        .map(|cell| helper::lock_hash_160(&cell.lock()))
+        .collect::<Vec<_>>();
+
+    let mut spends = Vec::new();
+    for group in lock_script_groups(input_cells) {
+        let lock_script = &group.lock_script;
+        if lock_script.code_hash().as_slice() != cheque_script.code_hash.as_slice()
+            || lock_script.hash_type().as_slice() != cheque_hash_type.as_slice()
+        {
+            continue;
+        }
+        let cheque_args = ChequeArgs::from_slice(&lock_script.args().raw_data())?;
+        let analyzer = GroupAnalyzer {
+            tx,
+            input_cells,
+            input_lock_hashes: &input_lock_hashes,
+            group: &group,
+            cheque_args: &cheque_args,
+        };
+        spends.push(analyzer.analyze()?);
+    }
+    Ok(spends)
+}
+
+struct GroupAnalyzer<'a> {
+    tx:                &'a TransactionView,
+    input_cells:       &'a [CellOutput],
+    input_lock_hashes: &'a [[u8; 20]],
+    group:             &'a ScriptGroup,
+    cheque_args:       &'a ChequeArgs,
+}
+
+impl<'a> GroupAnalyzer<'a> {
+    fn analyze(&self) -> Result<ChequeSpend, Error> {
+        let witnesses = self.witnesses();
+        let first_witness = &witnesses[self.group.input_indices[0]];
+        // A witness which is not a WitnessArgs has no lock, like the contract treats it
+        let witness_args = parse_witness_args(first_witness).unwrap_or_default();
+        let input_type: Bytes = witness_args
+            .input_type()
+            .to_opt()
+            .map_or_else(Bytes::new, |input_type| input_type.unpack());
+        let witness_lock: Option<Bytes> = witness_args.lock().to_opt().map(|lock| lock.unpack());
+        let receiver_salt = load_receiver_salt(self.cheque_args, first_witness).unwrap_or(None);
+
+        let (branch, authorized_lock_hashes) = match self.cheque_args.arbiter_lock_hash {
+            Some(arbiter_lock_hash) => {
+                self.escrow_branch(&arbiter_lock_hash, &witness_lock, &input_type, &witnesses)
+            }
+            None => match &witness_lock {
+                None => self.input_branch(&receiver_salt),
+                Some(witness_lock) => {
+                    self.signature_branch(witness_lock, &receiver_salt, &input_type, &witnesses)
+                }
+            },
+        };
+
+        let action = input_type.first().copied();
+        let checks_sender_capacity = match branch {
+            Ok(UnlockBranch::ReceiverInput) | Ok(UnlockBranch::ReceiverSignature) => {
+                action.map_or(true, |action| action == CLAIM_ACTION_NONE)
+            }
+            Ok(UnlockBranch::EscrowRelease) => true,
+            _ => false,
+        };
+        let capacity_flow = if checks_sender_capacity {
+            Some(self.sender_capacity_flow()?)
+        } else {
+            None
+        };
+
+        Ok(ChequeSpend {
+            cheque_args: self.cheque_args.clone(),
+            input_indices: self.group.input_indices.clone(),
+            action,
+            branch,
+            authorized_lock_hashes,
+            capacity_flow,
+        })
+    }
+
+    fn witnesses(&self) -> Vec<Bytes> {
+        let mut witnesses: Vec<Bytes> = self
+            .tx
+            .witnesses()
+            .into_iter()
+            .map(|witness| witness.unpack())
+            .collect();
+        let first_index = self.group.input_indices[0];
+        if witnesses.len() <= first_index {
+            witnesses.resize(first_index + 1, Bytes::new());
+        }
+        witnesses
+    }
+
+    fn match_receiver_lock_hash(
+        &self,
+        lock_hash: &[u8; 20],
+        salt: &Option<[u8; SALT_LEN]>,
+    ) -> bool {
+        if !self.cheque_args.has_flag(FLAG_SALTED) {
+            return &self.cheque_args.receiver_lock_hash == lock_hash;
+        }
+        match salt {
+            Some(salt) => self.cheque_args.receiver_lock_hash == salted_lock_hash(lock_hash, salt),
+            None => false,
+        }
+    }
+
+    // The receivers or the sender provide their inputs when the cheque witness has no lock
+    fn input_branch(
+        &self,
+        receiver_salt: &Option<[u8; SALT_LEN]>,
+    ) -> (Result<UnlockBranch, ChequeError>, Vec<[u8; 20]>) {
+        let has_input = |lock_hash: &[u8; 20]| self.input_lock_hashes.contains(lock_hash);
+        let receiver_lock_hash = match &self.cheque_args.receiver_group {
+            Some(receiver_group) => receiver_group.match_authorized(has_input),
+            None => self
+                .input_lock_hashes
+                .iter()
+                .find(|lock_hash| self.match_receiver_lock_hash(lock_hash, receiver_salt))
+                .copied(),
+        };
+        if let Some(receiver_lock_hash) = receiver_lock_hash {
+            (Ok(UnlockBranch::ReceiverInput), vec![receiver_lock_hash])
+        } else if has_input(&self.cheque_args.sender_lock_hash) {
+            (Ok(UnlockBranch::SenderInput), vec![
+                self.cheque_args.sender_lock_hash,
+            ])
+        } else {
+            (Err(ChequeError::NoMatchedInputs), Vec::new())
+        }
+    }
+
+    fn signature_branch(
+        &self,
+        witness_lock: &Bytes,
+        receiver_salt: &Option<[u8; SALT_LEN]>,
+        input_type: &Bytes,
+        witnesses: &[Bytes],
+    ) -> (Result<UnlockBranch, ChequeError>, Vec<[u8; 20]>) {
+        if let Some(receiver_group) = &self.cheque_args.receiver_group {
+            let signer_lock_hashes = match self.recover_signers(witness_lock, witnesses) {
+                Some(signer_lock_hashes) => signer_lock_hashes,
+                None => return (Err(ChequeError::NoMatchedSignature), Vec::new()),
+            };
+            let receiver_lock_hash =
+                receiver_group.match_authorized(|lock_hash| signer_lock_hashes.contains(lock_hash));
+            return if let Some(receiver_lock_hash) = receiver_lock_hash {
+                (Ok(UnlockBranch::ReceiverSignature), vec![
+                    receiver_lock_hash,
+                ])
+            } else if signer_lock_hashes == [self.cheque_args.sender_lock_hash] {
+                (Ok(UnlockBranch::SenderSignature), signer_lock_hashes)
+            } else {
+                (Err(ChequeError::NoMatchedSignature), Vec::new())
+            };
+        }
+
+        let signer_lock_hash = if is_otx_signature(witness_lock) {
+            self.recover_otx_signer(witness_lock, input_type)
+        } else if witness_lock.len() == SIGNATURE_LEN {
+            self.recover_signers(witness_lock, witnesses)
+                .and_then(|signer_lock_hashes| signer_lock_hashes.first().copied())
+        } else {
+            None
+        };
+        match signer_lock_hash {
+            Some(lock_hash) if self.match_receiver_lock_hash(&lock_hash, receiver_salt) => {
+                (Ok(UnlockBranch::ReceiverSignature), vec![lock_hash])
+            }
+            Some(lock_hash) if lock_hash == self.cheque_args.sender_lock_hash => {
+                (Ok(UnlockBranch::SenderSignature), vec![lock_hash])
+            }
+            _ => (Err(ChequeError::NoMatchedSignature), Vec::new()),
+        }
+    }
+
+    // Any two of the sender, the receiver and the arbiter release or refund the escrow, and the
+    // sender alone can still withdraw it
+    fn escrow_branch(
+        &self,
+        arbiter_lock_hash: &[u8; 20],
+        witness_lock: &Option<Bytes>,
+        input_type: &Bytes,
+        witnesses: &[Bytes],
+    ) -> (Result<UnlockBranch, ChequeError>, Vec<[u8; 20]>) {
+        let (authorized_lock_hashes, not_matched_error) = match witness_lock {
+            None => (
+                self.input_lock_hashes.to_vec(),
+                ChequeError::NoMatchedInputs,
+            ),
+            Some(witness_lock) => match self.recover_signers(witness_lock, witnesses) {
+                Some(signer_lock_hashes) => (signer_lock_hashes, ChequeError::NoMatchedSignature),
+                None => return (Err(ChequeError::NoMatchedSignature), Vec::new()),
+            },
+        };
+        let authorized_parties = [
+            self.cheque_args.sender_lock_hash,
+            self.cheque_args.receiver_lock_hash,
+            *arbiter_lock_hash,
+        ]
+        .iter()
+        .filter(|lock_hash| authorized_lock_hashes.contains(lock_hash))
+        .copied()
+        .collect::<Vec<_>>();
+
+        match input_type.first() {
+            Some(&action) if action == ESCROW_ACTION_RELEASE || action == ESCROW_ACTION_REFUND => {
+                if authorized_parties.len() < 2 {
+                    (Err(not_matched_error), Vec::new())
+                } else if action == ESCROW_ACTION_RELEASE {
+                    (Ok(UnlockBranch::EscrowRelease), authorized_parties)
+                } else {
+                    (Ok(UnlockBranch::EscrowRefund), authorized_parties)
+                }
+            }
+            _ => {
+                let sender_lock_hash = self.cheque_args.sender_lock_hash;
+                if !authorized_parties.contains(&sender_lock_hash) {
+                    (Err(not_matched_error), Vec::new())
+                } else if witness_lock.is_none() {
+                    (Ok(UnlockBranch::SenderInput), vec![sender_lock_hash])
+                } else {
+                    (Ok(UnlockBranch::SenderSignature), vec![sender_lock_hash])
+                }
+            }
+        }
+    }
+
+    // The sighash_all signatures of the witness lock, like `validate_secp256k1_blake2b_sighash_all`
+    fn recover_signers(&self, witness_lock: &Bytes, witnesses: &[Bytes]) -> Option<Vec<[u8; 20]>> {
+        let signatures_count = witness_lock.len() / SIGNATURE_LEN;
+        if witness_lock.len() % SIGNATURE_LEN != 0
+            || signatures_count == 0
+            || signatures_count > MAX_SIGNATURES_COUNT
+        {
+            return None;
+        }
+        let message = sighash_all_message(
+            &self.tx.hash(),
+            witnesses,
+            self.group,
+            self.tx.inputs().len(),
+            signatures_count,
+        )
+        .ok()?;
+        witness_lock
+            .chunks(SIGNATURE_LEN)
+            .map(|signature| recover_lock_hash(&message, signature))
+            .collect()
+    }
+
+    // The receiver and the sender sign the outputs bound to themselves, like
+    // `helper::recover_signer_lock_hash` of the contract
+    fn recover_otx_signer(&self, witness_lock: &Bytes, input_type: &Bytes) -> Option<[u8; 20]> {
+        let receiver_lock_hashes = receiver_bound_lock_hashes(self.cheque_args);
+        let message = self.otx_signing_message(input_type, &receiver_lock_hashes)?;
+        let lock_hash = recover_lock_hash(&message, &witness_lock[1..]);
+        if lock_hash == Some(self.cheque_args.receiver_lock_hash) {
+            return lock_hash;
+        }
+        let sender_lock_hashes = sender_bound_lock_hashes(self.cheque_args);
+        let message = self.otx_signing_message(input_type, &sender_lock_hashes)?;
+        recover_lock_hash(&message, &witness_lock[1..])
+    }
+
+    // The same message as `otx::signing_message` of the contract, which only signs the cheque
+    // inputs, the outputs bound to the signer or the cheque and the input_type of the cheque witness
+    fn otx_signing_message(
+        &self,
+        input_type: &Bytes,
+        bound_lock_hashes: &[[u8; 20]],
+    ) -> Option<[u8; 32]> {
+        if self.cheque_args.has_flag(FLAG_SALTED) {
+            return None;
+        }
+        let lock_script = &self.group.lock_script;
+        let tx_inputs = self.tx.inputs();
+        let group_inputs = self
+            .group
+            .input_indices
+            .iter()
+            .map(|index| {
+                let input = tx_inputs.get(*index)?;
+                Some((input.previous_output(), input.since()))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let bound_outputs = self
+            .tx
+            .outputs_with_data_iter()
+            .filter(|(output, _)| {
+                let lock = output.lock();
+                bound_lock_hashes.contains(&helper::lock_hash_160(&lock))
+                    || (lock.code_hash().as_slice() == lock_script.code_hash().as_slice()
+                        && lock.hash_type().as_slice() == lock_script.hash_type().as_slice())
+            })
+            .collect::<Vec<_>>();
+
+        let inputs = group_inputs
+            .iter()
+            .map(|(previous_output, since)| (previous_output.as_slice(), since.as_slice()))
+            .collect::<Vec<_>>();
+        let outputs = bound_outputs
+            .iter()
+            .map(|(output, data)| (output.as_slice(), &data[..]))
+            .collect::<Vec<_>>();
+        Some(otx::signing_message(
+            lock_script.calc_script_hash().as_slice(),
+            &inputs,
+            &outputs,
+            input_type,
+        ))
+    }
+
+    fn sender_capacity_flow(&self) -> Result<SenderCapacityFlow, Error> {
+        let refund_lock_hash = *self.cheque_args.sender_refund_lock_hash();
+        let outputs = self.tx.outputs().into_iter().collect::<Vec<_>>();
+        let sender_inputs_capacity = sum_cells_capacity(
+            self.input_cells
+                .iter()
+                .filter(|cell| helper::lock_hash_160(&cell.lock()) == refund_lock_hash),
+        )?;
+        let sender_outputs_capacity = sum_cells_capacity(
+            outputs
+                .iter()
+                .filter(|cell| helper::lock_hash_160(&cell.lock()) == refund_lock_hash),
+        )?;
+        // Every cheque input of the same refund lock hash returns its own capacity, where the
+        // capacity of the cheque cells without type script belongs to the receiver with the
+        // FLAG_CAPACITY, and the DAO deposits stay in the DAO outputs
+        let lock_script = &self.group.lock_script;
+        let mut cheque_inputs = Vec::new();
+        for cell in self.input_cells.iter() {
+            let lock = cell.lock();
+            if lock.code_hash().as_slice() != lock_script.code_hash().as_slice()
+                || lock.hash_type().as_slice() != lock_script.hash_type().as_slice()
+            {
+                continue;
+            }
+            let cheque_args = match ChequeArgs::from_slice(&lock.args().raw_data()) {
+                Ok(cheque_args) => cheque_args,
+                Err(_) => continue,
+            };
+            if cheque_args.sender_refund_lock_hash() == &refund_lock_hash
+                && (!cheque_args.has_flag(FLAG_CAPACITY) || cell.type_().to_opt().is_some())
+                && !helper::is_dao_cell(&cheque_args, cell)
+            {
+                cheque_inputs.push(cell);
+            }
+        }
+        let cheque_inputs_capacity = sum_cells_capacity(cheque_inputs.into_iter())?;
+
+        Ok(SenderCapacityFlow {
+            refund_lock_hash,
+            sender_inputs_capacity,
+            cheque_inputs_capacity,
+            sender_outputs_capacity,
+        })
+    }
+}
+
+fn sum_cells_capacity<'a, I: Iterator<Item = &'a CellOutput>>(cells: I) -> Result<u64, Error> {
+    cells.try_fold(0u64, |sum, cell| {
+        let capacity: u64 = cell.capacity().unpack();
+        sum.checked_add(capacity).ok_or(Error::CapacityNotEnough)
+    })
+}
+
+fn recover_lock_hash(message: &[u8; 32], signature: &[u8]) -> Option<[u8; 20]> {
+    let signature = Signature::from_slice(signature).ok()?;
+    let public_key = signature.recover(&H256::from(*message)).ok()?;
+    let public_key_hash = hash::blake2b_160(public_key.serialize());
+    Some(helper::lock_hash_160(&secp256k1_lock_script(
+        &public_key_hash,
+    )))
+}
@@ -0,0 +1,41 @@
+use super::address;
+use super::error::Error;
+use cheque_core::args::{ChequeArgs, LOCK_HASH_LEN};
+use ckb_tool::ckb_types::{packed::Script, prelude::*, H256};
+
+/// The first 20 bytes of the lock script hash, which the cheque lock args keep for the receiver
+/// and the sender
+pub fn lock_hash_160(lock_script: &Script) -> [u8; LOCK_HASH_LEN] {
+    let mut lock_hash = [0u8; LOCK_HASH_LEN];
+    lock_hash.copy_from_slice(&lock_script.calc_script_hash().as_slice()[0..LOCK_HASH_LEN]);
+    lock_hash
+}
+
+/// The constructors of the cheque lock args from the lock scripts and the addresses of the
+/// receiver and the sender, whose flags are empty until they are set
+pub trait ChequeArgsExt: Sized {
+    fn from_lock_scripts(receiver_lock_script: &Script, sender_lock_script: &Script) -> Self;
+
+    fn from_addresses(receiver_address: &str, sender_address: &str) -> Result<Self, Error>;
+}
+
+impl ChequeArgsExt for ChequeArgs {
+    fn from_lock_scripts(receiver_lock_script: &Script, sender_lock_script: &Script) -> Self {
+        let receiver_lock_hash: H256 = receiver_lock_script.calc_script_hash().unpack();
+        let sender_lock_hash: H256 = sender_lock_script.calc_script_hash().unpack();
+        ChequeArgs::from_lock_hashes(&receiver_lock_hash.0, &sender_lock_hash.0)
+    }
+
+    // The addresses of different networks cannot be mixed in one cheque
+    fn from_addresses(receiver_address: &str, sender_address: &str) -> Result<Self, Error> {
+        let (receiver_network, receiver_lock_script) = address::parse_address(receiver_address)?;
+        let (sender_network, sender_lock_script) = address::parse_address(sender_address)?;
+        if receiver_network != sender_network {
+            return Err(Error::InvalidAddress);
+        }
+        Ok(Self::from_lock_scripts(
+            &receiver_lock_script,
+            &sender_lock_script,
+        ))
+    }
+}
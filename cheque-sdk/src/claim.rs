@@ -0,0 +1,235 @@
+use super::error::Error;
+use super::helper;
+use super::script::{ChequeScript, LiveCell};
+use super::witness;
+use cheque_core::args::{
+    ChequeArgs, FLAG_ARBITER, FLAG_CAPACITY, FLAG_RECEIVER_GROUP, FLAG_SALTED, FLAG_SUBSCRIPTION,
+    FLAG_VESTING,
+};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{HeaderView, TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script},
+    prelude::*,
+};
+
+// The salted, vesting, receiver group, escrow and subscription cheques need the claim proofs which
+// the builder cannot know
+const UNSUPPORTED_CLAIM_FLAGS: u16 =
+    FLAG_SALTED | FLAG_VESTING | FLAG_RECEIVER_GROUP | FLAG_ARBITER | FLAG_SUBSCRIPTION;
+
+/// How the receiver authorizes the claim
+pub enum ClaimMode {
+    /// The receiver provides a live cell of the receiver lock, and the cheque witness is empty
+    LockInput(LiveCell),
+    /// The receiver signs the transaction in the witness lock of the first cheque input
+    Signature,
+}
+
+/// Builds the transaction of the receiver which claims the cheque cells of the same lock args.
+///
+/// The capacity of the cheque cells goes back to the sender lock, unless the FLAG_CAPACITY of the
+/// lock args pays the capacity without type script to the receiver. Every UDT or NFT cheque cell
+/// gets its own receiver output of the occupied capacity, which is paid by the receiver input and
+/// the other inputs, and the rest capacity after the fee goes to the change lock, which is the
+/// receiver lock by default. The other inputs must not be locked by the sender lock, whose
+/// capacity is checked on claiming. The oracle cell of the FLAG_ORACLE is given by `cell_dep`.
+///
+/// Every DAO cheque cell starts withdrawing to the receiver at the output of the same index, so
+/// the DAO cheque cells come first, and the header of the block of every deposit is given by
+/// `deposit_header`.
+pub struct ClaimBuilder {
+    cheque_script:   ChequeScript,
+    cheque_args:     ChequeArgs,
+    receiver_lock:   Script,
+    sender_lock:     Script,
+    mode:            ClaimMode,
+    change_lock:     Option<Script>,
+    cheque_cells:    Vec<LiveCell>,
+    inputs:          Vec<LiveCell>,
+    cell_deps:       Vec<CellDep>,
+    deposit_headers: Vec<(OutPoint, HeaderView)>,
+    fee:             u64,
+}
+
+impl ClaimBuilder {
+    /// The sender lock is the lock of the refund lock hash if the FLAG_REFUND of the lock args is
+    /// set
+    pub fn new(
+        cheque_script: ChequeScript,
+        cheque_args: ChequeArgs,
+        receiver_lock: Script,
+        sender_lock: Script,
+        mode: ClaimMode,
+    ) -> Self {
+        ClaimBuilder {
+            cheque_script,
+            cheque_args,
+            receiver_lock,
+            sender_lock,
+            mode,
+            change_lock: None,
+            cheque_cells: Vec::new(),
+            inputs: Vec::new(),
+            cell_deps: Vec::new(),
+            deposit_headers: Vec::new(),
+            fee: 0,
+        }
+    }
+
+    pub fn cheque_cell(mut self, cell: LiveCell) -> Self {
+        self.cheque_cells.push(cell);
+        self
+    }
+
+    pub fn input(mut self, cell: LiveCell) -> Self {
+        self.inputs.push(cell);
+        self
+    }
+
+    pub fn change_lock(mut self, change_lock: Script) -> Self {
+        self.change_lock = Some(change_lock);
+        self
+    }
+
+    pub fn cell_dep(mut self, cell_dep: CellDep) -> Self {
+        self.cell_deps.push(cell_dep);
+        self
+    }
+
+    /// The header of the block in which the DAO cheque cell was deposited
+    pub fn deposit_header(mut self, out_point: OutPoint, header: HeaderView) -> Self {
+        self.deposit_headers.push((out_point, header));
+        self
+    }
+
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn build(self) -> Result<TransactionView, Error> {
+        let cheque_args = &self.cheque_args;
+        helper::check_cheque_args(cheque_args, UNSUPPORTED_CLAIM_FLAGS)?;
+        helper::check_cheque_cells(&self.cheque_script, cheque_args, &self.cheque_cells)?;
+        if helper::lock_hash_160(&self.receiver_lock) != cheque_args.receiver_lock_hash
+            || &helper::lock_hash_160(&self.sender_lock) != cheque_args.sender_refund_lock_hash()
+        {
+            return Err(Error::LockNotMatched);
+        }
+
+        let mut funding_cells = self.inputs.clone();
+        if let ClaimMode::LockInput(receiver_cell) = &self.mode {
+            if helper::lock_hash_160(&receiver_cell.output.lock()) != cheque_args.receiver_lock_hash
+            {
+                return Err(Error::LockNotMatched);
+            }
+            funding_cells.insert(0, receiver_cell.clone());
+        }
+
+        let mut cheque_cells = self.cheque_cells.clone();
+        cheque_cells.sort_by_key(|cell| !helper::is_dao_cell(cheque_args, &cell.output));
+
+        let mut outputs: Vec<(CellOutput, Bytes)> = Vec::new();
+        let mut header_deps = Vec::new();
+        let mut sender_capacity = 0u64;
+        let mut receiver_capacity = 0u64;
+        let mut receiver_outputs_capacity = self.fee;
+        for cell in cheque_cells.iter() {
+            let capacity = cell.capacity();
+            // The DAO deposit goes to the receiver, and starts withdrawing with the block number
+            // of the deposit
+            if helper::is_dao_cell(cheque_args, &cell.output) {
+                let (output, data, header_dep) = helper::dao_withdrawing_output(
+                    cell,
+                    &self.receiver_lock,
+                    &self.deposit_headers,
+                )?;
+                outputs.push((output, data));
+                header_deps.push(header_dep);
+                continue;
+            }
+            match cell.output.type_().to_opt() {
+                Some(type_script) => {
+                    let output = CellOutput::new_builder()
+                        .lock(self.receiver_lock.clone())
+                        .type_(Some(type_script).pack())
+                        .build();
+                    let occupied_capacity = helper::occupied_capacity(&output, &cell.data)?;
+                    let output = output
+                        .as_builder()
+                        .capacity(occupied_capacity.pack())
+                        .build();
+                    outputs.push((output, cell.data.clone()));
+                    receiver_outputs_capacity = receiver_outputs_capacity
+                        .checked_add(occupied_capacity)
+                        .ok_or(Error::CapacityNotEnough)?;
+                    sender_capacity = sender_capacity
+                        .checked_add(capacity)
+                        .ok_or(Error::CapacityNotEnough)?;
+                }
+                None if cheque_args.has_flag(FLAG_CAPACITY) => {
+                    receiver_capacity = receiver_capacity
+                        .checked_add(capacity)
+                        .ok_or(Error::CapacityNotEnough)?;
+                }
+                None => {
+                    sender_capacity = sender_capacity
+                        .checked_add(capacity)
+                        .ok_or(Error::CapacityNotEnough)?;
+                }
+            }
+        }
+
+        if let Some(output) = helper::change_output(&self.sender_lock, sender_capacity)? {
+            outputs.push((output, Bytes::new()));
+        }
+        let change_capacity = helper::sum_capacity(&funding_cells)?
+            .checked_add(receiver_capacity)
+            .and_then(|capacity| capacity.checked_sub(receiver_outputs_capacity))
+            .ok_or(Error::CapacityNotEnough)?;
+        let change_lock = self.change_lock.as_ref().unwrap_or(&self.receiver_lock);
+        if let Some(change) = helper::change_output(change_lock, change_capacity)? {
+            outputs.push((change, Bytes::new()));
+        }
+
+        let claim_since = cheque_args.claim_since.unwrap_or(0);
+        let inputs = cheque_cells
+            .iter()
+            .map(|cell| (cell, claim_since))
+            .chain(funding_cells.iter().map(|cell| (cell, 0)))
+            .map(|(cell, since)| {
+                CellInput::new_builder()
+                    .previous_output(cell.out_point.clone())
+                    .since(since.pack())
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        let mut cell_deps = vec![self.cheque_script.cell_dep.clone()];
+        let mut witnesses = vec![Bytes::new(); cheque_cells.len()];
+        if let ClaimMode::Signature = self.mode {
+            cell_deps.push(self.cheque_script.secp256k1_data_dep.clone());
+            witnesses[0] = witness::signature_placeholder();
+        }
+        cell_deps.extend(self.cell_deps);
+        let funding_outputs = funding_cells
+            .iter()
+            .map(|cell| cell.output.clone())
+            .collect::<Vec<_>>();
+        witnesses.extend(witness::lock_group_placeholders(&funding_outputs));
+        let outputs_data = outputs
+            .iter()
+            .map(|(_, data)| data.clone())
+            .collect::<Vec<_>>();
+
+        Ok(TransactionBuilder::default()
+            .inputs(inputs)
+            .outputs(outputs.into_iter().map(|(output, _)| output))
+            .outputs_data(outputs_data.pack())
+            .cell_deps(cell_deps)
+            .header_deps(header_deps)
+            .witnesses(witnesses.pack())
+            .build())
+    }
+}
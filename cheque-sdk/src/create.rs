@@ -0,0 +1,148 @@
+use super::error::Error;
+use super::helper;
+use super::script::{ChequeScript, LiveCell};
+use super::witness;
+use cheque_core::args::ChequeArgs;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutput, Script},
+    prelude::*,
+};
+
+/// Builds the transaction of the sender which locks a cheque cell for the receiver.
+///
+/// The cheque lock script does not run on creation, so the cell deps are only the ones given by
+/// `cell_dep`, like the lock scripts of the inputs and the type script of the UDT or the NFT. The
+/// rest capacity of the inputs after the outputs and the fee goes to the change lock.
+pub struct CreateChequeBuilder {
+    cheque_script: ChequeScript,
+    cheque_args:   ChequeArgs,
+    change_lock:   Script,
+    capacity:      Option<u64>,
+    type_script:   Option<Script>,
+    data:          Bytes,
+    inputs:        Vec<LiveCell>,
+    outputs:       Vec<(CellOutput, Bytes)>,
+    cell_deps:     Vec<CellDep>,
+    fee:           u64,
+}
+
+impl CreateChequeBuilder {
+    pub fn new(cheque_script: ChequeScript, cheque_args: ChequeArgs, change_lock: Script) -> Self {
+        CreateChequeBuilder {
+            cheque_script,
+            cheque_args,
+            change_lock,
+            capacity: None,
+            type_script: None,
+            data: Bytes::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            cell_deps: Vec::new(),
+            fee: 0,
+        }
+    }
+
+    /// The capacity of the cheque cell, which is its occupied capacity by default
+    pub fn capacity(mut self, capacity: u64) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// The cheque cell carries the amount of the UDT
+    pub fn udt(mut self, type_script: Script, amount: u128) -> Self {
+        self.type_script = Some(type_script);
+        self.data = Bytes::from(amount.to_le_bytes().to_vec());
+        self
+    }
+
+    /// The cheque cell carries the NFT, which needs the FLAG_NFT of the lock args
+    pub fn nft(mut self, type_script: Script, data: Bytes) -> Self {
+        self.type_script = Some(type_script);
+        self.data = data;
+        self
+    }
+
+    pub fn input(mut self, cell: LiveCell) -> Self {
+        self.inputs.push(cell);
+        self
+    }
+
+    /// The other outputs of the sender, like the change of the UDT
+    pub fn output(mut self, output: CellOutput, data: Bytes) -> Self {
+        self.outputs.push((output, data));
+        self
+    }
+
+    pub fn cell_dep(mut self, cell_dep: CellDep) -> Self {
+        self.cell_deps.push(cell_dep);
+        self
+    }
+
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn build(self) -> Result<TransactionView, Error> {
+        helper::check_cheque_args(&self.cheque_args, 0)?;
+
+        let cheque_output = CellOutput::new_builder()
+            .lock(self.cheque_script.lock_script(&self.cheque_args))
+            .type_(self.type_script.clone().pack())
+            .build();
+        let occupied_capacity = helper::occupied_capacity(&cheque_output, &self.data)?;
+        let cheque_capacity = self.capacity.unwrap_or(occupied_capacity);
+        if cheque_capacity < occupied_capacity {
+            return Err(Error::CapacityNotEnough);
+        }
+        let cheque_output = cheque_output
+            .as_builder()
+            .capacity(cheque_capacity.pack())
+            .build();
+
+        let mut outputs = vec![(cheque_output, self.data.clone())];
+        outputs.extend(self.outputs.iter().cloned());
+        let outputs_capacity = outputs
+            .iter()
+            .try_fold(self.fee, |sum, (output, _)| {
+                sum.checked_add(output.capacity().unpack())
+            })
+            .ok_or(Error::CapacityNotEnough)?;
+        let change_capacity = helper::sum_capacity(&self.inputs)?
+            .checked_sub(outputs_capacity)
+            .ok_or(Error::CapacityNotEnough)?;
+        if let Some(change) = helper::change_output(&self.change_lock, change_capacity)? {
+            outputs.push((change, Bytes::new()));
+        }
+
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|cell| {
+                CellInput::new_builder()
+                    .previous_output(cell.out_point.clone())
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let input_cells = self
+            .inputs
+            .iter()
+            .map(|cell| cell.output.clone())
+            .collect::<Vec<_>>();
+        let witnesses = witness::lock_group_placeholders(&input_cells);
+        let outputs_data = outputs
+            .iter()
+            .map(|(_, data)| data.clone())
+            .collect::<Vec<_>>();
+
+        Ok(TransactionBuilder::default()
+            .inputs(inputs)
+            .outputs(outputs.into_iter().map(|(output, _)| output))
+            .outputs_data(outputs_data.pack())
+            .cell_deps(self.cell_deps)
+            .witnesses(witnesses.pack())
+            .build())
+    }
+}
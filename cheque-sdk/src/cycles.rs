@@ -0,0 +1,225 @@
+use super::error::Error;
+use super::script::LiveCell;
+use super::signer::lock_script_groups;
+use cheque_core::error::Error as ChequeError;
+use ckb_tool::ckb_error::Error as VerifyError;
+use ckb_tool::ckb_script::{DataLoader, ScriptError, ScriptGroupType, TransactionScriptsVerifier};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{
+        cell::{CellMeta, CellMetaBuilder, ResolvedTransaction},
+        BlockExt, Cycle, DepType, EpochExt, HeaderView, TransactionInfo, TransactionView,
+    },
+    packed::{Byte32, CellOutput, OutPoint, OutPointVec},
+    prelude::*,
+};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// The max cycles of all the transactions of a block on the mainnet
+pub const MAX_BLOCK_CYCLES: Cycle = 3_500_000_000;
+
+/// The cycles of a script group of the transaction, or the error of the script
+#[derive(Debug)]
+pub struct GroupCycles {
+    pub group_type:  ScriptGroupType,
+    pub script_hash: Byte32,
+    // The first input of the group, or the first output of a type group without inputs, by which
+    // the verifier names the group in its errors
+    pub index:       usize,
+    pub is_output:   bool,
+    pub result:      Result<Cycle, VerifyError>,
+}
+
+impl GroupCycles {
+    /// The error of the cheque lock script if the group exits with one of its error codes
+    pub fn cheque_error(&self) -> Option<ChequeError> {
+        self.result.as_ref().err().and_then(cheque_error)
+    }
+}
+
+/// The error of the cheque lock script from the exit code of a script group which failed the
+/// validation
+pub fn cheque_error(err: &VerifyError) -> Option<ChequeError> {
+    match err.downcast_ref::<ScriptError>() {
+        Some(ScriptError::ValidationFailure(code)) => ChequeError::try_from(*code).ok(),
+        _ => None,
+    }
+}
+
+/// Estimates the cycles of every script group of a transaction by running the RISC-V binaries of
+/// the cell deps, like the cheque lock script, in a local `ckb-script` verifier.
+///
+/// The estimator does not fetch anything, so every input, cell dep and member of a dep group is
+/// given by `cell`, and every header dep by `header`. The group inputs load their headers only if
+/// the headers of their blocks are given by `input_header` and are also in the header deps. The
+/// since of the inputs is not verified.
+pub struct CycleEstimator {
+    cells:         HashMap<OutPoint, (CellOutput, Bytes)>,
+    input_headers: HashMap<OutPoint, HeaderView>,
+    headers:       HashMap<Byte32, HeaderView>,
+    max_cycles:    Cycle,
+}
+
+impl Default for CycleEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CycleEstimator {
+    pub fn new() -> Self {
+        CycleEstimator {
+            cells:         HashMap::new(),
+            input_headers: HashMap::new(),
+            headers:       HashMap::new(),
+            max_cycles:    MAX_BLOCK_CYCLES,
+        }
+    }
+
+    /// The max cycles of every script group, which is `MAX_BLOCK_CYCLES` by default
+    pub fn max_cycles(mut self, max_cycles: Cycle) -> Self {
+        self.max_cycles = max_cycles;
+        self
+    }
+
+    pub fn cell(mut self, cell: LiveCell) -> Self {
+        self.cells.insert(cell.out_point, (cell.output, cell.data));
+        self
+    }
+
+    pub fn header(mut self, header: HeaderView) -> Self {
+        self.headers.insert(header.hash(), header);
+        self
+    }
+
+    /// The header of the block in which the input cell was created
+    pub fn input_header(mut self, out_point: OutPoint, header: HeaderView) -> Self {
+        self.input_headers.insert(out_point, header);
+        self
+    }
+
+    /// Runs the lock groups in the order of their first inputs, and then the type groups of the
+    /// inputs and of the outputs
+    pub fn estimate(&self, tx: &TransactionView) -> Result<Vec<GroupCycles>, Error> {
+        let rtx = self.resolve(tx)?;
+        let verifier = TransactionScriptsVerifier::new(&rtx, self);
+
+        let input_cells = rtx
+            .resolved_inputs
+            .iter()
+            .map(|cell| cell.cell_output.clone())
+            .collect::<Vec<_>>();
+        let mut groups = lock_script_groups(&input_cells)
+            .into_iter()
+            .map(|group| {
+                (
+                    ScriptGroupType::Lock,
+                    group.lock_script.calc_script_hash(),
+                    group.input_indices[0],
+                    false,
+                )
+            })
+            .collect::<Vec<_>>();
+        let type_scripts = input_cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (cell.type_(), index, false))
+            .chain(
+                tx.outputs()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, cell)| (cell.type_(), index, true)),
+            );
+        for (type_script, index, is_output) in type_scripts {
+            if let Some(type_script) = type_script.to_opt() {
+                let script_hash = type_script.calc_script_hash();
+                if !groups.iter().any(|(group_type, hash, _, _)| {
+                    *group_type == ScriptGroupType::Type && *hash == script_hash
+                }) {
+                    groups.push((ScriptGroupType::Type, script_hash, index, is_output));
+                }
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|(group_type, script_hash, index, is_output)| {
+                let result = verifier.verify_single(group_type, &script_hash, self.max_cycles);
+                GroupCycles {
+                    group_type,
+                    script_hash,
+                    index,
+                    is_output,
+                    result,
+                }
+            })
+            .collect())
+    }
+
+    fn resolve(&self, tx: &TransactionView) -> Result<ResolvedTransaction, Error> {
+        let resolved_inputs = tx
+            .inputs()
+            .into_iter()
+            .map(|input| self.cell_meta(&input.previous_output()))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut resolved_cell_deps = Vec::new();
+        let mut resolved_dep_groups = Vec::new();
+        for cell_dep in tx.cell_deps() {
+            let cell_meta = self.cell_meta(&cell_dep.out_point())?;
+            if cell_dep.dep_type() == DepType::DepGroup.into() {
+                let (_, data) = &self.cells[&cell_dep.out_point()];
+                let members = OutPointVec::from_slice(data).map_err(|_| Error::CellNotResolved)?;
+                for member in members {
+                    resolved_cell_deps.push(self.cell_meta(&member)?);
+                }
+                resolved_dep_groups.push(cell_meta);
+            } else {
+                resolved_cell_deps.push(cell_meta);
+            }
+        }
+
+        Ok(ResolvedTransaction {
+            transaction: tx.clone(),
+            resolved_cell_deps,
+            resolved_inputs,
+            resolved_dep_groups,
+        })
+    }
+
+    fn cell_meta(&self, out_point: &OutPoint) -> Result<CellMeta, Error> {
+        let (output, data) = self.cells.get(out_point).ok_or(Error::CellNotResolved)?;
+        let mut builder = CellMetaBuilder::from_cell_output(output.clone(), data.clone())
+            .out_point(out_point.clone());
+        if let Some(header) = self.input_headers.get(out_point) {
+            builder = builder.transaction_info(TransactionInfo::new(
+                header.number(),
+                header.epoch(),
+                header.hash(),
+                0,
+            ));
+        }
+        Ok(builder.build())
+    }
+}
+
+impl DataLoader for CycleEstimator {
+    fn load_cell_data(&self, cell: &CellMeta) -> Option<(Bytes, Byte32)> {
+        self.cells
+            .get(&cell.out_point)
+            .map(|(_, data)| (data.clone(), CellOutput::calc_data_hash(data)))
+    }
+
+    fn get_block_ext(&self, _block_hash: &Byte32) -> Option<BlockExt> {
+        None
+    }
+
+    fn get_header(&self, block_hash: &Byte32) -> Option<HeaderView> {
+        self.headers.get(block_hash).cloned()
+    }
+
+    fn get_block_epoch(&self, _block_hash: &Byte32) -> Option<EpochExt> {
+        None
+    }
+}
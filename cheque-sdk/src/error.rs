@@ -0,0 +1,57 @@
+use core::fmt;
+
+/// Error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    // The lock args cannot be parsed by the cheque lock script
+    InvalidArgument(cheque_core::error::Error),
+    // The builder has no cheque cell to claim or withdraw
+    NoChequeCells,
+    // A cheque cell is not locked by the cheque script of the lock args
+    ChequeCellNotMatched,
+    // The lock script of the receiver or the sender does not match the lock hash of the lock args
+    LockNotMatched,
+    // The builder does not support the flags of the lock args, see the docs of the builder
+    UnsupportedFlags(u16),
+    // The address is neither a full nor a short CKB address of the mainnet or the testnet
+    InvalidAddress,
+    // The inputs cannot pay the outputs and the fee, or the change cannot hold its own cell
+    CapacityNotEnough,
+    // The input cells of the signer do not match the inputs of the transaction
+    InputsNotMatched,
+    // A witness to sign is not a WitnessArgs
+    InvalidWitness,
+    // The signer key cannot sign the message
+    Secp256k1,
+    // A cell of the inputs or the cell deps is not given to the cycle estimator
+    CellNotResolved,
+    // The deposit header of a DAO cheque cell is not given to the claim builder
+    HeaderNotFound,
+}
+
+impl From<cheque_core::error::Error> for Error {
+    fn from(err: cheque_core::error::Error) -> Self {
+        Self::InvalidArgument(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidArgument(err) => write!(f, "invalid cheque lock args: {:?}", err),
+            Self::NoChequeCells => write!(f, "no cheque cells"),
+            Self::ChequeCellNotMatched => write!(f, "cheque cell not locked by the cheque args"),
+            Self::LockNotMatched => write!(f, "lock script not matched with the cheque args"),
+            Self::UnsupportedFlags(flags) => write!(f, "unsupported cheque flags {:#06x}", flags),
+            Self::InvalidAddress => write!(f, "invalid address"),
+            Self::CapacityNotEnough => write!(f, "capacity not enough"),
+            Self::InputsNotMatched => write!(f, "input cells not matched with the inputs"),
+            Self::InvalidWitness => write!(f, "invalid witness args"),
+            Self::Secp256k1 => write!(f, "secp256k1 signing failed"),
+            Self::CellNotResolved => write!(f, "cell not resolved"),
+            Self::HeaderNotFound => write!(f, "deposit header not found"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
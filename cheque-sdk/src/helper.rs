@@ -0,0 +1,97 @@
+pub use super::args::lock_hash_160;
+use super::error::Error;
+use super::script::{ChequeScript, LiveCell};
+use cheque_core::args::{ChequeArgs, FLAG_DAO};
+use cheque_core::code_hash::{DAO_TYPE_CODE_HASH, HASH_TYPE_TYPE};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, HeaderView},
+    packed::{Byte32, CellOutput, OutPoint, Script},
+    prelude::*,
+};
+
+// The cheque lock script only accepts the lock args which can be parsed back, so the builders
+// refuse the others instead of locking the cells forever
+pub fn check_cheque_args(cheque_args: &ChequeArgs, unsupported_flags: u16) -> Result<(), Error> {
+    ChequeArgs::from_slice(&cheque_args.to_bytes())?;
+    if cheque_args.flags & unsupported_flags != 0 {
+        return Err(Error::UnsupportedFlags(
+            cheque_args.flags & unsupported_flags,
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_cheque_cells(
+    cheque_script: &ChequeScript,
+    cheque_args: &ChequeArgs,
+    cheque_cells: &[LiveCell],
+) -> Result<(), Error> {
+    if cheque_cells.is_empty() {
+        return Err(Error::NoChequeCells);
+    }
+    let lock_script = cheque_script.lock_script(cheque_args);
+    if cheque_cells
+        .iter()
+        .any(|cell| cell.output.lock().as_slice() != lock_script.as_slice())
+    {
+        return Err(Error::ChequeCellNotMatched);
+    }
+    Ok(())
+}
+
+pub fn is_dao_cell(cheque_args: &ChequeArgs, cell: &CellOutput) -> bool {
+    cheque_args.has_flag(FLAG_DAO)
+        && cell.type_().to_opt().map_or(false, |type_script| {
+            type_script.code_hash().as_slice() == DAO_TYPE_CODE_HASH
+                && type_script.hash_type().as_slice() == [HASH_TYPE_TYPE]
+                && type_script.args().raw_data().is_empty()
+        })
+}
+
+// The DAO cheque cell starts withdrawing to the lock at the output of the same index, with the
+// block number of the deposit, and the DAO type script loads the deposit header from the header
+// deps
+pub fn dao_withdrawing_output(
+    cell: &LiveCell,
+    lock: &Script,
+    deposit_headers: &[(OutPoint, HeaderView)],
+) -> Result<(CellOutput, Bytes, Byte32), Error> {
+    let header = deposit_headers
+        .iter()
+        .find(|(out_point, _)| out_point == &cell.out_point)
+        .map(|(_, header)| header)
+        .ok_or(Error::HeaderNotFound)?;
+    let output = cell.output.clone().as_builder().lock(lock.clone()).build();
+    let data = Bytes::from(header.number().to_le_bytes().to_vec());
+    Ok((output, data, header.hash()))
+}
+
+pub fn occupied_capacity(output: &CellOutput, data: &Bytes) -> Result<u64, Error> {
+    output
+        .occupied_capacity(Capacity::bytes(data.len()).map_err(|_| Error::CapacityNotEnough)?)
+        .map(|capacity| capacity.as_u64())
+        .map_err(|_| Error::CapacityNotEnough)
+}
+
+pub fn sum_capacity<'a, I: IntoIterator<Item = &'a LiveCell>>(cells: I) -> Result<u64, Error> {
+    cells.into_iter().try_fold(0u64, |sum, cell| {
+        sum.checked_add(cell.capacity())
+            .ok_or(Error::CapacityNotEnough)
+    })
+}
+
+// The change of zero capacity needs no output, and the others must hold their own cell
+pub fn change_output(lock: &Script, capacity: u64) -> Result<Option<CellOutput>, Error> {
+    if capacity == 0 {
+        return Ok(None);
+    }
+    let output = CellOutput::new_builder()
+        .capacity(capacity.pack())
+        .lock(lock.clone())
+        .build();
+    if capacity < occupied_capacity(&output, &Bytes::new())? {
+        return Err(Error::CapacityNotEnough);
+    }
+    Ok(Some(output))
+}
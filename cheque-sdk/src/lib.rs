@@ -0,0 +1,44 @@
+//! Off-chain builders of the cheque transactions, which share the lock args codec with the
+//! cheque lock script through `cheque-core`.
+//!
+//! Every builder returns an unsigned `TransactionView` with the since of the cheque inputs, the
+//! cell deps of the cheque lock script and the witness placeholders, so the caller only pays the
+//! fee and signs it.
+//!
+//! See `args.rs` and `address.rs` for the lock args from the lock scripts and the addresses.
+//! See `script.rs` for the deployed cheque lock script and the live cells.
+//! See `create.rs`, `claim.rs` and `withdraw.rs` for the builders.
+//! See `lifecycle.rs` for who can unlock a live cheque cell and when the withdraw opens.
+//! See `analyzer.rs` for which branch of the cheque lock script unlocked the cheque groups of a
+//! transaction.
+//! See `cycles.rs` for the cycles of every script group of a transaction, which runs the RISC-V
+//! binary of the cheque lock script in a local `ckb-script` verifier.
+//! See `signer.rs` for signing the cheque and the secp256k1_blake160 lock groups of a transaction.
+//! See `error.rs` for the `Error` type.
+
+pub mod address;
+pub mod analyzer;
+pub mod args;
+pub mod claim;
+pub mod create;
+pub mod cycles;
+pub mod error;
+mod helper;
+pub mod lifecycle;
+pub mod script;
+pub mod signer;
+pub mod withdraw;
+pub mod witness;
+
+pub use address::Network;
+pub use analyzer::{analyze_cheque_spends, ChequeSpend, SenderCapacityFlow, UnlockBranch};
+pub use args::ChequeArgsExt;
+pub use cheque_core::args::ChequeArgs;
+pub use claim::{ClaimBuilder, ClaimMode};
+pub use create::CreateChequeBuilder;
+pub use cycles::{CycleEstimator, GroupCycles};
+pub use error::Error;
+pub use lifecycle::{ChequeLifecycle, ChequeState, Deadline};
+pub use script::{ChequeScript, LiveCell};
+pub use signer::Signer;
+pub use withdraw::{WithdrawBuilder, WithdrawMode};
@@ -0,0 +1,211 @@
+use super::error::Error;
+use cheque_core::args::ChequeArgs;
+use cheque_core::since::{
+    CHEQUE_CELL_SINCE, SINCE_METRIC_EPOCH, SINCE_METRIC_MASK, SINCE_RELATIVE_FLAG, SINCE_VALUE_MASK,
+};
+use ckb_tool::ckb_types::{
+    core::{EpochNumberWithFraction, HeaderView},
+    packed::CellOutput,
+    prelude::*,
+};
+
+// The bits between the value and the flags of since are reserved and must be zero
+const SINCE_RESERVED_MASK: u64 = 0x1F00_0000_0000_0000;
+const SINCE_METRIC_BLOCK_NUMBER: u64 = 0x0000_0000_0000_0000;
+const SINCE_METRIC_TIMESTAMP: u64 = 0x4000_0000_0000_0000;
+
+/// Who can unlock a live cheque cell alone at the tip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChequeState {
+    // Neither the receiver nor the sender can unlock the cheque cell alone yet
+    Locked,
+    // The receiver can claim, and the sender waits for the withdraw since
+    Claimable,
+    // The sender can withdraw, and the receiver cannot claim alone
+    Withdrawable,
+    // The receiver can still claim until the sender withdraws
+    ClaimableOrWithdrawable,
+}
+
+/// The absolute point from which a since is satisfied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deadline {
+    BlockNumber(u64),
+    // The epoch number with the fraction index / length in lowest terms, whose length can be
+    // beyond the u16 of the epoch since after adding a relative since with another length
+    Epoch {
+        number: u64,
+        index:  u64,
+        length: u64,
+    },
+    // The milliseconds compared with the median time of the past 37 blocks
+    Timestamp(u64),
+    // The since is malformed and can never be satisfied
+    Never,
+}
+
+/// The lifecycle of a live cheque cell between its creation and the tip, calculated off chain by
+/// the same since rules as the chain verifies the cheque inputs.
+///
+/// The claim opens from the creation unless the FLAG_CLAIM_SINCE of the lock args is set, and the
+/// withdraw opens after the withdraw since of the lock args or `CHEQUE_CELL_SINCE`, where a
+/// relative since counts from the block in which the cheque cell was created. The receiver of an
+/// escrow cheque never claims alone. The oracle milestone, the vested capacity and the subscription
+/// period are checked on claiming and not here.
+///
+/// The chain compares the since with the block committing the transaction, which is after the tip,
+/// and the timestamp with the median time of the past 37 blocks, which is before the tip, so a
+/// timestamp deadline may be reached at the tip a little earlier than on chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChequeLifecycle {
+    pub state:             ChequeState,
+    // None if the receiver can claim from the creation
+    pub claim_opens_at:    Option<Deadline>,
+    pub withdraw_opens_at: Deadline,
+}
+
+impl ChequeLifecycle {
+    pub fn calculate(
+        cheque_cell: &CellOutput,
+        created_header: &HeaderView,
+        tip_header: &HeaderView,
+    ) -> Result<Self, Error> {
+        let cheque_args = ChequeArgs::from_slice(&cheque_cell.lock().args().raw_data())?;
+
+        let claim_opens_at = if cheque_args.arbiter_lock_hash.is_some() {
+            Some(Deadline::Never)
+        } else {
+            cheque_args
+                .claim_since
+                .map(|claim_since| since_deadline(claim_since, created_header))
+        };
+        let withdraw_opens_at = since_deadline(
+            cheque_args.withdraw_since.unwrap_or(CHEQUE_CELL_SINCE),
+            created_header,
+        );
+
+        let claimable = claim_opens_at.map_or(true, |deadline| deadline.is_reached(tip_header));
+        let withdrawable = withdraw_opens_at.is_reached(tip_header);
+        let state = match (claimable, withdrawable) {
+            (false, false) => ChequeState::Locked,
+            (true, false) => ChequeState::Claimable,
+            (false, true) => ChequeState::Withdrawable,
+            (true, true) => ChequeState::ClaimableOrWithdrawable,
+        };
+        Ok(ChequeLifecycle {
+            state,
+            claim_opens_at,
+            withdraw_opens_at,
+        })
+    }
+
+    pub fn receiver_can_claim(&self) -> bool {
+        self.state == ChequeState::Claimable || self.state == ChequeState::ClaimableOrWithdrawable
+    }
+
+    pub fn sender_can_withdraw(&self) -> bool {
+        self.state == ChequeState::Withdrawable
+            || self.state == ChequeState::ClaimableOrWithdrawable
+    }
+}
+
+impl Deadline {
+    pub fn is_reached(&self, header: &HeaderView) -> bool {
+        match *self {
+            Deadline::BlockNumber(number) => header.number() >= number,
+            Deadline::Epoch {
+                number,
+                index,
+                length,
+            } => {
+                let epoch = header.epoch();
+                let (epoch_index, epoch_length) = epoch_fraction(&epoch);
+                // Compare the fractions by cross multiplying to keep them exact
+                epoch.number() > number
+                    || (epoch.number() == number
+                        && epoch_index as u128 * length as u128
+                            >= index as u128 * epoch_length as u128)
+            }
+            Deadline::Timestamp(timestamp) => header.timestamp() >= timestamp,
+            Deadline::Never => false,
+        }
+    }
+}
+
+/// The deadline of the since of a cheque input, where a relative since counts from the header of
+/// the block in which the cheque cell was created
+pub fn since_deadline(since: u64, created_header: &HeaderView) -> Deadline {
+    if since & SINCE_RESERVED_MASK != 0 {
+        return Deadline::Never;
+    }
+    let relative = since & SINCE_RELATIVE_FLAG != 0;
+    let value = since & SINCE_VALUE_MASK;
+    match since & SINCE_METRIC_MASK {
+        SINCE_METRIC_BLOCK_NUMBER if relative => created_header
+            .number()
+            .checked_add(value)
+            .map_or(Deadline::Never, Deadline::BlockNumber),
+        SINCE_METRIC_BLOCK_NUMBER => Deadline::BlockNumber(value),
+        SINCE_METRIC_EPOCH => {
+            let epoch = EpochNumberWithFraction::from_full_value(value);
+            let (index, length) = epoch_fraction(&epoch);
+            if relative {
+                // A relative epoch of whole epochs may leave the fraction zero
+                if !(epoch.index() < epoch.length() || (epoch.index() == 0 && epoch.length() == 0))
+                {
+                    return Deadline::Never;
+                }
+                let created_epoch = created_header.epoch();
+                let (created_index, created_length) = epoch_fraction(&created_epoch);
+                add_epochs(
+                    created_epoch.number() + epoch.number(),
+                    created_index * length + index * created_length,
+                    created_length * length,
+                )
+            } else if epoch.index() < epoch.length() {
+                add_epochs(epoch.number(), index, length)
+            } else {
+                Deadline::Never
+            }
+        }
+        // The since of timestamp is in seconds and the header timestamp is in milliseconds
+        SINCE_METRIC_TIMESTAMP if relative => value
+            .checked_mul(1000)
+            .and_then(|millis| created_header.timestamp().checked_add(millis))
+            .map_or(Deadline::Never, Deadline::Timestamp),
+        SINCE_METRIC_TIMESTAMP => value
+            .checked_mul(1000)
+            .map_or(Deadline::Never, Deadline::Timestamp),
+        _ => Deadline::Never,
+    }
+}
+
+// The fraction of an epoch without fraction is 0 / 1
+fn epoch_fraction(epoch: &EpochNumberWithFraction) -> (u64, u64) {
+    match epoch.length() {
+        0 => (0, 1),
+        length => (epoch.index(), length),
+    }
+}
+
+fn add_epochs(number: u64, index: u64, length: u64) -> Deadline {
+    let (number, index) = if index >= length {
+        (number + 1, index - length)
+    } else {
+        (number, index)
+    };
+    let divisor = gcd(index, length);
+    Deadline::Epoch {
+        number,
+        index: index / divisor,
+        length: length / divisor,
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
@@ -0,0 +1,49 @@
+use cheque_core::args::ChequeArgs;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{Byte32, CellDep, CellOutput, OutPoint, Script},
+    prelude::*,
+};
+
+/// The cheque lock script deployed on a chain
+#[derive(Clone)]
+pub struct ChequeScript {
+    pub code_hash:          Byte32,
+    pub hash_type:          ScriptHashType,
+    pub cell_dep:           CellDep,
+    // The secp256k1_data cell which the cheque lock script loads to recover the signatures
+    pub secp256k1_data_dep: CellDep,
+}
+
+impl ChequeScript {
+    pub fn lock_script(&self, cheque_args: &ChequeArgs) -> Script {
+        Script::new_builder()
+            .code_hash(self.code_hash.clone())
+            .hash_type(self.hash_type.into())
+            .args(Bytes::from(cheque_args.to_bytes()).pack())
+            .build()
+    }
+}
+
+/// A live cell consumed by a cheque transaction
+#[derive(Clone)]
+pub struct LiveCell {
+    pub out_point: OutPoint,
+    pub output:    CellOutput,
+    pub data:      Bytes,
+}
+
+impl LiveCell {
+    pub fn new(out_point: OutPoint, output: CellOutput, data: Bytes) -> Self {
+        LiveCell {
+            out_point,
+            output,
+            data,
+        }
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.output.capacity().unpack()
+    }
+}
@@ -0,0 +1,274 @@
+use super::error::Error;
+use super::helper;
+use super::script::ChequeScript;
+use super::witness::SIGNATURE_LEN;
+use cheque_core::args::{salted_lock_hash, ChequeArgs, FLAG_SALTED, SALT_LEN};
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE};
+use cheque_core::hash;
+use cheque_core::witness::{parse_receiver_salt, MAX_SIGNATURES_COUNT};
+use ckb_tool::ckb_crypto::secp::Privkey;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{Byte, Byte32, CellOutput, Script, WitnessArgs},
+    prelude::*,
+    H256,
+};
+
+/// The inputs of the same lock script, which run the lock script once as a group
+pub struct ScriptGroup {
+    pub lock_script:   Script,
+    pub input_indices: Vec<usize>,
+}
+
+/// Groups the inputs by their lock scripts in the order of the first input of every group
+pub fn lock_script_groups(input_cells: &[CellOutput]) -> Vec<ScriptGroup> {
+    let mut groups: Vec<ScriptGroup> = Vec::new();
+    for (index, cell) in input_cells.iter().enumerate() {
+        let lock_script = cell.lock();
+        match groups
+            .iter_mut()
+            .find(|group| group.lock_script.as_slice() == lock_script.as_slice())
+        {
+            Some(group) => group.input_indices.push(index),
+            None => groups.push(ScriptGroup {
+                lock_script,
+                input_indices: vec![index],
+            }),
+        }
+    }
+    groups
+}
+
+fn update_witness(blake2b: &mut hash::Blake2b, witness: &[u8]) {
+    blake2b.update(&(witness.len() as u64).to_le_bytes());
+    blake2b.update(witness);
+}
+
+/// The sighash_all message of the group as `validate_secp256k1_blake2b_sighash_all` of the
+/// secp256k1 library digests it: the transaction hash, the first witness of the group with the
+/// lock zero filled for the signatures, the other witnesses of the group until the first missing
+/// one, and the witnesses beyond the inputs.
+pub fn sighash_all_message(
+    tx_hash: &Byte32,
+    witnesses: &[Bytes],
+    group: &ScriptGroup,
+    inputs_len: usize,
+    signatures_count: usize,
+) -> Result<[u8; 32], Error> {
+    let first_index = *group.input_indices.first().ok_or(Error::InputsNotMatched)?;
+    let first_witness = witnesses.get(first_index).ok_or(Error::InvalidWitness)?;
+    let witness_for_digest = parse_witness_args(first_witness)?
+        .as_builder()
+        .lock(Some(Bytes::from(vec![0u8; SIGNATURE_LEN * signatures_count])).pack())
+        .build();
+
+    let mut blake2b = hash::new_blake2b();
+    blake2b.update(tx_hash.as_slice());
+    update_witness(&mut blake2b, witness_for_digest.as_slice());
+    for index in group.input_indices.iter().skip(1) {
+        match witnesses.get(*index) {
+            Some(witness) => update_witness(&mut blake2b, witness),
+            None => break,
+        }
+    }
+    for witness in witnesses.iter().skip(inputs_len) {
+        update_witness(&mut blake2b, witness);
+    }
+
+    let mut message = [0u8; 32];
+    blake2b.finalize(&mut message);
+    Ok(message)
+}
+
+// An empty witness is the default witness args, so the placeholders are optional
+pub(crate) fn parse_witness_args(witness: &[u8]) -> Result<WitnessArgs, Error> {
+    if witness.is_empty() {
+        return Ok(WitnessArgs::default());
+    }
+    WitnessArgs::from_slice(witness).map_err(|_| Error::InvalidWitness)
+}
+
+pub(crate) fn secp256k1_lock_script(public_key_hash: &[u8; 20]) -> Script {
+    Script::new_builder()
+        .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
+        .args(Bytes::copy_from_slice(public_key_hash).pack())
+        .hash_type(Byte::new(HASH_TYPE_TYPE))
+        .build()
+}
+
+struct SignerKey {
+    key:             Privkey,
+    public_key_hash: [u8; 20],
+    // The first 20 bytes of the secp256k1_blake160 lock hash of the key, as kept by the cheque
+    // lock args
+    lock_hash:       [u8; 20],
+}
+
+/// Signs every lock group of secp256k1_blake160_sighash_all or of the cheque lock script whose
+/// signer key is known, and leaves the other groups as they are.
+///
+/// A cheque group is signed by the key of the receiver, or the sender if the receiver key is
+/// unknown. The receiver group and the escrow cheques are signed by all the known keys of their
+/// parties, which must be enough for the action in the input_type of the cheque witness. The OTX
+/// signature is not supported.
+pub struct Signer {
+    cheque_script: ChequeScript,
+    keys:          Vec<SignerKey>,
+}
+
+impl Signer {
+    pub fn new(cheque_script: ChequeScript) -> Self {
+        Signer {
+            cheque_script,
+            keys: Vec::new(),
+        }
+    }
+
+    pub fn key(mut self, key: Privkey) -> Result<Self, Error> {
+        let public_key = key.pubkey().map_err(|_| Error::Secp256k1)?;
+        let public_key_hash = hash::blake2b_160(public_key.serialize());
+        let lock_hash = helper::lock_hash_160(&secp256k1_lock_script(&public_key_hash));
+        self.keys.push(SignerKey {
+            key,
+            public_key_hash,
+            lock_hash,
+        });
+        Ok(self)
+    }
+
+    /// Signs the transaction whose input cells are given in the order of the inputs, and the
+    /// witnesses beyond the inputs must be final before signing
+    pub fn sign(
+        &self,
+        tx: TransactionView,
+        input_cells: &[CellOutput],
+    ) -> Result<TransactionView, Error> {
+        let inputs_len = tx.inputs().len();
+        if input_cells.len() != inputs_len {
+            return Err(Error::InputsNotMatched);
+        }
+        let mut witnesses: Vec<Bytes> = tx.witnesses().into_iter().map(|w| w.unpack()).collect();
+        // Every group digests the same witnesses of its inputs, so the missing witnesses of the
+        // inputs are filled before signing any group
+        if witnesses.len() < inputs_len {
+            witnesses.resize(inputs_len, Bytes::new());
+        }
+        let tx_hash = tx.hash();
+
+        for group in lock_script_groups(input_cells).iter() {
+            let first_index = group.input_indices[0];
+            let keys = self.group_keys(group, &witnesses[first_index])?;
+            if keys.is_empty() {
+                continue;
+            }
+
+            let message = sighash_all_message(&tx_hash, &witnesses, group, inputs_len, keys.len())?;
+            let message = H256::from(message);
+            let mut signatures = Vec::with_capacity(SIGNATURE_LEN * keys.len());
+            for key in keys {
+                let signature = key
+                    .sign_recoverable(&message)
+                    .map_err(|_| Error::Secp256k1)?;
+                signatures.extend_from_slice(&signature.serialize());
+            }
+            witnesses[first_index] = parse_witness_args(&witnesses[first_index])?
+                .as_builder()
+                .lock(Some(Bytes::from(signatures)).pack())
+                .build()
+                .as_bytes();
+        }
+
+        Ok(tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses.into_iter().map(|w| w.pack()).collect())
+            .build())
+    }
+
+    fn group_keys(
+        &self,
+        group: &ScriptGroup,
+        first_witness: &Bytes,
+    ) -> Result<Vec<&Privkey>, Error> {
+        let lock_script = &group.lock_script;
+        if lock_script.code_hash().as_slice() == CODE_HASH_SECP256K1_BLAKE160
+            && lock_script.hash_type().as_slice() == [HASH_TYPE_TYPE]
+        {
+            let args = lock_script.args().raw_data();
+            return Ok(self
+                .keys
+                .iter()
+                .filter(|signer_key| signer_key.public_key_hash[..] == args[..])
+                .map(|signer_key| &signer_key.key)
+                .take(1)
+                .collect());
+        }
+
+        let cheque_hash_type: Byte = self.cheque_script.hash_type.into();
+        if lock_script.code_hash().as_slice() != self.cheque_script.code_hash.as_slice()
+            || lock_script.hash_type().as_slice() != cheque_hash_type.as_slice()
+        {
+            return Ok(Vec::new());
+        }
+        let cheque_args = ChequeArgs::from_slice(&lock_script.args().raw_data())?;
+        let find_key = |lock_hash: &[u8; 20]| {
+            self.keys
+                .iter()
+                .find(|signer_key| &signer_key.lock_hash == lock_hash)
+                .map(|signer_key| &signer_key.key)
+        };
+
+        let mut parties: Vec<[u8; 20]> = Vec::new();
+        if let Some(receiver_group) = &cheque_args.receiver_group {
+            parties.extend(receiver_group.receiver_lock_hashes.iter().copied());
+        } else if let Some(salt) = load_receiver_salt(&cheque_args, first_witness)? {
+            parties.extend(
+                self.keys
+                    .iter()
+                    .map(|signer_key| signer_key.lock_hash)
+                    .filter(|lock_hash| {
+                        salted_lock_hash(lock_hash, &salt) == cheque_args.receiver_lock_hash
+                    }),
+            );
+        } else {
+            parties.push(cheque_args.receiver_lock_hash);
+        }
+        if let Some(arbiter_lock_hash) = cheque_args.arbiter_lock_hash {
+            parties.push(arbiter_lock_hash);
+        }
+        parties.push(cheque_args.sender_lock_hash);
+
+        // The same key signs once even if it is the lock of several parties
+        let mut unique_parties: Vec<[u8; 20]> = Vec::new();
+        for party in parties {
+            if !unique_parties.contains(&party) {
+                unique_parties.push(party);
+            }
+        }
+        let mut keys = unique_parties
+            .iter()
+            .filter_map(find_key)
+            .collect::<Vec<_>>();
+        // Only the receiver group and the escrow cheques accept multiple signatures
+        if cheque_args.receiver_group.is_none() && cheque_args.arbiter_lock_hash.is_none() {
+            keys.truncate(1);
+        }
+        keys.truncate(MAX_SIGNATURES_COUNT);
+        Ok(keys)
+    }
+}
+
+// The salt of the receiver follows the claim action in the input_type of the cheque witness
+pub(crate) fn load_receiver_salt(
+    cheque_args: &ChequeArgs,
+    first_witness: &Bytes,
+) -> Result<Option<[u8; SALT_LEN]>, Error> {
+    if !cheque_args.has_flag(FLAG_SALTED) {
+        return Ok(None);
+    }
+    let input_type: Bytes = match parse_witness_args(first_witness)?.input_type().to_opt() {
+        Some(input_type) => input_type.unpack(),
+        None => return Ok(None),
+    };
+    Ok(parse_receiver_salt(&input_type))
+}
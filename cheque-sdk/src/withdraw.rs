@@ -0,0 +1,203 @@
+use super::error::Error;
+use super::helper;
+use super::script::{ChequeScript, LiveCell};
+use super::witness;
+use cheque_core::args::{ChequeArgs, FLAG_VESTING};
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{HeaderView, TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script},
+    prelude::*,
+};
+
+// The vesting cheques need the vested output of the receiver which the builder cannot know
+const UNSUPPORTED_WITHDRAW_FLAGS: u16 = FLAG_VESTING;
+
+/// How the sender authorizes the withdrawal
+pub enum WithdrawMode {
+    /// The sender provides a live cell of the sender lock, and the cheque witness is empty
+    LockInput(LiveCell),
+    /// The sender signs the transaction in the witness lock of the first cheque input
+    Signature,
+}
+
+/// Builds the transaction of the sender which withdraws the cheque cells of the same lock args
+/// after the withdraw since.
+///
+/// Every cheque cell with type script goes to its own output of the refund lock with the same
+/// capacity, type script and data, which is the sender lock unless the FLAG_REFUND of the lock args
+/// is set. The capacity of the cheque cells without type script and of the other inputs pays the
+/// fee, and the rest goes to the change lock, which is the refund lock by default. With the
+/// FLAG_REFUND, the capacity of the cheque cells without type script goes to its own output of the
+/// refund lock, and only the other inputs pay the fee.
+///
+/// Every DAO cheque cell starts withdrawing to the refund lock at the output of the same index
+/// instead, like the claim of the receiver, so the DAO cheque cells come first, and the header of
+/// the block of every deposit is given by `deposit_header`.
+pub struct WithdrawBuilder {
+    cheque_script:   ChequeScript,
+    cheque_args:     ChequeArgs,
+    refund_lock:     Script,
+    mode:            WithdrawMode,
+    change_lock:     Option<Script>,
+    cheque_cells:    Vec<LiveCell>,
+    inputs:          Vec<LiveCell>,
+    cell_deps:       Vec<CellDep>,
+    deposit_headers: Vec<(OutPoint, HeaderView)>,
+    fee:             u64,
+}
+
+impl WithdrawBuilder {
+    pub fn new(
+        cheque_script: ChequeScript,
+        cheque_args: ChequeArgs,
+        refund_lock: Script,
+        mode: WithdrawMode,
+    ) -> Self {
+        WithdrawBuilder {
+            cheque_script,
+            cheque_args,
+            refund_lock,
+            mode,
+            change_lock: None,
+            cheque_cells: Vec::new(),
+            inputs: Vec::new(),
+            cell_deps: Vec::new(),
+            deposit_headers: Vec::new(),
+            fee: 0,
+        }
+    }
+
+    pub fn cheque_cell(mut self, cell: LiveCell) -> Self {
+        self.cheque_cells.push(cell);
+        self
+    }
+
+    pub fn input(mut self, cell: LiveCell) -> Self {
+        self.inputs.push(cell);
+        self
+    }
+
+    pub fn change_lock(mut self, change_lock: Script) -> Self {
+        self.change_lock = Some(change_lock);
+        self
+    }
+
+    pub fn cell_dep(mut self, cell_dep: CellDep) -> Self {
+        self.cell_deps.push(cell_dep);
+        self
+    }
+
+    /// The header of the block in which the DAO cheque cell was deposited
+    pub fn deposit_header(mut self, out_point: OutPoint, header: HeaderView) -> Self {
+        self.deposit_headers.push((out_point, header));
+        self
+    }
+
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn build(self) -> Result<TransactionView, Error> {
+        let cheque_args = &self.cheque_args;
+        helper::check_cheque_args(cheque_args, UNSUPPORTED_WITHDRAW_FLAGS)?;
+        helper::check_cheque_cells(&self.cheque_script, cheque_args, &self.cheque_cells)?;
+        if &helper::lock_hash_160(&self.refund_lock) != cheque_args.sender_refund_lock_hash() {
+            return Err(Error::LockNotMatched);
+        }
+
+        let mut funding_cells = self.inputs.clone();
+        if let WithdrawMode::LockInput(sender_cell) = &self.mode {
+            if helper::lock_hash_160(&sender_cell.output.lock()) != cheque_args.sender_lock_hash {
+                return Err(Error::LockNotMatched);
+            }
+            funding_cells.insert(0, sender_cell.clone());
+        }
+
+        let mut cheque_cells = self.cheque_cells.clone();
+        cheque_cells.sort_by_key(|cell| !helper::is_dao_cell(cheque_args, &cell.output));
+
+        let mut outputs: Vec<(CellOutput, Bytes)> = Vec::new();
+        let mut header_deps = Vec::new();
+        let mut cheque_capacity = 0u64;
+        for cell in cheque_cells.iter() {
+            if helper::is_dao_cell(cheque_args, &cell.output) {
+                let (output, data, header_dep) =
+                    helper::dao_withdrawing_output(cell, &self.refund_lock, &self.deposit_headers)?;
+                outputs.push((output, data));
+                header_deps.push(header_dep);
+            } else if cell.output.type_().to_opt().is_some() {
+                let output = cell
+                    .output
+                    .clone()
+                    .as_builder()
+                    .lock(self.refund_lock.clone())
+                    .build();
+                outputs.push((output, cell.data.clone()));
+            } else {
+                cheque_capacity = cheque_capacity
+                    .checked_add(cell.capacity())
+                    .ok_or(Error::CapacityNotEnough)?;
+            }
+        }
+
+        // The refund lock of the FLAG_REFUND does not authorize the withdrawal, so it gets the whole
+        // capacity of the cheque cells and the other inputs pay the fee
+        if cheque_args.sender_refund_lock_hash() != &cheque_args.sender_lock_hash {
+            if let Some(output) = helper::change_output(&self.refund_lock, cheque_capacity)? {
+                outputs.push((output, Bytes::new()));
+            }
+            cheque_capacity = 0;
+        }
+
+        let change_capacity = helper::sum_capacity(&funding_cells)?
+            .checked_add(cheque_capacity)
+            .and_then(|capacity| capacity.checked_sub(self.fee))
+            .ok_or(Error::CapacityNotEnough)?;
+        let change_lock = self.change_lock.as_ref().unwrap_or(&self.refund_lock);
+        if let Some(change) = helper::change_output(change_lock, change_capacity)? {
+            outputs.push((change, Bytes::new()));
+        }
+
+        let withdraw_since = cheque_args.withdraw_since.unwrap_or(CHEQUE_CELL_SINCE);
+        let inputs = cheque_cells
+            .iter()
+            .map(|cell| (cell, withdraw_since))
+            .chain(funding_cells.iter().map(|cell| (cell, 0)))
+            .map(|(cell, since)| {
+                CellInput::new_builder()
+                    .previous_output(cell.out_point.clone())
+                    .since(since.pack())
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        let mut cell_deps = vec![self.cheque_script.cell_dep.clone()];
+        let mut witnesses = vec![Bytes::new(); cheque_cells.len()];
+        if let WithdrawMode::Signature = self.mode {
+            cell_deps.push(self.cheque_script.secp256k1_data_dep.clone());
+            witnesses[0] = witness::signature_placeholder();
+        }
+        cell_deps.extend(self.cell_deps);
+        let funding_outputs = funding_cells
+            .iter()
+            .map(|cell| cell.output.clone())
+            .collect::<Vec<_>>();
+        witnesses.extend(witness::lock_group_placeholders(&funding_outputs));
+        let outputs_data = outputs
+            .iter()
+            .map(|(_, data)| data.clone())
+            .collect::<Vec<_>>();
+
+        Ok(TransactionBuilder::default()
+            .inputs(inputs)
+            .outputs(outputs.into_iter().map(|(output, _)| output))
+            .outputs_data(outputs_data.pack())
+            .cell_deps(cell_deps)
+            .header_deps(header_deps)
+            .witnesses(witnesses.pack())
+            .build())
+    }
+}
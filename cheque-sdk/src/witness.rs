@@ -0,0 +1,33 @@
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    packed::{CellOutput, WitnessArgs},
+    prelude::*,
+};
+
+// The secp256k1_blake160_sighash_all signature and the cheque signature are both 65 bytes
+pub const SIGNATURE_LEN: usize = 65;
+
+/// The witness of the first input of a lock group, whose lock is zero filled until it is signed
+pub fn signature_placeholder() -> Bytes {
+    WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(vec![0u8; SIGNATURE_LEN])).pack())
+        .build()
+        .as_bytes()
+}
+
+/// The witnesses of the inputs, where the first input of every lock group gets a signature
+/// placeholder and the others get empty witnesses
+pub fn lock_group_placeholders(inputs: &[CellOutput]) -> Vec<Bytes> {
+    let mut witnesses = Vec::with_capacity(inputs.len());
+    for (index, input) in inputs.iter().enumerate() {
+        let is_first_of_group = inputs[0..index]
+            .iter()
+            .all(|previous| previous.lock().as_slice() != input.lock().as_slice());
+        if is_first_of_group {
+            witnesses.push(signature_placeholder());
+        } else {
+            witnesses.push(Bytes::new());
+        }
+    }
+    witnesses
+}
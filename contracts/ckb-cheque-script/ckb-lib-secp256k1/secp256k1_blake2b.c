@@ -6,6 +6,9 @@
 /* 32 KB */
 #define ONE_BATCH_SIZE 32768
 
+/* The most signatures in the witness lock of the cheque cell */
+#define MAX_SIGNATURES_COUNT 16
+
 /*
  * data should at least be CKB_SECP256K1_DATA_SIZE big
  * so as to hold all loaded data.
@@ -49,9 +52,15 @@ int load_and_hash_witness(blake2b_state *ctx, size_t index, size_t source) {
   return CKB_SUCCESS;
 }
 
-int validate_secp256k1_blake2b_sighash_all(uint8_t *output_public_key_hash) {
-  unsigned char temp[TEMP_SIZE];
-  unsigned char lock_bytes[SIGNATURE_SIZE];
+/*
+ * Load the signatures in the witness lock of the first group input, which must be
+ * signatures_count * SIGNATURE_SIZE bytes, and calculate the sighash_all message
+ * with the witness lock cleared to zero.
+ */
+static int load_signatures_and_message(unsigned char *temp,
+                                       unsigned char *lock_bytes,
+                                       uint64_t *signatures_count,
+                                       unsigned char *message) {
   uint64_t len = 0;
 
   // Load witness of first input
@@ -65,16 +74,18 @@ int validate_secp256k1_blake2b_sighash_all(uint8_t *output_public_key_hash) {
     return ERROR_WITNESS_SIZE;
   }
 
-  // load signature
+  // load signatures
   mol_seg_t lock_bytes_seg;
   ret = extract_witness_lock(temp, witness_len, &lock_bytes_seg);
   if (ret != 0) {
     return ERROR_ENCODING;
   }
 
-  if (lock_bytes_seg.size != SIGNATURE_SIZE) {
+  if (lock_bytes_seg.size == 0 || lock_bytes_seg.size % SIGNATURE_SIZE != 0 ||
+      lock_bytes_seg.size / SIGNATURE_SIZE > *signatures_count) {
     return ERROR_ARGUMENTS_LEN;
   }
+  *signatures_count = lock_bytes_seg.size / SIGNATURE_SIZE;
   memcpy(lock_bytes, lock_bytes_seg.ptr, lock_bytes_seg.size);
 
   // Load tx hash
@@ -89,7 +100,6 @@ int validate_secp256k1_blake2b_sighash_all(uint8_t *output_public_key_hash) {
   }
 
   // Prepare sign message
-  unsigned char message[BLAKE2B_BLOCK_SIZE];
   blake2b_state blake2b_ctx;
   blake2b_init(&blake2b_ctx, BLAKE2B_BLOCK_SIZE);
   blake2b_update(&blake2b_ctx, tx_hash, BLAKE2B_BLOCK_SIZE);
@@ -124,11 +134,21 @@ int validate_secp256k1_blake2b_sighash_all(uint8_t *output_public_key_hash) {
     i += 1;
   }
   blake2b_final(&blake2b_ctx, message, BLAKE2B_BLOCK_SIZE);
+  return CKB_SUCCESS;
+}
 
-  // Load signature
+/*
+ * Recover the public keys from the signatures of the message and output the
+ * blake160 hashes of the compressed public keys.
+ */
+static int recover_public_key_hashes(unsigned char *temp,
+                                     unsigned char *lock_bytes,
+                                     uint64_t signatures_count,
+                                     unsigned char *message,
+                                     uint8_t *output_public_key_hashes) {
   secp256k1_context context;
   uint8_t secp_data[CKB_SECP256K1_DATA_SIZE];
-  ret = ckb_secp256k1_custom_load_data(secp_data);
+  int ret = ckb_secp256k1_custom_load_data(secp_data);
   if (ret != 0) {
     return ret;
   }
@@ -137,30 +157,86 @@ int validate_secp256k1_blake2b_sighash_all(uint8_t *output_public_key_hash) {
     return ret;
   }
 
-  secp256k1_ecdsa_recoverable_signature signature;
-  if (secp256k1_ecdsa_recoverable_signature_parse_compact(
-          &context, &signature, lock_bytes, lock_bytes[RECID_INDEX]) == 0) {
-    return ERROR_SECP_PARSE_SIGNATURE;
-  }
+  for (uint64_t i = 0; i < signatures_count; i++) {
+    unsigned char *signature_bytes = lock_bytes + i * SIGNATURE_SIZE;
+    secp256k1_ecdsa_recoverable_signature signature;
+    if (secp256k1_ecdsa_recoverable_signature_parse_compact(
+            &context, &signature, signature_bytes,
+            signature_bytes[RECID_INDEX]) == 0) {
+      return ERROR_SECP_PARSE_SIGNATURE;
+    }
+
+    // Recover pubkey
+    secp256k1_pubkey pubkey;
+    if (secp256k1_ecdsa_recover(&context, &pubkey, &signature, message) != 1) {
+      return ERROR_SECP_RECOVER_PUBKEY;
+    }
+
+    // Check pubkey hash
+    size_t pubkey_size = PUBKEY_SIZE;
+    if (secp256k1_ec_pubkey_serialize(&context, temp, &pubkey_size, &pubkey,
+                                      SECP256K1_EC_COMPRESSED) != 1) {
+      return ERROR_SECP_SERIALIZE_PUBKEY;
+    }
 
-  // Recover pubkey
-  secp256k1_pubkey pubkey;
-  if (secp256k1_ecdsa_recover(&context, &pubkey, &signature, message) != 1) {
-    return ERROR_SECP_RECOVER_PUBKEY;
+    blake2b_state blake2b_ctx;
+    blake2b_init(&blake2b_ctx, BLAKE2B_BLOCK_SIZE);
+    blake2b_update(&blake2b_ctx, temp, pubkey_size);
+    blake2b_final(&blake2b_ctx, temp, BLAKE2B_BLOCK_SIZE);
+
+    memcpy(output_public_key_hashes + i * BLAKE160_SIZE, temp, BLAKE160_SIZE);
   }
 
-  // Check pubkey hash
-  size_t pubkey_size = PUBKEY_SIZE;
-  if (secp256k1_ec_pubkey_serialize(&context, temp, &pubkey_size, &pubkey,
-                                    SECP256K1_EC_COMPRESSED) != 1) {
-    return ERROR_SECP_SERIALIZE_PUBKEY;
+  return CKB_SUCCESS;
+}
+
+int validate_secp256k1_blake2b_sighash_all(uint8_t *output_public_key_hash) {
+  unsigned char temp[TEMP_SIZE];
+  unsigned char lock_bytes[SIGNATURE_SIZE];
+  unsigned char message[BLAKE2B_BLOCK_SIZE];
+  uint64_t signatures_count = 1;
+
+  int ret = load_signatures_and_message(temp, lock_bytes, &signatures_count,
+                                        message);
+  if (ret != CKB_SUCCESS) {
+    return ret;
   }
+  return recover_public_key_hashes(temp, lock_bytes, signatures_count, message,
+                                   output_public_key_hash);
+}
 
-  blake2b_init(&blake2b_ctx, BLAKE2B_BLOCK_SIZE);
-  blake2b_update(&blake2b_ctx, temp, pubkey_size);
-  blake2b_final(&blake2b_ctx, temp, BLAKE2B_BLOCK_SIZE);
+/*
+ * The witness lock of the first group input holds up to MAX_SIGNATURES_COUNT
+ * signatures of the same sighash_all message, and the blake160 hashes of their
+ * public keys are output in the order of the signatures.
+ */
+int validate_secp256k1_blake2b_sighash_all_multiple(
+    uint8_t *output_public_key_hashes, uint64_t *signatures_count) {
+  unsigned char temp[TEMP_SIZE];
+  unsigned char lock_bytes[SIGNATURE_SIZE * MAX_SIGNATURES_COUNT];
+  unsigned char message[BLAKE2B_BLOCK_SIZE];
+  *signatures_count = MAX_SIGNATURES_COUNT;
 
-  memcpy(output_public_key_hash, temp, BLAKE160_SIZE);
+  int ret = load_signatures_and_message(temp, lock_bytes, signatures_count,
+                                        message);
+  if (ret != CKB_SUCCESS) {
+    return ret;
+  }
+  return recover_public_key_hashes(temp, lock_bytes, *signatures_count, message,
+                                   output_public_key_hashes);
+}
 
-  return CKB_SUCCESS;
-}
\ No newline at end of file
+/*
+ * Recover the public key from the signature of the given message, which is
+ * calculated by the caller, and output the blake160 hash of it.
+ */
+int recover_secp256k1_blake160(const uint8_t *message, const uint8_t *signature,
+                               uint8_t *output_public_key_hash) {
+  unsigned char temp[TEMP_SIZE];
+  unsigned char lock_bytes[SIGNATURE_SIZE];
+  unsigned char message_bytes[BLAKE2B_BLOCK_SIZE];
+  memcpy(lock_bytes, signature, SIGNATURE_SIZE);
+  memcpy(message_bytes, message, BLAKE2B_BLOCK_SIZE);
+  return recover_public_key_hashes(temp, lock_bytes, 1, message_bytes,
+                                   output_public_key_hash);
+}
@@ -1,28 +1,88 @@
 use core::result::Result;
 
-use super::helper;
-use crate::error::Error;
-use ckb_std::{ckb_constants::Source, high_level::load_script_hash};
-
-pub fn validate(
-    sender_lock_hash: &[u8; 20],
-    receiver_lock_hash: &[u8; 20],
-    cheque_witness_is_none: bool,
-) -> Result<(), Error> {
-    let script_hash = load_script_hash()?;
-    let mut cheque_lock_hash = [0u8; 20];
-    cheque_lock_hash.copy_from_slice(&script_hash[0..20]);
+use super::dao;
+use super::header;
+use super::helper::{self, ChequeInput};
+use super::subscription;
+use super::vesting;
+use super::withdraw;
+use alloc::vec::Vec;
+use cheque_core::args::{ChequeArgs, FLAG_CAPACITY, FLAG_DAO, FLAG_WITHDRAW_SINCE};
+use cheque_core::error::Error;
+use cheque_core::since::{self, CHEQUE_CELL_SINCE};
+use cheque_core::witness::{CLAIM_ACTION_FORWARD, CLAIM_ACTION_NONE};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    high_level::{load_cell, load_cell_data, load_script, QueryIter},
+};
 
-    if check_cheque_inputs_since_not_zero() {
-        return Err(Error::ClaimChequeInputSinceNotZero);
+pub fn validate(cheque_args: &ChequeArgs, cheque_witness_is_none: bool) -> Result<(), Error> {
+    match cheque_args.claim_since {
+        Some(claim_since) => {
+            if !check_cheque_inputs_since_not_earlier(claim_since) {
+                return Err(Error::ClaimChequeInputSinceTooEarly);
+            }
+        }
+        // The since of vesting and subscription cheque inputs proves the epoch of claiming
+        None if cheque_args.vesting.is_some() || cheque_args.subscription.is_some() => {}
+        None => {
+            if check_cheque_inputs_since_not_zero() {
+                return Err(Error::ClaimChequeInputSinceNotZero);
+            }
+        }
     }
 
-    if !check_sender_cells_capacity_same(sender_lock_hash, &cheque_lock_hash)? {
-        return Err(Error::SenderCapacityNotSame);
+    let cheque_witness_input_type = helper::load_cheque_witness_input_type();
+    match cheque_witness_input_type.first() {
+        None | Some(&CLAIM_ACTION_NONE) => {
+            if let Some((oracle_type_hash, milestone)) = cheque_args.oracle {
+                if !helper::check_oracle_milestone_reached(&oracle_type_hash, milestone) {
+                    return Err(Error::OracleMilestoneNotReached);
+                }
+            }
+
+            if let Some(vesting) = cheque_args.vesting {
+                if !vesting::check_claim(&vesting)? {
+                    return Err(Error::VestingCapacityNotMatched);
+                }
+            }
+
+            if let Some(subscription) = cheque_args.subscription {
+                if !subscription::check_pull(&subscription)? {
+                    return Err(Error::SubscriptionNotMatched);
+                }
+            }
+
+            check_claim_assets(cheque_args)?;
+
+            // The OTX signature of the receiver does not sign the outputs of the other groups
+            // merged into the transaction, so the capacity must reach the receiver once for every
+            // cheque cell claimed by the receiver
+            if cheque_args.vesting.is_none()
+                && cheque_args.subscription.is_none()
+                && helper::is_otx_signed()
+            {
+                let receiver_lock_hash = &cheque_args.receiver_lock_hash;
+                check_receiver_capacity(cheque_args, |cheque_input| {
+                    let input_args = &cheque_input.cheque_args;
+                    input_args.vesting.is_none()
+                        && input_args.subscription.is_none()
+                        && &input_args.receiver_lock_hash == receiver_lock_hash
+                        && is_claimed(cheque_input)
+                })?;
+            }
+        }
+        Some(&CLAIM_ACTION_FORWARD) => {
+            if !check_forwarded_cheque_outputs(cheque_args)? {
+                return Err(Error::ForwardedChequeNotMatched);
+            }
+        }
+        Some(_) => return Err(Error::Encoding),
     }
 
     if cheque_witness_is_none {
-        match helper::position_input_by_lock_hash(receiver_lock_hash) {
+        match helper::position_input_by_lock_hash(&cheque_args.receiver_lock_hash) {
             Some(position) => helper::check_witness_args(position),
             None => Err(Error::NoMatchedInputs),
         }
@@ -31,16 +91,75 @@ pub fn validate(
     }
 }
 
-fn check_sender_cells_capacity_same(
-    sender_lock_hash: &[u8; 20],
-    cheque_lock_hash: &[u8; 20],
-) -> Result<bool, Error> {
+// The escrow cheque cells are released to the receiver with the authorization of two parties
+pub fn validate_escrow(cheque_args: &ChequeArgs) -> Result<(), Error> {
+    check_claim_assets(cheque_args)?;
+
+    // The receiver may not authorize the release, so the capacity paid to the receiver must reach
+    // it, and the fee is paid by the other inputs. The capacity is summed over every escrow cheque
+    // cell released to the same receiver, so one output cannot be counted by several groups.
+    let receiver_lock_hash = &cheque_args.receiver_lock_hash;
+    check_receiver_capacity(cheque_args, |cheque_input| {
+        let input_args = &cheque_input.cheque_args;
+        input_args.arbiter_lock_hash.is_some()
+            && &input_args.receiver_lock_hash == receiver_lock_hash
+            && is_claimed(cheque_input)
+    })
+}
+
+// The capacity of the cheque cells with FLAG_CAPACITY and without type script is summed over the
+// group inputs and the cheque inputs released to the same receiver by the other groups
+fn check_receiver_capacity<F: Fn(&ChequeInput) -> bool>(
+    cheque_args: &ChequeArgs,
+    is_released_to_receiver: F,
+) -> Result<(), Error> {
+    if !cheque_args.has_flag(FLAG_CAPACITY) {
+        return Ok(());
+    }
+    let receiver_capacity = helper::sum_cheque_inputs_capacity(|cheque_input| {
+        let input_args = &cheque_input.cheque_args;
+        (cheque_input.in_group || is_released_to_receiver(cheque_input))
+            && input_args.has_flag(FLAG_CAPACITY)
+            && cheque_input.cell.type_().to_opt().is_none()
+    })?;
+    if !helper::check_capacity_to_lock_hash(&cheque_args.receiver_lock_hash, receiver_capacity)? {
+        return Err(Error::ChequeAssetNotMatched);
+    }
+    Ok(())
+}
+
+fn check_claim_assets(cheque_args: &ChequeArgs) -> Result<(), Error> {
+    if !check_sender_cells_capacity_same(cheque_args)? {
+        return Err(Error::SenderCapacityNotSame);
+    }
+
+    let receiver_lock_hash = &cheque_args.receiver_lock_hash;
+    if !helper::check_group_assets_to_lock_hash(cheque_args, receiver_lock_hash, |cheque_input| {
+        &cheque_input.cheque_args.receiver_lock_hash == receiver_lock_hash
+            && is_claimed(cheque_input)
+    })? {
+        return Err(Error::ChequeAssetNotMatched);
+    }
+    Ok(())
+}
+
+// A cheque cell of another group pays the receiver unless it is refunded or forwarded to a new
+// cheque cell
+fn is_claimed(cheque_input: &ChequeInput) -> bool {
+    !withdraw::is_refunded(cheque_input)
+        && cheque_input.load_cheque_witness_input_type().first() != Some(&CLAIM_ACTION_FORWARD)
+}
+
+// The capacity returned to the sender is counted per cheque cell over every cheque input refunding
+// to the same lock hash, so the claims of several cheque cells of the same sender can be merged into
+// one transaction, and every claimed group checks the same sum
+fn check_sender_cells_capacity_same(cheque_args: &ChequeArgs) -> Result<bool, Error> {
+    let refund_lock_hash = cheque_args.sender_refund_lock_hash();
     let sum_sender_inputs_capacity =
-        helper::sum_cells_capacity_of_lock_hash(sender_lock_hash, Source::Input)?;
+        helper::sum_cells_capacity_of_lock_hash(refund_lock_hash, Source::Input)?;
     let sum_sender_outputs_capacity =
-        helper::sum_cells_capacity_of_lock_hash(sender_lock_hash, Source::Output)?;
-    let sum_cheque_inputs_capacity =
-        helper::sum_cells_capacity_of_lock_hash(cheque_lock_hash, Source::Input)?;
+        helper::sum_cells_capacity_of_lock_hash(refund_lock_hash, Source::Output)?;
+    let sum_cheque_inputs_capacity = sum_cheque_inputs_refund_capacity(refund_lock_hash)?;
 
     match sum_sender_inputs_capacity.checked_add(sum_cheque_inputs_capacity) {
         Some(sum_inputs_capacity) => Ok(sum_inputs_capacity == sum_sender_outputs_capacity),
@@ -48,7 +167,118 @@ fn check_sender_cells_capacity_same(
     }
 }
 
+fn sum_cheque_inputs_refund_capacity(refund_lock_hash: &[u8; 20]) -> Result<u64, Error> {
+    helper::sum_cheque_inputs_capacity(|cheque_input| {
+        let cheque_args = &cheque_input.cheque_args;
+        let cell = &cheque_input.cell;
+        // The capacity of a cheque cell without type script belongs to the receiver with
+        // FLAG_CAPACITY, and the capacity of a DAO cheque cell is the deposit itself, which stays
+        // in the DAO outputs
+        let is_receiver_capacity =
+            cheque_args.has_flag(FLAG_CAPACITY) && cell.type_().to_opt().is_none();
+        let is_dao_deposit = cheque_args.has_flag(FLAG_DAO) && dao::is_dao_cell(cell);
+        cheque_args.sender_refund_lock_hash() == refund_lock_hash
+            && !is_receiver_capacity
+            && !is_dao_deposit
+    })
+}
+
+// Every cheque cell must be forwarded to its own cheque output in the same order, which keeps the
+// capacity, type script and data of the cheque cell, and whose lock args only change the receiver
+// and fix the withdraw since to the absolute deadline of the cheque cell. A relative withdraw since
+// which is not counted in epochs cannot be converted and is rejected.
+fn check_forwarded_cheque_outputs(cheque_args: &ChequeArgs) -> Result<bool, Error> {
+    let script = load_script()?;
+    let forwarded_outputs = QueryIter::new(load_cell, Source::Output)
+        .zip(QueryIter::new(load_cell_data, Source::Output))
+        .filter(|(cell, _)| {
+            let lock = cell.lock();
+            let args: Bytes = lock.args().unpack();
+            lock.code_hash().as_slice() == script.code_hash().as_slice()
+                && lock.hash_type().as_slice() == script.hash_type().as_slice()
+                && match ChequeArgs::from_slice(&args) {
+                    Ok(output_cheque_args) => {
+                        output_cheque_args.sender_lock_hash == cheque_args.sender_lock_hash
+                    }
+                    Err(_) => false,
+                }
+        })
+        .collect::<Vec<_>>();
+
+    let cheque_inputs = QueryIter::new(load_cell, Source::GroupInput)
+        .zip(QueryIter::new(load_cell_data, Source::GroupInput))
+        .collect::<Vec<_>>();
+    if cheque_inputs.len() != forwarded_outputs.len() {
+        return Ok(false);
+    }
+
+    for (index, ((cell, data), (output, output_data))) in
+        cheque_inputs.iter().zip(forwarded_outputs.iter()).enumerate()
+    {
+        // A relative withdraw since would restart at every forward, so it is anchored at the
+        // creation of the cheque cell like the default lock-up period
+        let withdraw_since = match cheque_args.withdraw_since {
+            Some(withdraw_since) if withdraw_since & since::SINCE_RELATIVE_FLAG == 0 => {
+                withdraw_since
+            }
+            Some(withdraw_since) => header::load_group_input_deadline(index, withdraw_since)?,
+            None => header::load_group_input_deadline(index, CHEQUE_CELL_SINCE)?,
+        };
+        let output_args: Bytes = output.lock().args().unpack();
+        let expected_args = ChequeArgs {
+            receiver_lock_hash: ChequeArgs::from_slice(&output_args)?.receiver_lock_hash,
+            flags: cheque_args.flags | FLAG_WITHDRAW_SINCE,
+            withdraw_since: Some(withdraw_since),
+            ..cheque_args.clone()
+        };
+        if expected_args.to_bytes().as_slice() != output_args.as_ref() {
+            return Ok(false);
+        }
+
+        let output_capacity: u64 = output.capacity().unpack();
+        let cheque_capacity: u64 = cell.capacity().unpack();
+        if output_capacity != cheque_capacity
+            || output.type_().as_slice() != cell.type_().as_slice()
+            || output_data != data
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
 fn check_cheque_inputs_since_not_zero() -> bool {
     let cheque_inputs_since = helper::load_group_inputs_since();
     cheque_inputs_since.into_iter().any(|since| since != 0)
 }
+
+// A relative claim since can also be proved with the zero since of the cheque input, when the
+// headers of the header deps show that the claim since has elapsed from the creation of the cheque
+// cell
+fn check_cheque_inputs_since_not_earlier(claim_since: u64) -> bool {
+    let cheque_inputs_since = helper::load_group_inputs_since();
+    cheque_inputs_since
+        .into_iter()
+        .enumerate()
+        .all(|(index, input_since)| {
+            since::is_since_not_earlier(input_since, claim_since)
+                || (input_since == 0 && check_elapsed_since_creation(index, claim_since))
+        })
+}
+
+fn check_elapsed_since_creation(index: usize, relative_since: u64) -> bool {
+    if relative_since & since::SINCE_RELATIVE_FLAG == 0 {
+        return false;
+    }
+    let value = relative_since & since::SINCE_VALUE_MASK;
+    let elapsed = match relative_since & since::SINCE_METRIC_MASK {
+        0 => header::elapsed_blocks_since_creation(index)
+            .map(|elapsed| elapsed.map_or(false, |blocks| blocks >= value)),
+        since::SINCE_METRIC_EPOCH => header::elapsed_epochs_since_creation(index).map(|elapsed| {
+            elapsed.map_or(false, |epochs| epochs >= since::epoch_to_fixed_point(value))
+        }),
+        _ => return false,
+    };
+    // The header of the block of the cheque cell may be missing from the header deps
+    elapsed.unwrap_or(false)
+}
@@ -0,0 +1,63 @@
+use core::result::Result;
+
+use cheque_core::code_hash::{DAO_TYPE_CODE_HASH, HASH_TYPE_TYPE};
+use cheque_core::error::Error;
+use cheque_core::hash;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{packed::CellOutput, prelude::*},
+    error::SysError,
+    high_level::{load_cell, load_cell_data, load_header, load_script, QueryIter},
+};
+
+// The data of a DAO deposit cell is 8 zero bytes, and the data of a withdrawing cell is the block
+// number of the deposit in u64 little endian
+const DAO_DEPOSIT_DATA: [u8; 8] = [0u8; 8];
+
+pub fn is_dao_cell(cell: &CellOutput) -> bool {
+    match cell.type_().to_opt() {
+        Some(type_script) => {
+            type_script.code_hash().as_slice() == DAO_TYPE_CODE_HASH
+                && type_script.hash_type().as_slice() == [HASH_TYPE_TYPE]
+                && type_script.args().raw_data().is_empty()
+        }
+        None => false,
+    }
+}
+
+// Every DAO cheque cell of the group must be a deposit cell, and the output at the same index must
+// be a DAO cell of the lock hash with the same capacity, which starts withdrawing with the block
+// number of the deposit. The DAO type script checks the withdrawing output against the deposit
+// header in the header deps, so the receiver keeps the interest accumulated while the cheque cell
+// waited.
+pub fn check_group_dao_deposits_to_lock_hash(lock_hash: &[u8; 20]) -> Result<bool, Error> {
+    let script = load_script()?;
+    for (index, cell) in QueryIter::new(load_cell, Source::Input).enumerate() {
+        if cell.lock().as_slice() != script.as_slice() || !is_dao_cell(&cell) {
+            continue;
+        }
+        if load_cell_data(index, Source::Input)?.as_slice() != DAO_DEPOSIT_DATA {
+            return Ok(false);
+        }
+
+        let output = match load_cell(index, Source::Output) {
+            Ok(output) => output,
+            Err(SysError::IndexOutOfBound) => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+        let capacity: u64 = cell.capacity().unpack();
+        let output_capacity: u64 = output.capacity().unpack();
+        if !is_dao_cell(&output)
+            || &hash::blake2b_160(output.lock().as_slice()) != lock_hash
+            || output_capacity != capacity
+        {
+            return Ok(false);
+        }
+
+        let deposit_number: u64 = load_header(index, Source::Input)?.raw().number().unpack();
+        if load_cell_data(index, Source::Output)?.as_slice() != deposit_number.to_le_bytes() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
@@ -14,6 +14,88 @@
  *      and the first 20 byte of the sender lock hash must be equal to sender_lock_hash[0..20] of the cheque cell lock args.
  *   2.b. The sender provides an official secp256k1_blake160 input cell whose the first 20 byte of lock script hash 
  *      must be equal to sender_lock_hash[0..20] of the cheque cell lock args.
+ *
+ * All the cheque cells with the same lock args are one script group, so they are claimed or withdrawn together
+ * by the same party in one transaction. Every asset carried by the group must go to that party:
+ *   - A cheque cell with a type script carries a UDT whose amount is the first 16 bytes of the cell data, and
+ *     the outputs locked by the receiver(claim) or the sender(withdraw) must hold at least the same amount of
+ *     every UDT type of the group.
+ *   - If the FLAG_NFT of the lock args is set, every cheque cell with type script carries a non-fungible asset,
+ *     and it must have its own output locked by the receiver(claim) or the sender(withdraw) with exactly the
+ *     same type script and cell data, so the asset can neither be swapped nor altered.
+ *   - The capacity of the cheque cells is returned to the sender when claiming, unless the FLAG_CAPACITY of
+ *     the lock args is set, then the capacity of the cheque cells without type script is paid to the receiver.
+ *
+ * The receiver can also forward the cheque cells to a new receiver instead of claiming them, with the first byte
+ * of the input_type of the cheque witness being 1. Every cheque cell must be moved into its own cheque output with
+ * the same capacity, type script and data, whose lock args only change the receiver and set the withdraw since
+ * (FLAG_WITHDRAW_SINCE) to the absolute deadline of the cheque cell, so the sender keeps the same withdraw time. A
+ * relative withdraw since of the lock args is converted like the default one, and it must be counted in epochs.
+ * The header of the block in which the cheque cell was created must be in the header deps to calculate the deadline.
+ *
+ * If the FLAG_ORACLE of the lock args is set, the receiver can only claim when a cell dep whose type script hash is
+ * the oracle_type_hash of the lock args has the bit of the milestone set in its data. Forwarding and withdrawing
+ * do not depend on the oracle, so the sender can always recover the cheque cells.
+ *
+ * The since of the cheque inputs must be zero when claiming, unless the FLAG_CLAIM_SINCE of the lock args is set,
+ * then the since of the cheque inputs must have the same flags as the claim_since of the lock args and must not be
+ * earlier than it, so the receiver cannot claim before the start time. A relative claim_since can also be proved
+ * with the zero since of a cheque input and the header of the block of the cheque cell in the header deps, when the
+ * latest header of the header deps is at least the claim_since later. The header deps can only prove that the
+ * transaction is committed after a block, so the claim has no deadline.
+ *
+ * If the FLAG_SALTED of the lock args is set, the receiver_lock_hash of the lock args is the salted commitment
+ * blake2b_160(receiver_lock_hash[0..20] | salt) which hides the receiver until claiming. The receiver reveals the
+ * 32 bytes salt after the claim action in the input_type of the cheque witness, and the receiver input or the
+ * signer of the cheque cell must match the commitment with the salt.
+ *
+ * If the FLAG_VESTING of the lock args is set, the capacity of the cheque cells without type script vests linearly
+ * from the start epoch to the end epoch of the lock args. The receiver proves the epoch of claiming with the absolute
+ * epoch since of the cheque inputs, or with the latest header of the header deps if the since is zero, and can take
+ * up to the vested capacity, and the unvested capacity must go back to the cheque outputs with the same lock. The
+ * sender can only withdraw the capacity unvested at the withdraw deadline, and the vested capacity which has not been
+ * claimed goes to a cheque output without vesting for the receiver.
+ *
+ * If the FLAG_RECEIVER_GROUP of the lock args is set, the lock args carry a threshold and a group of receiver lock
+ * hashes, and the receiver_lock_hash of the lock args is the blake2b_160 of them. The receivers claim with at least
+ * the threshold of distinct receiver inputs, or with the signatures of at least the threshold of distinct receivers
+ * in the witness lock of the cheque cell, and the first authorizing receiver of the group receives the assets.
+ *
+ * If the FLAG_ARBITER of the lock args is set, the cheque cells are held in escrow by the arbiter_lock_hash of the lock
+ * args. Any two of the sender, the receiver and the arbiter can release the cheque cells to the receiver with the
+ * escrow action 2 or refund them to the sender with the escrow action 3 in the input_type of the cheque witness, by
+ * their inputs or by their signatures in the witness lock of the cheque cell. The refunded capacity of the cheque cells
+ * must reach the outputs of the sender on top of its own inputs, and so must the capacity released to the receiver with
+ * the FLAG_CAPACITY, so the fee is paid by the other inputs. The receiver alone cannot claim, and the sender alone can
+ * still withdraw after the withdraw since.
+ *
+ * If the FLAG_REFUND of the lock args is set, the capacity returned to the sender on claiming and the assets withdrawn
+ * by the sender go to the refund_lock_hash of the lock args instead of the sender_lock_hash, while the withdrawing is
+ * still authorized by the sender_lock_hash. The withdrawn capacity of the cheque cells, except the vested capacity left
+ * to the receiver, must reach the outputs of the refund_lock_hash on top of its own inputs, so the fee is paid by the
+ * other inputs.
+ *
+ * If the FLAG_DAO of the lock args is set, the cheque cells with the Nervos DAO type script are deposit cells, and every
+ * one of them must have its own DAO output of the same capacity locked by the receiver(claim) or the sender(withdraw),
+ * which either keeps the deposit data or starts withdrawing from the deposit, so the interest is kept. Their capacity
+ * is not returned to the sender when claiming.
+ *
+ * If the FLAG_SUBSCRIPTION of the lock args is set, the receiver pulls the amount of the lock args once per period of
+ * period_epochs from the start_epoch, proving the epoch of pulling like the vesting cheque. Every pull recreates the
+ * only cheque cell of the group with the capacity reduced by the amount and the period counter in its data bumped by
+ * one, until the rest capacity cannot hold the cheque cell. The sender can cancel the subscription by withdrawing the
+ * rest after the withdraw since, which counts from the last pull as the notice period.
+ *
+ * The receiver or the sender can also sign the cheque cell with an OTX signature, whose witness lock is the byte 1
+ * followed by the 65 bytes signature. It only signs the cheque inputs, the outputs bound to the signer or the cheque
+ * script, and the input_type of the cheque witness(see otx.rs), so independent parties can merge the signed cheque cells
+ * into one transaction. The outputs bound to the receiver are locked by the receiver lock hash, and the outputs bound to
+ * the sender are locked by the sender or the refund lock hash. The capacity returned to the sender on claiming is
+ * checked per cheque cell over every cheque input of the same refund lock hash, so the claims of several cheque cells
+ * of the same sender can be merged as well. The OTX signature is not supported by salted, receiver group and escrow
+ * cheques.
+ *
+ * Cheque cell lock args: receiver_lock_hash[0..20] | sender_lock_hash[20..40] | flags[40..42](optional) | fields
  */
 
 use core::result::Result;
@@ -27,38 +109,63 @@ use ckb_std::{
 use super::claim;
 use super::helper;
 use super::withdraw;
-use crate::error::Error;
+use alloc::vec::Vec;
+use cheque_core::args::{salted_lock_hash, ChequeArgs, FLAG_SALTED, SALT_LEN};
+use cheque_core::error::Error;
+use cheque_core::witness::{self, ESCROW_ACTION_REFUND, ESCROW_ACTION_RELEASE};
 
 pub fn main() -> Result<(), Error> {
     let script = load_script()?;
     let args: Bytes = script.args().unpack();
-    if args.len() != 40 {
-        return Err(Error::InvalidArgument);
-    }
-
-    let mut receiver_lock_hash = [0u8; 20];
-    let mut sender_lock_hash = [0u8; 20];
-    receiver_lock_hash.copy_from_slice(&args[0..20]);
-    sender_lock_hash.copy_from_slice(&args[20..]);
+    let mut cheque_args = ChequeArgs::from_slice(&args)?;
+    let receiver_salt = load_receiver_salt(&cheque_args);
 
     let cheque_witness_is_none = check_cheque_cell_witness_is_none()?;
+    if let Some(arbiter_lock_hash) = cheque_args.arbiter_lock_hash {
+        return validate_escrow(&cheque_args, &arbiter_lock_hash, cheque_witness_is_none);
+    }
+
     if cheque_witness_is_none {
         // Check if the inputs contain the same input as receiver lock hash or sender lock hash
-        if helper::has_input_by_lock_hash(&receiver_lock_hash) {
-            claim::validate(&sender_lock_hash, &receiver_lock_hash, cheque_witness_is_none)
-        } else if helper::has_input_by_lock_hash(&sender_lock_hash) {
-            withdraw::validate(&sender_lock_hash, cheque_witness_is_none)
+        let receiver_lock_hash = match &cheque_args.receiver_group {
+            Some(receiver_group) => receiver_group.match_authorized(helper::has_input_by_lock_hash),
+            None => helper::find_input_lock_hash(|lock_hash| {
+                match_receiver_lock_hash(&cheque_args, lock_hash, &receiver_salt)
+            }),
+        };
+        if let Some(receiver_lock_hash) = receiver_lock_hash {
+            cheque_args.receiver_lock_hash = receiver_lock_hash;
+            claim::validate(&cheque_args, cheque_witness_is_none)
+        } else if helper::has_input_by_lock_hash(&cheque_args.sender_lock_hash) {
+            withdraw::validate(&cheque_args, cheque_witness_is_none)
         } else {
             Err(Error::NoMatchedInputs)
         }
+    } else if let Some(receiver_group) = cheque_args.receiver_group.clone() {
+        // The witness lock of the cheque cell can hold the signatures of multiple receivers
+        let signer_lock_hashes =
+            helper::recover_signer_lock_hashes().map_err(|_| Error::NoMatchedSignature)?;
+        let receiver_lock_hash =
+            receiver_group.match_authorized(|lock_hash| signer_lock_hashes.contains(lock_hash));
+        if let Some(receiver_lock_hash) = receiver_lock_hash {
+            cheque_args.receiver_lock_hash = receiver_lock_hash;
+            claim::validate(&cheque_args, cheque_witness_is_none)
+        } else if signer_lock_hashes == [cheque_args.sender_lock_hash] {
+            withdraw::validate(&cheque_args, cheque_witness_is_none)
+        } else {
+            Err(Error::NoMatchedSignature)
+        }
     } else {
         // Validate the signatures of receiver and sender
-        match helper::validate_signature_of_receiver_and_sender(&receiver_lock_hash, &sender_lock_hash) {
-            Ok(is_receiver) => {
-                if is_receiver {
-                    claim::validate(&sender_lock_hash, &receiver_lock_hash, cheque_witness_is_none)
+        match helper::recover_signer_lock_hash(&cheque_args) {
+            Ok(lock_hash) => {
+                if match_receiver_lock_hash(&cheque_args, &lock_hash, &receiver_salt) {
+                    cheque_args.receiver_lock_hash = lock_hash;
+                    claim::validate(&cheque_args, cheque_witness_is_none)
+                } else if lock_hash == cheque_args.sender_lock_hash {
+                    withdraw::validate(&cheque_args, cheque_witness_is_none)
                 } else {
-                    withdraw::validate(&sender_lock_hash, cheque_witness_is_none)
+                    Err(Error::NoMatchedSignature)
                 }
             }
             Err(_) => Err(Error::NoMatchedSignature),
@@ -66,9 +173,91 @@ pub fn main() -> Result<(), Error> {
     }
 }
 
+// The escrow cheque cells are released to the receiver or refunded to the sender with any two of
+// the sender, the receiver and the arbiter, and the sender alone can still withdraw them after the
+// withdraw since.
+fn validate_escrow(
+    cheque_args: &ChequeArgs,
+    arbiter_lock_hash: &[u8; 20],
+    cheque_witness_is_none: bool,
+) -> Result<(), Error> {
+    let authorized_lock_hashes = if cheque_witness_is_none {
+        helper::load_inputs_lock_hashes()
+    } else {
+        helper::recover_signer_lock_hashes().map_err(|_| Error::NoMatchedSignature)?
+    };
+    let parties = [
+        cheque_args.sender_lock_hash,
+        cheque_args.receiver_lock_hash,
+        *arbiter_lock_hash,
+    ];
+    let authorized_parties = parties
+        .iter()
+        .filter(|lock_hash| authorized_lock_hashes.contains(*lock_hash))
+        .copied()
+        .collect::<Vec<_>>();
+    let not_matched_error = if cheque_witness_is_none {
+        Error::NoMatchedInputs
+    } else {
+        Error::NoMatchedSignature
+    };
+
+    let cheque_witness_input_type = helper::load_cheque_witness_input_type();
+    match cheque_witness_input_type.first() {
+        Some(&action) if action == ESCROW_ACTION_RELEASE || action == ESCROW_ACTION_REFUND => {
+            if authorized_parties.len() < 2 {
+                return Err(not_matched_error);
+            }
+            if action == ESCROW_ACTION_RELEASE {
+                claim::validate_escrow(cheque_args)?;
+            } else {
+                withdraw::validate_escrow(cheque_args)?;
+            }
+            if cheque_witness_is_none {
+                for lock_hash in authorized_parties.iter() {
+                    match helper::position_input_by_lock_hash(lock_hash) {
+                        Some(position) => helper::check_witness_args(position)?,
+                        None => return Err(Error::NoMatchedInputs),
+                    }
+                }
+            }
+            Ok(())
+        }
+        _ => {
+            if authorized_parties.contains(&cheque_args.sender_lock_hash) {
+                withdraw::validate(cheque_args, cheque_witness_is_none)
+            } else {
+                Err(not_matched_error)
+            }
+        }
+    }
+}
+
 fn check_cheque_cell_witness_is_none() -> Result<bool, Error> {
     match load_witness_args(0, Source::GroupInput) {
         Ok(witness_args) => Ok(witness_args.lock().to_opt().is_none()),
         Err(_) => Ok(true),
     }
 }
+
+// The salt of the receiver follows the claim action in the input_type of the cheque witness
+fn load_receiver_salt(cheque_args: &ChequeArgs) -> Option<[u8; SALT_LEN]> {
+    if !cheque_args.has_flag(FLAG_SALTED) {
+        return None;
+    }
+    witness::parse_receiver_salt(&helper::load_cheque_witness_input_type())
+}
+
+fn match_receiver_lock_hash(
+    cheque_args: &ChequeArgs,
+    lock_hash: &[u8; 20],
+    receiver_salt: &Option<[u8; SALT_LEN]>,
+) -> bool {
+    if !cheque_args.has_flag(FLAG_SALTED) {
+        return &cheque_args.receiver_lock_hash == lock_hash;
+    }
+    match receiver_salt {
+        Some(salt) => cheque_args.receiver_lock_hash == salted_lock_hash(lock_hash, salt),
+        None => false,
+    }
+}
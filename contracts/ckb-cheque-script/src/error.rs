@@ -1,32 +0,0 @@
-use ckb_std::error::SysError;
-
-/// Error
-#[repr(i8)]
-pub enum Error {
-    IndexOutOfBound = 1,
-    ItemMissing,
-    LengthNotEnough,
-    Encoding,
-    InvalidArgument = 5,
-    NoMatchedInputs,
-    WitnessSignatureWrong,
-    SenderCapacityNotSame,
-    WrongPubKey,
-    ClaimChequeInputSinceNotZero = 10,
-    WithdrawChequeInputSinceError,
-    Secp256k1,
-    NoMatchedSignature,
-}
-
-impl From<SysError> for Error {
-    fn from(err: SysError) -> Self {
-        use SysError::*;
-        match err {
-            IndexOutOfBound => Self::IndexOutOfBound,
-            ItemMissing => Self::ItemMissing,
-            LengthNotEnough(_) => Self::LengthNotEnough,
-            Encoding => Self::Encoding,
-            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
-        }
-    }
-}
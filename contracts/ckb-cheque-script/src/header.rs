@@ -0,0 +1,75 @@
+use super::helper;
+use cheque_core::error::Error;
+use cheque_core::since::{self, SINCE_METRIC_EPOCH, SINCE_VALUE_MASK};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{packed::Header, prelude::*},
+    high_level::{load_header, QueryIter},
+};
+use core::result::Result;
+
+// The header of the block in which the cheque cell was created must be in the header deps
+pub fn load_group_input_header(index: usize) -> Result<Header, Error> {
+    Ok(load_header(index, Source::GroupInput)?)
+}
+
+pub fn load_group_input_epoch(index: usize) -> Result<u64, Error> {
+    Ok(load_group_input_header(index)?.raw().epoch().unpack())
+}
+
+pub fn load_group_input_number(index: usize) -> Result<u64, Error> {
+    Ok(load_group_input_header(index)?.raw().number().unpack())
+}
+
+// The latest header of the header deps proves that the transaction is committed after its block
+pub fn load_latest_header_dep() -> Option<Header> {
+    QueryIter::new(load_header, Source::HeaderDep).max_by_key(|header| {
+        let number: u64 = header.raw().number().unpack();
+        number
+    })
+}
+
+// The receiver proves the epoch of claiming with the same absolute epoch since of all the cheque
+// inputs, or with the latest header of the header deps if the since is zero.
+pub fn load_claim_epoch() -> Option<u64> {
+    let cheque_inputs_since = helper::load_group_inputs_since();
+    let epoch_since = *cheque_inputs_since.first()?;
+    if cheque_inputs_since.iter().any(|since| *since != epoch_since) {
+        return None;
+    }
+    if epoch_since == 0 {
+        return load_latest_header_dep().map(|header| header.raw().epoch().unpack());
+    }
+    if epoch_since & !SINCE_VALUE_MASK != SINCE_METRIC_EPOCH {
+        return None;
+    }
+    Some(epoch_since & SINCE_VALUE_MASK)
+}
+
+// Convert a relative epoch since into the absolute epoch since of the deadline counted from the
+// epoch in which the cheque cell was created.
+pub fn load_group_input_deadline(index: usize, relative_since: u64) -> Result<u64, Error> {
+    since::relative_epoch_to_absolute(relative_since, load_group_input_epoch(index)?)
+        .ok_or(Error::InvalidArgument)
+}
+
+// The elapsed blocks from the creation of the cheque cell to the latest header of the header deps,
+// which only proves that at least as many blocks have passed
+pub fn elapsed_blocks_since_creation(index: usize) -> Result<Option<u64>, Error> {
+    let created_number = load_group_input_number(index)?;
+    Ok(load_latest_header_dep().and_then(|header| {
+        let number: u64 = header.raw().number().unpack();
+        number.checked_sub(created_number)
+    }))
+}
+
+// The elapsed epochs from the creation of the cheque cell to the latest header of the header deps
+// as a fixed point number in units of 1/65536 epoch
+pub fn elapsed_epochs_since_creation(index: usize) -> Result<Option<u64>, Error> {
+    let created_epoch = load_group_input_epoch(index)?;
+    Ok(load_latest_header_dep().and_then(|header| {
+        let epoch: u64 = header.raw().epoch().unpack();
+        since::epoch_to_fixed_point(epoch & SINCE_VALUE_MASK)
+            .checked_sub(since::epoch_to_fixed_point(created_epoch))
+    }))
+}
@@ -1,18 +1,39 @@
 use ckb_std::{
     ckb_constants::Source,
-    ckb_types::{packed::*, prelude::*},
-    high_level::{load_cell, load_input_since, load_witness_args, QueryIter},
+    ckb_types::{bytes::Bytes, packed::*, prelude::*},
+    high_level::{
+        load_cell, load_cell_data, load_cell_type_hash, load_input_since, load_script,
+        load_witness_args, QueryIter,
+    },
 };
 
-use super::hash;
-use crate::error::Error;
+use super::dao;
+use super::otx;
 use alloc::vec::Vec;
+use cheque_core::args::{ChequeArgs, FLAG_DAO, FLAG_NFT};
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE};
+use cheque_core::error::Error;
+use cheque_core::hash;
+use cheque_core::otx::{is_otx_signature, receiver_bound_lock_hashes, sender_bound_lock_hashes};
+use cheque_core::witness::MAX_SIGNATURES_COUNT;
 
 pub fn has_input_by_lock_hash(lock_hash: &[u8; 20]) -> bool {
     QueryIter::new(load_cell, Source::Input)
         .any(|cell| &hash::blake2b_160(cell.lock().as_slice()) == lock_hash)
 }
 
+pub fn find_input_lock_hash<F: Fn(&[u8; 20]) -> bool>(predicate: F) -> Option<[u8; 20]> {
+    QueryIter::new(load_cell, Source::Input)
+        .map(|cell| hash::blake2b_160(cell.lock().as_slice()))
+        .find(|lock_hash| predicate(lock_hash))
+}
+
+pub fn load_inputs_lock_hashes() -> Vec<[u8; 20]> {
+    QueryIter::new(load_cell, Source::Input)
+        .map(|cell| hash::blake2b_160(cell.lock().as_slice()))
+        .collect()
+}
+
 pub fn position_input_by_lock_hash(lock_hash: &[u8; 20]) -> Option<usize> {
     QueryIter::new(load_cell, Source::Input)
         .position(|cell| &hash::blake2b_160(cell.lock().as_slice()) == lock_hash)
@@ -22,6 +43,38 @@ pub fn load_group_inputs_since() -> Vec<u64> {
     QueryIter::new(load_input_since, Source::GroupInput).collect::<Vec<_>>()
 }
 
+// The input_type of the first witness of the cheque cells carries the extra data of the cheque
+pub fn load_cheque_witness_input_type() -> Bytes {
+    load_witness_input_type(0, Source::GroupInput)
+}
+
+fn load_witness_input_type(index: usize, source: Source) -> Bytes {
+    match load_witness_args(index, source) {
+        Ok(witness_args) => match witness_args.input_type().to_opt() {
+            Some(input_type) => input_type.unpack(),
+            None => Bytes::new(),
+        },
+        Err(_) => Bytes::new(),
+    }
+}
+
+// The lock of the first witness of the cheque cells carries the signature of the cheque
+fn load_cheque_witness_lock() -> Bytes {
+    match load_witness_args(0, Source::GroupInput) {
+        Ok(witness_args) => match witness_args.lock().to_opt() {
+            Some(lock) => lock.unpack(),
+            None => Bytes::new(),
+        },
+        Err(_) => Bytes::new(),
+    }
+}
+
+// The OTX signature binds only the outputs of the signer, so the cheque cells merged with other
+// transactions must not count the same outputs twice
+pub fn is_otx_signed() -> bool {
+    is_otx_signature(&load_cheque_witness_lock())
+}
+
 fn add_capacity(c1: u64, c2: u64) -> Result<u64, Error> {
     match c1.checked_add(c2) {
         Some(sum) => Ok(sum),
@@ -35,6 +88,216 @@ pub fn sum_cells_capacity_of_lock_hash(lock_hash: &[u8; 20], source: Source) ->
         .try_fold(0, |sum, c| add_capacity(sum, c.capacity().unpack()))
 }
 
+pub fn sum_group_inputs_capacity() -> Result<u64, Error> {
+    QueryIter::new(load_cell, Source::GroupInput)
+        .try_fold(0, |sum, c| add_capacity(sum, c.capacity().unpack()))
+}
+
+// The outputs of the lock hash must hold at least the capacity more than the inputs of the lock
+// hash, so the capacity cannot be paid by the own inputs of the lock hash
+pub fn check_capacity_to_lock_hash(lock_hash: &[u8; 20], capacity: u64) -> Result<bool, Error> {
+    let sum_inputs_capacity = sum_cells_capacity_of_lock_hash(lock_hash, Source::Input)?;
+    let sum_outputs_capacity = sum_cells_capacity_of_lock_hash(lock_hash, Source::Output)?;
+    Ok(sum_outputs_capacity
+        .checked_sub(sum_inputs_capacity)
+        .map_or(false, |net_capacity| net_capacity >= capacity))
+}
+
+pub fn sum_group_inputs_capacity_without_type() -> Result<u64, Error> {
+    QueryIter::new(load_cell, Source::GroupInput)
+        .filter(|cell| cell.type_().to_opt().is_none())
+        .try_fold(0, |sum, c| add_capacity(sum, c.capacity().unpack()))
+}
+
+// A cheque input of the transaction, which is either in the group of the running script or in
+// the group of another cheque lock args
+pub struct ChequeInput {
+    pub cell:        CellOutput,
+    pub data:        Vec<u8>,
+    pub since:       u64,
+    pub cheque_args: ChequeArgs,
+    pub in_group:    bool,
+}
+
+impl ChequeInput {
+    // The input_type of the cheque witness of the group of the cheque input, which is the witness
+    // of the first input with the same lock
+    pub fn load_cheque_witness_input_type(&self) -> Bytes {
+        let lock = self.cell.lock();
+        QueryIter::new(load_cell, Source::Input)
+            .position(|cell| cell.lock().as_slice() == lock.as_slice())
+            .map_or(Bytes::new(), |index| {
+                load_witness_input_type(index, Source::Input)
+            })
+    }
+}
+
+// Every input of the cheque script whose lock args are valid, so the groups paying the same lock
+// hash can count the cheque cells of each other
+pub fn load_cheque_inputs() -> Result<Vec<ChequeInput>, Error> {
+    let script = load_script()?;
+    let cheque_inputs = QueryIter::new(load_cell, Source::Input)
+        .zip(QueryIter::new(load_cell_data, Source::Input))
+        .zip(QueryIter::new(load_input_since, Source::Input))
+        .filter_map(|((cell, data), since)| {
+            let lock = cell.lock();
+            if lock.code_hash().as_slice() != script.code_hash().as_slice()
+                || lock.hash_type().as_slice() != script.hash_type().as_slice()
+            {
+                return None;
+            }
+            let args: Bytes = lock.args().unpack();
+            let cheque_args = ChequeArgs::from_slice(&args).ok()?;
+            let in_group = lock.as_slice() == script.as_slice();
+            Some(ChequeInput {
+                cell,
+                data,
+                since,
+                cheque_args,
+                in_group,
+            })
+        })
+        .collect();
+    Ok(cheque_inputs)
+}
+
+pub fn sum_cheque_inputs_capacity<F: Fn(&ChequeInput) -> bool>(predicate: F) -> Result<u64, Error> {
+    load_cheque_inputs()?
+        .iter()
+        .filter(|cheque_input| predicate(cheque_input))
+        .try_fold(0, |sum, cheque_input| {
+            add_capacity(sum, cheque_input.cell.capacity().unpack())
+        })
+}
+
+const UDT_AMOUNT_LEN: usize = 16;
+
+fn parse_udt_amount(data: &[u8]) -> Result<u128, Error> {
+    if data.len() < UDT_AMOUNT_LEN {
+        return Err(Error::Encoding);
+    }
+    let mut buf = [0u8; UDT_AMOUNT_LEN];
+    buf.copy_from_slice(&data[0..UDT_AMOUNT_LEN]);
+    Ok(u128::from_le_bytes(buf))
+}
+
+fn add_amount(a1: u128, a2: u128) -> Result<u128, Error> {
+    match a1.checked_add(a2) {
+        Some(sum) => Ok(sum),
+        None => Err(Error::Encoding),
+    }
+}
+
+fn sum_udt_amount_of_lock_hash(lock_hash: &[u8; 20], type_script: &Script) -> Result<u128, Error> {
+    QueryIter::new(load_cell, Source::Output)
+        .zip(QueryIter::new(load_cell_data, Source::Output))
+        .filter(|(cell, _)| {
+            &hash::blake2b_160(cell.lock().as_slice()) == lock_hash
+                && cell
+                    .type_()
+                    .to_opt()
+                    .map_or(false, |script| script.as_slice() == type_script.as_slice())
+        })
+        .try_fold(0, |sum, (_, data)| add_amount(sum, parse_udt_amount(&data)?))
+}
+
+// The assets of the cheque cells of the group, and of the cheque cells of other groups paid to the
+// same lock hash, must go to the outputs of the lock hash. The assets are summed over all of them,
+// so one output cannot be counted by several groups, like the groups merged by OTX signatures.
+pub fn check_group_assets_to_lock_hash<F: Fn(&ChequeInput) -> bool>(
+    cheque_args: &ChequeArgs,
+    lock_hash: &[u8; 20],
+    is_paid_to_lock_hash: F,
+) -> Result<bool, Error> {
+    if cheque_args.has_flag(FLAG_DAO) && !dao::check_group_dao_deposits_to_lock_hash(lock_hash)? {
+        return Ok(false);
+    }
+
+    // The DAO cheque cells are checked on their own and carry neither UDT nor NFT
+    let (nft_inputs, udt_inputs): (Vec<_>, Vec<_>) = load_cheque_inputs()?
+        .into_iter()
+        .filter(|cheque_input| {
+            let cell = &cheque_input.cell;
+            (cheque_input.in_group || is_paid_to_lock_hash(cheque_input))
+                && cell.type_().to_opt().is_some()
+                && !(cheque_input.cheque_args.has_flag(FLAG_DAO) && dao::is_dao_cell(cell))
+        })
+        .partition(|cheque_input| cheque_input.cheque_args.has_flag(FLAG_NFT));
+    Ok(check_udts_to_lock_hash(lock_hash, &udt_inputs)?
+        && check_nfts_to_lock_hash(lock_hash, &nft_inputs))
+}
+
+// Every UDT type carried by the cheque cells must go to the outputs of the lock hash with at least
+// the same amount.
+fn check_udts_to_lock_hash(
+    lock_hash: &[u8; 20],
+    cheque_inputs: &[ChequeInput],
+) -> Result<bool, Error> {
+    let mut assets: Vec<(Script, u128)> = Vec::new();
+    for cheque_input in cheque_inputs.iter() {
+        if let Some(type_script) = cheque_input.cell.type_().to_opt() {
+            let amount = parse_udt_amount(&cheque_input.data)?;
+            match assets
+                .iter_mut()
+                .find(|(script, _)| script.as_slice() == type_script.as_slice())
+            {
+                Some((_, sum)) => *sum = add_amount(*sum, amount)?,
+                None => assets.push((type_script, amount)),
+            }
+        }
+    }
+
+    for (type_script, amount) in assets.iter() {
+        if sum_udt_amount_of_lock_hash(lock_hash, type_script)? < *amount {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Every cheque cell with type script must have its own output of the lock hash with exactly the
+// same type script and cell data.
+fn check_nfts_to_lock_hash(lock_hash: &[u8; 20], cheque_inputs: &[ChequeInput]) -> bool {
+    let mut outputs = QueryIter::new(load_cell, Source::Output)
+        .zip(QueryIter::new(load_cell_data, Source::Output))
+        .filter(|(cell, _)| {
+            cell.type_().to_opt().is_some()
+                && &hash::blake2b_160(cell.lock().as_slice()) == lock_hash
+        })
+        .map(|(cell, data)| Some((cell.type_(), data)))
+        .collect::<Vec<_>>();
+
+    for cheque_input in cheque_inputs.iter() {
+        let position = outputs.iter().position(|output| match output {
+            Some((type_script, output_data)) => {
+                type_script.as_slice() == cheque_input.cell.type_().as_slice()
+                    && output_data == &cheque_input.data
+            }
+            None => false,
+        });
+        match position {
+            // Every output can only keep one cheque cell
+            Some(index) => outputs[index] = None,
+            None => return false,
+        }
+    }
+    true
+}
+
+// The data of the oracle cell is a bitmap of the reached milestones, and the lowest bit of
+// the first byte is the milestone 0.
+pub fn check_oracle_milestone_reached(oracle_type_hash: &[u8; 32], milestone: u8) -> bool {
+    let byte_index = (milestone / 8) as usize;
+    let bit = 1u8 << (milestone % 8);
+    QueryIter::new(load_cell_type_hash, Source::CellDep)
+        .enumerate()
+        .filter(|(_, type_hash)| type_hash.as_ref() == Some(oracle_type_hash))
+        .any(|(index, _)| match load_cell_data(index, Source::CellDep) {
+            Ok(data) => data.get(byte_index).map_or(false, |byte| byte & bit != 0),
+            Err(_) => false,
+        })
+}
+
 pub fn check_witness_args(position: usize) -> Result<(), Error> {
     match load_witness_args(position, Source::Input) {
         Ok(witness_args) => {
@@ -48,35 +311,59 @@ pub fn check_witness_args(position: usize) -> Result<(), Error> {
     }
 }
 
-const TYPE: u8 = 1;
-const CODE_HASH_SECP256K1_BLAKE160: [u8; 32] = [
-    155, 215, 224, 111, 62, 207, 75, 224, 242, 252, 210, 24, 139, 35, 241, 185, 252, 200, 142, 93,
-    75, 101, 168, 99, 123, 23, 114, 59, 189, 163, 204, 232,
-];
-// Recover public key from the signature 
-// and check whether the public key belongs to the receiver or the sender.
-pub fn validate_signature_of_receiver_and_sender(
-    receiver_lock_hash: &[u8; 20],
-    sender_lock_hash: &[u8; 20],
-) -> Result<bool, Error> {
+// Recover public key from the signature and return the lock hash of the signer, and the signature
+// signs the whole transaction unless it is an OTX signature.
+pub fn recover_signer_lock_hash(cheque_args: &ChequeArgs) -> Result<[u8; 20], Error> {
+    let witness_lock = load_cheque_witness_lock();
+    if is_otx_signature(&witness_lock) {
+        // The receiver and the sender sign the outputs bound to themselves, so the signature is
+        // recovered with the message of the receiver first and then with the message of the sender
+        let message = otx::signing_message(cheque_args, &receiver_bound_lock_hashes(cheque_args))?;
+        let lock_hash = recover_otx_signer_lock_hash(&message, &witness_lock[1..]);
+        if lock_hash.as_ref() == Ok(&cheque_args.receiver_lock_hash) {
+            return lock_hash;
+        }
+        let message = otx::signing_message(cheque_args, &sender_bound_lock_hashes(cheque_args))?;
+        return recover_otx_signer_lock_hash(&message, &witness_lock[1..]);
+    }
+
+    let mut public_key_hash = [0u8; 20];
+    validate_blake2b_signature(&mut public_key_hash).map_err(|_| Error::Secp256k1)?;
+    Ok(secp256k1_lock_hash(&public_key_hash))
+}
+
+fn recover_otx_signer_lock_hash(message: &[u8; 32], signature: &[u8]) -> Result<[u8; 20], Error> {
     let mut public_key_hash = [0u8; 20];
-    validate_blake2b_signature(&mut public_key_hash)
+    recover_blake2b_signature(message, signature, &mut public_key_hash)
+        .map_err(|_| Error::Secp256k1)?;
+    Ok(secp256k1_lock_hash(&public_key_hash))
+}
+
+// Recover public keys from the multiple signatures and return the lock hashes of the signers
+pub fn recover_signer_lock_hashes() -> Result<Vec<[u8; 20]>, Error> {
+    let mut public_key_hashes = [0u8; 20 * MAX_SIGNATURES_COUNT];
+    let signatures_count = validate_blake2b_signatures(&mut public_key_hashes)
         .map_err(|_| Error::Secp256k1)?;
 
+    let signer_lock_hashes = public_key_hashes
+        .chunks(20)
+        .take(signatures_count)
+        .map(|chunk| {
+            let mut public_key_hash = [0u8; 20];
+            public_key_hash.copy_from_slice(chunk);
+            secp256k1_lock_hash(&public_key_hash)
+        })
+        .collect();
+    Ok(signer_lock_hashes)
+}
+
+fn secp256k1_lock_hash(public_key_hash: &[u8; 20]) -> [u8; 20] {
     let lock_script = Script::new_builder()
         .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
         .args(public_key_hash.pack())
-        .hash_type(Byte::new(TYPE))
+        .hash_type(Byte::new(HASH_TYPE_TYPE))
         .build();
-    let lock_hash = hash::blake2b_160(lock_script.as_slice());
-
-    if receiver_lock_hash == &lock_hash {
-        Ok(true)
-    } else if sender_lock_hash == &lock_hash {
-        Ok(false)
-    } else {
-        Err(Error::WrongPubKey)
-    }
+    hash::blake2b_160(lock_script.as_slice())
 }
 
 const CKB_SUCCESS: i32 = 0;
@@ -84,6 +371,15 @@ const CKB_SUCCESS: i32 = 0;
 #[link(name = "ckb-lib-secp256k1", kind="static")]
 extern "C" {
     fn validate_secp256k1_blake2b_sighash_all(pubkey_hash: *const u8) -> i32;
+    fn validate_secp256k1_blake2b_sighash_all_multiple(
+        pubkey_hashes: *const u8,
+        signatures_count: *mut u64,
+    ) -> i32;
+    fn recover_secp256k1_blake160(
+        message: *const u8,
+        signature: *const u8,
+        pubkey_hash: *mut u8,
+    ) -> i32;
 }
 
 fn validate_blake2b_signature(pubkey_hash: &mut [u8; 20]) -> Result<(), i32> {
@@ -94,3 +390,35 @@ fn validate_blake2b_signature(pubkey_hash: &mut [u8; 20]) -> Result<(), i32> {
     }
     Ok(())
 }
+
+fn recover_blake2b_signature(
+    message: &[u8; 32],
+    signature: &[u8],
+    pubkey_hash: &mut [u8; 20],
+) -> Result<(), i32> {
+    let error_code = unsafe {
+        recover_secp256k1_blake160(message.as_ptr(), signature.as_ptr(), pubkey_hash.as_mut_ptr())
+    };
+
+    if error_code != CKB_SUCCESS {
+        return Err(error_code);
+    }
+    Ok(())
+}
+
+fn validate_blake2b_signatures(
+    pubkey_hashes: &mut [u8; 20 * MAX_SIGNATURES_COUNT],
+) -> Result<usize, i32> {
+    let mut signatures_count: u64 = 0;
+    let error_code = unsafe {
+        validate_secp256k1_blake2b_sighash_all_multiple(
+            pubkey_hashes.as_mut_ptr(),
+            &mut signatures_count,
+        )
+    };
+
+    if error_code != CKB_SUCCESS {
+        return Err(error_code);
+    }
+    Ok(signatures_count as usize)
+}
@@ -2,7 +2,7 @@
 //!
 //! `main.rs` is used to define rust lang items and modules.
 //! See `entry.rs` for the `main` function.
-//! See `error.rs` for the `Error` type.
+//! See `cheque-core` for the lock args codec and the `Error` type.
 
 #![no_std]
 #![no_main]
@@ -12,10 +12,13 @@
 
 // define modules
 mod claim;
+mod dao;
 mod entry;
-mod error;
-mod hash;
+mod header;
 mod helper;
+mod otx;
+mod subscription;
+mod vesting;
 mod withdraw;
 
 use ckb_std::default_alloc;
@@ -0,0 +1,54 @@
+use super::helper;
+use alloc::vec::Vec;
+use cheque_core::args::{ChequeArgs, FLAG_SALTED};
+use cheque_core::error::Error;
+use cheque_core::hash;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::prelude::*,
+    high_level::{load_cell, load_cell_data, load_input, load_script, load_script_hash, QueryIter},
+};
+use core::result::Result;
+
+// The OTX signing message of the cheque group with the outputs bound to the lock hashes of the
+// signer, see cheque_core::otx::signing_message
+pub fn signing_message(
+    cheque_args: &ChequeArgs,
+    bound_lock_hashes: &[[u8; 20]],
+) -> Result<[u8; 32], Error> {
+    // The outputs of a salted receiver cannot be bound before the receiver is revealed
+    if cheque_args.has_flag(FLAG_SALTED) {
+        return Err(Error::InvalidArgument);
+    }
+
+    let group_inputs = QueryIter::new(load_input, Source::GroupInput)
+        .map(|input| (input.previous_output(), input.since()))
+        .collect::<Vec<_>>();
+
+    let script = load_script()?;
+    let bound_outputs = QueryIter::new(load_cell, Source::Output)
+        .zip(QueryIter::new(load_cell_data, Source::Output))
+        .filter(|(cell, _)| {
+            let lock = cell.lock();
+            bound_lock_hashes.contains(&hash::blake2b_160(lock.as_slice()))
+                || (lock.code_hash().as_slice() == script.code_hash().as_slice()
+                    && lock.hash_type().as_slice() == script.hash_type().as_slice())
+        })
+        .collect::<Vec<_>>();
+
+    let inputs = group_inputs
+        .iter()
+        .map(|(previous_output, since)| (previous_output.as_slice(), since.as_slice()))
+        .collect::<Vec<_>>();
+    let outputs = bound_outputs
+        .iter()
+        .map(|(cell, data)| (cell.as_slice(), &data[..]))
+        .collect::<Vec<_>>();
+    let input_type = helper::load_cheque_witness_input_type();
+    Ok(cheque_core::otx::signing_message(
+        &load_script_hash()?,
+        &inputs,
+        &outputs,
+        &input_type,
+    ))
+}
@@ -0,0 +1,96 @@
+use core::result::Result;
+
+use super::header;
+use alloc::vec::Vec;
+use cheque_core::args::Subscription;
+use cheque_core::error::Error;
+use cheque_core::since;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::prelude::*,
+    high_level::{load_cell, load_cell_data, load_script, QueryIter},
+};
+
+// The data of the subscription cheque cell is the number of the pulled periods(u64 little endian),
+// and the empty data of a new subscription cheque cell is zero
+const PERIOD_COUNTER_LEN: usize = 8;
+
+const CAPACITY_LEN: usize = 8;
+const CODE_HASH_LEN: usize = 32;
+const HASH_TYPE_LEN: usize = 1;
+const SHANNONS_PER_BYTE: u64 = 100_000_000;
+
+fn parse_period_counter(data: &[u8]) -> Option<u64> {
+    match data.len() {
+        0 => Some(0),
+        PERIOD_COUNTER_LEN => {
+            let mut buf = [0u8; PERIOD_COUNTER_LEN];
+            buf.copy_from_slice(data);
+            Some(u64::from_le_bytes(buf))
+        }
+        _ => None,
+    }
+}
+
+// The period of the counter starts at start_epoch + counter * period_epochs as a fixed point number
+fn period_start(subscription: &Subscription, counter: u64) -> Option<u64> {
+    let elapsed = counter
+        .checked_mul(subscription.period_epochs)?
+        .checked_mul(1 << 16)?;
+    since::epoch_to_fixed_point(subscription.start_epoch).checked_add(elapsed)
+}
+
+// The receiver pulls the amount of the next period once the epoch of claiming reaches its start,
+// which is proved like the vesting epoch. The only subscription cheque cell of the group must be
+// recreated with the same lock, the capacity reduced by the amount and the counter bumped by one,
+// unless the rest capacity cannot hold the recreated cheque cell, then the receiver pulls it all.
+pub fn check_pull(subscription: &Subscription) -> Result<bool, Error> {
+    let cheque_inputs = QueryIter::new(load_cell, Source::GroupInput)
+        .zip(QueryIter::new(load_cell_data, Source::GroupInput))
+        .collect::<Vec<_>>();
+    if cheque_inputs.len() != 1 {
+        return Ok(false);
+    }
+    let (cell, data) = &cheque_inputs[0];
+    if cell.type_().to_opt().is_some() {
+        return Ok(false);
+    }
+    let counter = match parse_period_counter(data) {
+        Some(counter) => counter,
+        None => return Ok(false),
+    };
+
+    let claim_epoch = match header::load_claim_epoch() {
+        Some(epoch) => epoch,
+        None => return Ok(false),
+    };
+    match period_start(subscription, counter) {
+        Some(start) if since::epoch_to_fixed_point(claim_epoch) >= start => {}
+        _ => return Ok(false),
+    }
+
+    let script = load_script()?;
+    let capacity: u64 = cell.capacity().unpack();
+    let occupied_bytes = CAPACITY_LEN
+        + CODE_HASH_LEN
+        + HASH_TYPE_LEN
+        + script.args().raw_data().len()
+        + PERIOD_COUNTER_LEN;
+    let rest_capacity = capacity.saturating_sub(subscription.amount);
+    if rest_capacity < occupied_bytes as u64 * SHANNONS_PER_BYTE {
+        return Ok(true);
+    }
+
+    let cheque_outputs = QueryIter::new(load_cell, Source::Output)
+        .zip(QueryIter::new(load_cell_data, Source::Output))
+        .filter(|(output, _)| output.lock().as_slice() == script.as_slice())
+        .collect::<Vec<_>>();
+    if cheque_outputs.len() != 1 {
+        return Ok(false);
+    }
+    let (output, output_data) = &cheque_outputs[0];
+    let output_capacity: u64 = output.capacity().unpack();
+    Ok(output.type_().to_opt().is_none()
+        && output_capacity == rest_capacity
+        && output_data.as_slice() == (counter + 1).to_le_bytes())
+}
@@ -0,0 +1,107 @@
+use core::cmp::Ordering;
+use core::result::Result;
+
+use super::header;
+use super::helper;
+use cheque_core::args::{ChequeArgs, Vesting, FLAG_VESTING, FLAG_WITHDRAW_SINCE};
+use cheque_core::error::Error;
+use cheque_core::since::{self, SINCE_METRIC_EPOCH, SINCE_VALUE_MASK};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    high_level::{load_cell, load_script, QueryIter},
+};
+
+// The vested capacity grows linearly from zero at the start epoch to the total capacity at the end epoch
+pub fn vested_capacity(vesting: &Vesting, epoch: u64) -> u64 {
+    let start = since::epoch_to_fixed_point(vesting.start_epoch);
+    let end = since::epoch_to_fixed_point(vesting.end_epoch);
+    let current = since::epoch_to_fixed_point(epoch);
+    if current <= start {
+        0
+    } else if current >= end {
+        vesting.total_capacity
+    } else {
+        (vesting.total_capacity as u128 * (current - start) as u128 / (end - start) as u128) as u64
+    }
+}
+
+// The receiver proves the current epoch with the absolute epoch since of the cheque inputs, or with
+// the latest header of the header deps if the since is zero, and the capacity which has not vested
+// at that epoch must go back to the cheque outputs of the same lock.
+pub fn check_claim(vesting: &Vesting) -> Result<bool, Error> {
+    let claim_epoch = match header::load_claim_epoch() {
+        Some(epoch) => epoch,
+        None => return Ok(false),
+    };
+
+    let unvested_capacity = vesting.total_capacity - vested_capacity(vesting, claim_epoch);
+    let script_args: Bytes = load_script()?.args().unpack();
+    let continuing_capacity = sum_cheque_outputs_capacity_without_type(&script_args)?;
+    Ok(continuing_capacity >= unvested_capacity)
+}
+
+// The sender can only withdraw the capacity which has not vested at the withdraw deadline, and the
+// vested capacity which has not been claimed must go to a cheque output without vesting, so the
+// receiver can still claim it as a normal cheque.
+pub fn check_withdraw(cheque_args: &ChequeArgs, receiver_capacity: u64) -> Result<bool, Error> {
+    if receiver_capacity == 0 {
+        return Ok(true);
+    }
+
+    let vested_cheque_args = ChequeArgs {
+        flags: cheque_args.flags & !(FLAG_VESTING | FLAG_WITHDRAW_SINCE),
+        withdraw_since: None,
+        vesting: None,
+        ..cheque_args.clone()
+    }
+    .to_bytes();
+    let vested_cheque_capacity = sum_cheque_outputs_capacity_without_type(&vested_cheque_args)?;
+    Ok(vested_cheque_capacity >= receiver_capacity)
+}
+
+// The capacity vested at the withdraw deadline which has not been claimed by the receiver
+pub fn withdraw_receiver_capacity(vesting: &Vesting, withdraw_since: u64) -> Result<u64, Error> {
+    let vested = vested_capacity(vesting, load_withdraw_epoch(withdraw_since)?);
+    let claimed = vesting
+        .total_capacity
+        .saturating_sub(helper::sum_group_inputs_capacity_without_type()?);
+    Ok(vested.saturating_sub(claimed))
+}
+
+// The withdraw deadline is the absolute epoch of the withdraw since, and a relative withdraw since
+// counts from the latest epoch in which the cheque cells were created.
+fn load_withdraw_epoch(withdraw_since: u64) -> Result<u64, Error> {
+    if withdraw_since & !SINCE_VALUE_MASK == SINCE_METRIC_EPOCH {
+        return Ok(withdraw_since & SINCE_VALUE_MASK);
+    }
+
+    let mut withdraw_epoch: Option<u64> = None;
+    for index in 0..helper::load_group_inputs_since().len() {
+        let deadline = header::load_group_input_deadline(index, withdraw_since)? & SINCE_VALUE_MASK;
+        withdraw_epoch = match withdraw_epoch {
+            Some(epoch) if since::compare_epoch(epoch, deadline) != Ordering::Less => Some(epoch),
+            _ => Some(deadline),
+        };
+    }
+    withdraw_epoch.ok_or(Error::InvalidArgument)
+}
+
+// Sum the capacity of the outputs without type script whose lock is the cheque script with the lock args
+fn sum_cheque_outputs_capacity_without_type(lock_args: &[u8]) -> Result<u64, Error> {
+    let script = load_script()?;
+    let mut sum_capacity: u64 = 0;
+    for cell in QueryIter::new(load_cell, Source::Output) {
+        let lock = cell.lock();
+        let output_args: Bytes = lock.args().unpack();
+        if cell.type_().to_opt().is_none()
+            && lock.code_hash().as_slice() == script.code_hash().as_slice()
+            && lock.hash_type().as_slice() == script.hash_type().as_slice()
+            && output_args.as_ref() == lock_args
+        {
+            let capacity: u64 = cell.capacity().unpack();
+            sum_capacity = sum_capacity.checked_add(capacity).ok_or(Error::Encoding)?;
+        }
+    }
+    Ok(sum_capacity)
+}
@@ -1,17 +1,38 @@
-use super::helper;
-use crate::error::Error;
+use super::helper::{self, ChequeInput};
+use super::vesting;
+use cheque_core::args::ChequeArgs;
+use cheque_core::error::Error;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use cheque_core::witness::ESCROW_ACTION_REFUND;
 use core::result::Result;
 
-const CHEQUE_CELL_SINCE: u64 = 0xA000000000000006;
-
-pub fn validate(sender_lock_hash: &[u8; 20], cheque_witness_is_none: bool) -> Result<(), Error> {
+pub fn validate(cheque_args: &ChequeArgs, cheque_witness_is_none: bool) -> Result<(), Error> {
+    let withdraw_since = cheque_args.withdraw_since.unwrap_or(CHEQUE_CELL_SINCE);
     let cheque_inputs_since = helper::load_group_inputs_since();
-    if cheque_inputs_since.into_iter().any(|since| since != CHEQUE_CELL_SINCE) {
+    if cheque_inputs_since.into_iter().any(|since| since != withdraw_since) {
         return Err(Error::WithdrawChequeInputSinceError);
     }
 
+    check_withdraw_assets(cheque_args)?;
+
+    let mut receiver_capacity = 0;
+    if let Some(vesting) = cheque_args.vesting {
+        receiver_capacity = vesting::withdraw_receiver_capacity(&vesting, withdraw_since)?;
+        if !vesting::check_withdraw(cheque_args, receiver_capacity)? {
+            return Err(Error::VestingCapacityNotMatched);
+        }
+    }
+
+    // The sender decides where its own capacity goes, but not the capacity refunded to another lock
+    // or bound only by the OTX signature of the sender, which may be merged with other groups
+    if cheque_args.sender_refund_lock_hash() != &cheque_args.sender_lock_hash
+        || helper::is_otx_signed()
+    {
+        check_withdraw_capacity(cheque_args, receiver_capacity)?;
+    }
+
     if cheque_witness_is_none {
-        match helper::position_input_by_lock_hash(sender_lock_hash) {
+        match helper::position_input_by_lock_hash(&cheque_args.sender_lock_hash) {
             Some(position) => helper::check_witness_args(position),
             None => Err(Error::NoMatchedInputs),
         }
@@ -19,3 +40,50 @@ pub fn validate(sender_lock_hash: &[u8; 20], cheque_witness_is_none: bool) -> Re
         Ok(())
     }
 }
+
+// The escrow cheque cells are refunded to the sender with the authorization of two parties before
+// the withdraw since
+pub fn validate_escrow(cheque_args: &ChequeArgs) -> Result<(), Error> {
+    check_withdraw_assets(cheque_args)?;
+    check_withdraw_capacity(cheque_args, 0)
+}
+
+fn check_withdraw_assets(cheque_args: &ChequeArgs) -> Result<(), Error> {
+    let refund_lock_hash = cheque_args.sender_refund_lock_hash();
+    if !helper::check_group_assets_to_lock_hash(cheque_args, refund_lock_hash, |cheque_input| {
+        cheque_input.cheque_args.sender_refund_lock_hash() == refund_lock_hash
+            && is_refunded(cheque_input)
+    })? {
+        return Err(Error::ChequeAssetNotMatched);
+    }
+    Ok(())
+}
+
+// The capacity of the cheque cells, except the vested capacity left to the receiver, must go to the
+// refund lock hash, so the fee is paid by the other inputs. The capacity is summed over every
+// cheque cell refunded to the same lock hash, so one output cannot be counted by several groups,
+// and the cheque cells of the other groups count in full even if they are vesting.
+fn check_withdraw_capacity(cheque_args: &ChequeArgs, receiver_capacity: u64) -> Result<(), Error> {
+    let refund_lock_hash = cheque_args.sender_refund_lock_hash();
+    let refund_capacity = helper::sum_cheque_inputs_capacity(|cheque_input| {
+        cheque_input.cheque_args.sender_refund_lock_hash() == refund_lock_hash
+            && (cheque_input.in_group || is_refunded(cheque_input))
+    })?
+    .checked_sub(receiver_capacity)
+    .ok_or(Error::Encoding)?;
+    if !helper::check_capacity_to_lock_hash(refund_lock_hash, refund_capacity)? {
+        return Err(Error::ChequeAssetNotMatched);
+    }
+    Ok(())
+}
+
+// A cheque cell of another group is withdrawn with the since of its own withdraw since, or refunded
+// by the escrow action of its own cheque witness
+pub fn is_refunded(cheque_input: &ChequeInput) -> bool {
+    let input_args = &cheque_input.cheque_args;
+    if cheque_input.since == input_args.withdraw_since.unwrap_or(CHEQUE_CELL_SINCE) {
+        return true;
+    }
+    input_args.arbiter_lock_hash.is_some()
+        && cheque_input.load_cheque_witness_input_type().first() == Some(&ESCROW_ACTION_REFUND)
+}
@@ -2,14 +2,20 @@ extern crate alloc;
 
 #[path = "../../contracts/ckb-cheque-script/src/claim.rs"]
 mod claim;
+#[path = "../../contracts/ckb-cheque-script/src/dao.rs"]
+mod dao;
 #[path = "../../contracts/ckb-cheque-script/src/entry.rs"]
 mod entry;
-#[path = "../../contracts/ckb-cheque-script/src/error.rs"]
-mod error;
-#[path = "../../contracts/ckb-cheque-script/src/hash.rs"]
-mod hash;
+#[path = "../../contracts/ckb-cheque-script/src/header.rs"]
+mod header;
 #[path = "../../contracts/ckb-cheque-script/src/helper.rs"]
 mod helper;
+#[path = "../../contracts/ckb-cheque-script/src/otx.rs"]
+mod otx;
+#[path = "../../contracts/ckb-cheque-script/src/subscription.rs"]
+mod subscription;
+#[path = "../../contracts/ckb-cheque-script/src/vesting.rs"]
+mod vesting;
 #[path = "../../contracts/ckb-cheque-script/src/withdraw.rs"]
 mod withdraw;
 
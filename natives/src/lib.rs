@@ -0,0 +1,183 @@
+//! Verifies a script group of a `MockTransaction` in memory, without writing the `CKB_TX_FILE` and
+//! the `CKB_RUNNING_SETUP` or spawning the `ckb-cheque-script-sim` binary.
+//!
+//! The simulator keeps the transaction and the script group in its statics once per process, so
+//! the script group is run by the RISC-V binary of its cell dep in a `ckb-script` verifier instead,
+//! and the exit code is mapped back to the exact `Error` of the cheque lock script.
+
+use cheque_core::error::Error as ChequeError;
+use ckb_standalone_debugger::transaction::MockTransaction;
+use ckb_tool::ckb_error::Error as CkbError;
+use ckb_tool::ckb_script::{DataLoader, ScriptError, ScriptGroupType, TransactionScriptsVerifier};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{
+        cell::{CellMeta, CellMetaBuilder, ResolvedTransaction},
+        BlockExt, Cycle, DepType, EpochExt, HeaderView, TransactionInfo,
+    },
+    packed::{Byte32, CellOutput, OutPoint, OutPointVec, Script},
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::convert::TryFrom;
+
+/// The max cycles of all the transactions of a block on the mainnet
+pub const MAX_CYCLES: Cycle = 3_500_000_000;
+
+#[derive(Debug)]
+pub enum VerifyError {
+    // The script group exits with an error code of the cheque lock script
+    Script(ChequeError),
+    // The verifier fails without an error code of the cheque lock script, like exceeding the max
+    // cycles or loading a header which is not in the header deps
+    Verifier(CkbError),
+    // An input, a cell dep or a member of a dep group is not in the mock info
+    Resolve(OutPoint),
+    // The running setup points to no script of the transaction
+    ScriptNotFound,
+}
+
+/// Verifies the script group of the running setup, which is the same as the `CKB_RUNNING_SETUP`
+/// of the simulator, and returns the exact `Error` of the cheque lock script if it fails.
+///
+/// Every input and cell dep of the transaction, as well as every member of a dep group, is given
+/// by the mock info, and an input loads the header of its block only if the header is in the
+/// header deps. The since of the inputs is not verified.
+pub fn verify_mock_transaction(
+    mock_tx: &MockTransaction,
+    setup: &RunningSetup,
+) -> Result<(), VerifyError> {
+    let loader = MockDataLoader { mock_tx };
+    let (group_type, script) = loader.script(setup).ok_or(VerifyError::ScriptNotFound)?;
+    let rtx = loader.resolve()?;
+    let verifier = TransactionScriptsVerifier::new(&rtx, &loader);
+    match verifier.verify_single(group_type, &script.calc_script_hash(), MAX_CYCLES) {
+        Ok(_) => Ok(()),
+        Err(err) => match err.downcast_ref::<ScriptError>() {
+            Some(ScriptError::ValidationFailure(code)) => match ChequeError::try_from(*code) {
+                Ok(cheque_err) => Err(VerifyError::Script(cheque_err)),
+                Err(_) => Err(VerifyError::Verifier(err)),
+            },
+            _ => Err(VerifyError::Verifier(err)),
+        },
+    }
+}
+
+struct MockDataLoader<'a> {
+    mock_tx: &'a MockTransaction,
+}
+
+impl<'a> MockDataLoader<'a> {
+    fn script(&self, setup: &RunningSetup) -> Option<(ScriptGroupType, Script)> {
+        let index = setup.script_index as usize;
+        if setup.is_output {
+            let output = self.mock_tx.tx.raw().outputs().get(index)?;
+            return output
+                .type_()
+                .to_opt()
+                .map(|script| (ScriptGroupType::Type, script));
+        }
+        let input = self.mock_tx.mock_info.inputs.get(index)?;
+        if setup.is_lock_script {
+            Some((ScriptGroupType::Lock, input.output.lock()))
+        } else {
+            input
+                .output
+                .type_()
+                .to_opt()
+                .map(|script| (ScriptGroupType::Type, script))
+        }
+    }
+
+    // The cell and the block hash of the cell from the inputs or the cell deps of the mock info
+    fn cell(&self, out_point: &OutPoint) -> Option<(&CellOutput, &Bytes, Option<&Byte32>)> {
+        let mock_info = &self.mock_tx.mock_info;
+        mock_info
+            .inputs
+            .iter()
+            .find(|input| &input.input.previous_output() == out_point)
+            .map(|input| (&input.output, &input.data, input.header.as_ref()))
+            .or_else(|| {
+                mock_info
+                    .cell_deps
+                    .iter()
+                    .find(|cell_dep| &cell_dep.cell_dep.out_point() == out_point)
+                    .map(|cell_dep| (&cell_dep.output, &cell_dep.data, cell_dep.header.as_ref()))
+            })
+    }
+
+    fn cell_meta(&self, out_point: &OutPoint) -> Result<CellMeta, VerifyError> {
+        let (output, data, block_hash) = self
+            .cell(out_point)
+            .ok_or_else(|| VerifyError::Resolve(out_point.clone()))?;
+        let mut builder = CellMetaBuilder::from_cell_output(output.clone(), data.clone())
+            .out_point(out_point.clone());
+        if let Some(header) = block_hash.and_then(|block_hash| self.get_header(block_hash)) {
+            builder = builder.transaction_info(TransactionInfo::new(
+                header.number(),
+                header.epoch(),
+                header.hash(),
+                0,
+            ));
+        }
+        Ok(builder.build())
+    }
+
+    fn resolve(&self) -> Result<ResolvedTransaction, VerifyError> {
+        let tx = self.mock_tx.tx.clone().into_view();
+        let resolved_inputs = tx
+            .inputs()
+            .into_iter()
+            .map(|input| self.cell_meta(&input.previous_output()))
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+
+        let mut resolved_cell_deps = Vec::new();
+        let mut resolved_dep_groups = Vec::new();
+        for cell_dep in tx.cell_deps() {
+            let cell_meta = self.cell_meta(&cell_dep.out_point())?;
+            if cell_dep.dep_type() == DepType::DepGroup.into() {
+                let members = self
+                    .cell(&cell_dep.out_point())
+                    .and_then(|(_, data, _)| OutPointVec::from_slice(data).ok())
+                    .ok_or_else(|| VerifyError::Resolve(cell_dep.out_point()))?;
+                for member in members {
+                    resolved_cell_deps.push(self.cell_meta(&member)?);
+                }
+                resolved_dep_groups.push(cell_meta);
+            } else {
+                resolved_cell_deps.push(cell_meta);
+            }
+        }
+
+        Ok(ResolvedTransaction {
+            transaction: tx,
+            resolved_cell_deps,
+            resolved_inputs,
+            resolved_dep_groups,
+        })
+    }
+}
+
+impl<'a> DataLoader for MockDataLoader<'a> {
+    fn load_cell_data(&self, cell: &CellMeta) -> Option<(Bytes, Byte32)> {
+        self.cell(&cell.out_point)
+            .map(|(_, data, _)| (data.clone(), CellOutput::calc_data_hash(data)))
+    }
+
+    fn get_block_ext(&self, _block_hash: &Byte32) -> Option<BlockExt> {
+        None
+    }
+
+    fn get_header(&self, block_hash: &Byte32) -> Option<HeaderView> {
+        self.mock_tx
+            .mock_info
+            .header_deps
+            .iter()
+            .find(|header| &header.hash() == block_hash)
+            .cloned()
+    }
+
+    fn get_block_epoch(&self, _block_hash: &Byte32) -> Option<EpochExt> {
+        None
+    }
+}
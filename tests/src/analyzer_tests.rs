@@ -0,0 +1,270 @@
+use super::helper::blake160;
+use cheque_core::args::{ChequeArgs, FLAG_ARBITER};
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE as TYPE};
+use cheque_core::error::Error as ChequeError;
+use cheque_sdk::analyzer::{analyze_cheque_spends, UnlockBranch};
+use cheque_sdk::args::lock_hash_160;
+use cheque_sdk::{ChequeArgsExt, ChequeScript, Signer};
+use ckb_tool::ckb_crypto::secp::{Generator, Privkey};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+
+const CHEQUE_CAPACITY: u64 = 200_0000_0000;
+const OTHER_CAPACITY: u64 = 100_0000_0000;
+
+// The analyzer only reads the transaction, so the cheque lock script is never deployed
+fn build_cheque_script() -> ChequeScript {
+    ChequeScript {
+        code_hash:          [9u8; 32].pack(),
+        hash_type:          ScriptHashType::Data,
+        cell_dep:           CellDep::default(),
+        secp256k1_data_dep: CellDep::default(),
+    }
+}
+
+fn secp256k1_lock_script(private_key: &Privkey) -> Script {
+    let public_key = private_key.pubkey().expect("pubkey");
+    Script::new_builder()
+        .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
+        .args(Bytes::copy_from_slice(&blake160(&public_key.serialize())).pack())
+        .hash_type(Byte::new(TYPE))
+        .build()
+}
+
+fn other_lock_script(args: u8) -> Script {
+    Script::new_builder()
+        .code_hash([8u8; 32].pack())
+        .args(Bytes::from(vec![args]).pack())
+        .build()
+}
+
+fn build_cell(lock_script: Script, capacity: u64) -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(capacity.pack())
+        .lock(lock_script)
+        .build()
+}
+
+// Every input cell is a fresh out point with the witness of the same index
+fn build_tx(
+    input_cells: &[CellOutput],
+    witnesses: Vec<Bytes>,
+    outputs: Vec<CellOutput>,
+) -> TransactionView {
+    let mut builder = TransactionBuilder::default();
+    for index in 0..input_cells.len() {
+        builder = builder.input(CellInput::new(
+            OutPoint::new([index as u8; 32].pack(), 0),
+            0,
+        ));
+    }
+    for witness in witnesses {
+        builder = builder.witness(witness.pack());
+    }
+    for output in outputs {
+        builder = builder.output(output).output_data(Bytes::new().pack());
+    }
+    builder.build()
+}
+
+fn input_type_witness(input_type: Vec<u8>) -> Bytes {
+    WitnessArgs::new_builder()
+        .input_type(Some(Bytes::from(input_type)).pack())
+        .build()
+        .as_bytes()
+}
+
+#[test]
+fn test_analyze_claim_with_receiver_input() {
+    let cheque_script = build_cheque_script();
+    let receiver_lock_script = other_lock_script(1);
+    let sender_lock_script = other_lock_script(2);
+    let cheque_args = ChequeArgs::from_lock_scripts(&receiver_lock_script, &sender_lock_script);
+    let input_cells = vec![
+        build_cell(cheque_script.lock_script(&cheque_args), CHEQUE_CAPACITY),
+        build_cell(receiver_lock_script.clone(), OTHER_CAPACITY),
+    ];
+    let outputs = vec![
+        build_cell(receiver_lock_script, OTHER_CAPACITY),
+        build_cell(sender_lock_script.clone(), CHEQUE_CAPACITY),
+    ];
+    let tx = build_tx(&input_cells, vec![], outputs);
+
+    let spends = analyze_cheque_spends(&cheque_script, &tx, &input_cells).expect("analyze");
+    assert_eq!(spends.len(), 1);
+    assert_eq!(spends[0].input_indices, vec![0]);
+    assert_eq!(spends[0].action, None);
+    assert_eq!(spends[0].branch, Ok(UnlockBranch::ReceiverInput));
+    assert_eq!(spends[0].authorized_lock_hashes, vec![
+        cheque_args.receiver_lock_hash
+    ]);
+    let capacity_flow = spends[0].capacity_flow.expect("capacity flow");
+    assert_eq!(
+        capacity_flow.refund_lock_hash,
+        lock_hash_160(&sender_lock_script)
+    );
+    assert_eq!(capacity_flow.sender_inputs_capacity, 0);
+    assert_eq!(capacity_flow.cheque_inputs_capacity, CHEQUE_CAPACITY);
+    assert_eq!(capacity_flow.sender_outputs_capacity, CHEQUE_CAPACITY);
+    assert!(capacity_flow.is_same());
+}
+
+#[test]
+fn test_analyze_withdraw_with_sender_input() {
+    let cheque_script = build_cheque_script();
+    let receiver_lock_script = other_lock_script(1);
+    let sender_lock_script = other_lock_script(2);
+    let cheque_args = ChequeArgs::from_lock_scripts(&receiver_lock_script, &sender_lock_script);
+    let input_cells = vec![
+        build_cell(sender_lock_script.clone(), OTHER_CAPACITY),
+        build_cell(cheque_script.lock_script(&cheque_args), CHEQUE_CAPACITY),
+        build_cell(cheque_script.lock_script(&cheque_args), CHEQUE_CAPACITY),
+    ];
+    let outputs = vec![build_cell(
+        sender_lock_script,
+        OTHER_CAPACITY + CHEQUE_CAPACITY * 2,
+    )];
+    let tx = build_tx(&input_cells, vec![], outputs);
+
+    let spends = analyze_cheque_spends(&cheque_script, &tx, &input_cells).expect("analyze");
+    assert_eq!(spends.len(), 1);
+    assert_eq!(spends[0].input_indices, vec![1, 2]);
+    assert_eq!(spends[0].branch, Ok(UnlockBranch::SenderInput));
+    assert_eq!(spends[0].authorized_lock_hashes, vec![
+        cheque_args.sender_lock_hash
+    ]);
+    // the withdrawing does not check the sender capacity
+    assert!(spends[0].capacity_flow.is_none());
+}
+
+#[test]
+fn test_analyze_claim_and_withdraw_with_signatures() {
+    let cheque_script = build_cheque_script();
+    let receiver_key = Generator::random_privkey();
+    let sender_key = Generator::random_privkey();
+    let receiver_lock_script = secp256k1_lock_script(&receiver_key);
+    let sender_lock_script = secp256k1_lock_script(&sender_key);
+    let cheque_args = ChequeArgs::from_lock_scripts(&receiver_lock_script, &sender_lock_script);
+    let input_cells = vec![build_cell(
+        cheque_script.lock_script(&cheque_args),
+        CHEQUE_CAPACITY,
+    )];
+
+    // the receiver claims without returning the capacity to the sender
+    let tx = build_tx(&input_cells, vec![Bytes::new()], vec![build_cell(
+        receiver_lock_script,
+        CHEQUE_CAPACITY,
+    )]);
+    let claim_tx = Signer::new(cheque_script.clone())
+        .key(receiver_key)
+        .expect("key")
+        .sign(tx.clone(), &input_cells)
+        .expect("sign");
+    let spends = analyze_cheque_spends(&cheque_script, &claim_tx, &input_cells).expect("analyze");
+    assert_eq!(spends[0].branch, Ok(UnlockBranch::ReceiverSignature));
+    let capacity_flow = spends[0].capacity_flow.expect("capacity flow");
+    assert_eq!(capacity_flow.sender_outputs_capacity, 0);
+    assert!(!capacity_flow.is_same());
+
+    let withdraw_tx = Signer::new(cheque_script.clone())
+        .key(sender_key)
+        .expect("key")
+        .sign(tx, &input_cells)
+        .expect("sign");
+    let spends =
+        analyze_cheque_spends(&cheque_script, &withdraw_tx, &input_cells).expect("analyze");
+    assert_eq!(spends[0].branch, Ok(UnlockBranch::SenderSignature));
+    assert_eq!(spends[0].authorized_lock_hashes, vec![
+        cheque_args.sender_lock_hash
+    ]);
+    assert!(spends[0].capacity_flow.is_none());
+}
+
+#[test]
+fn test_analyze_not_matched_cheque_spends() {
+    let cheque_script = build_cheque_script();
+    let receiver_lock_script = other_lock_script(1);
+    let sender_key = Generator::random_privkey();
+    let sender_lock_script = secp256k1_lock_script(&sender_key);
+    let cheque_args = ChequeArgs::from_lock_scripts(&receiver_lock_script, &sender_lock_script);
+    let input_cells = vec![
+        build_cell(cheque_script.lock_script(&cheque_args), CHEQUE_CAPACITY),
+        build_cell(other_lock_script(3), OTHER_CAPACITY),
+    ];
+    let outputs = vec![build_cell(other_lock_script(3), CHEQUE_CAPACITY)];
+    let tx = build_tx(&input_cells, vec![], outputs);
+
+    let spends = analyze_cheque_spends(&cheque_script, &tx, &input_cells).expect("analyze");
+    assert_eq!(spends[0].branch, Err(ChequeError::NoMatchedInputs));
+    assert!(spends[0].authorized_lock_hashes.is_empty());
+    assert!(spends[0].capacity_flow.is_none());
+
+    // the sender of the signed cheque is neither the receiver nor the sender of the other cheque
+    let signed_tx = Signer::new(cheque_script.clone())
+        .key(sender_key)
+        .expect("key")
+        .sign(tx.clone(), &input_cells)
+        .expect("sign");
+    let other_cheque_args =
+        ChequeArgs::from_lock_scripts(&receiver_lock_script, &other_lock_script(3));
+    let other_input_cells = vec![
+        build_cell(
+            cheque_script.lock_script(&other_cheque_args),
+            CHEQUE_CAPACITY,
+        ),
+        build_cell(other_lock_script(3), OTHER_CAPACITY),
+    ];
+    let spends =
+        analyze_cheque_spends(&cheque_script, &signed_tx, &other_input_cells).expect("analyze");
+    assert_eq!(spends[0].branch, Err(ChequeError::NoMatchedSignature));
+
+    // the input cells must match the inputs
+    assert!(analyze_cheque_spends(&cheque_script, &tx, &other_input_cells[..1]).is_err());
+}
+
+#[test]
+fn test_analyze_escrow_refund_with_inputs() {
+    let cheque_script = build_cheque_script();
+    let receiver_lock_script = other_lock_script(1);
+    let sender_lock_script = other_lock_script(2);
+    let arbiter_lock_script = other_lock_script(3);
+    let mut cheque_args = ChequeArgs::from_lock_scripts(&receiver_lock_script, &sender_lock_script);
+    cheque_args.flags = FLAG_ARBITER;
+    cheque_args.arbiter_lock_hash = Some(lock_hash_160(&arbiter_lock_script));
+    let input_cells = vec![
+        build_cell(cheque_script.lock_script(&cheque_args), CHEQUE_CAPACITY),
+        build_cell(sender_lock_script.clone(), OTHER_CAPACITY),
+        build_cell(arbiter_lock_script, OTHER_CAPACITY),
+    ];
+    let outputs = vec![build_cell(sender_lock_script, CHEQUE_CAPACITY)];
+
+    let tx = build_tx(
+        &input_cells,
+        vec![input_type_witness(vec![3])],
+        outputs.clone(),
+    );
+    let spends = analyze_cheque_spends(&cheque_script, &tx, &input_cells).expect("analyze");
+    assert_eq!(spends[0].action, Some(3));
+    assert_eq!(spends[0].branch, Ok(UnlockBranch::EscrowRefund));
+    assert_eq!(spends[0].authorized_lock_hashes, vec![
+        cheque_args.sender_lock_hash,
+        cheque_args.arbiter_lock_hash.unwrap()
+    ]);
+    assert!(spends[0].capacity_flow.is_none());
+
+    // the release needs two parties besides the arbiter as well, and the sender alone withdraws
+    let tx = build_tx(
+        &input_cells[..2],
+        vec![input_type_witness(vec![2])],
+        outputs.clone(),
+    );
+    let spends = analyze_cheque_spends(&cheque_script, &tx, &input_cells[..2]).expect("analyze");
+    assert_eq!(spends[0].branch, Err(ChequeError::NoMatchedInputs));
+    let tx = build_tx(&input_cells[..2], vec![], outputs);
+    let spends = analyze_cheque_spends(&cheque_script, &tx, &input_cells[..2]).expect("analyze");
+    assert_eq!(spends[0].branch, Ok(UnlockBranch::SenderInput));
+}
@@ -0,0 +1,305 @@
+use super::{
+    helper::{write_native_setup, MAX_CYCLES},
+    *,
+};
+use cheque_core::args::{
+    ChequeArgs, Subscription, Vesting, FLAG_CAPACITY, FLAG_REFUND, FLAG_SUBSCRIPTION, FLAG_VESTING,
+    FLAG_WITHDRAW_SINCE,
+};
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE as TYPE};
+use cheque_core::error::Error as ArgsError;
+use cheque_core::since::SINCE_METRIC_EPOCH;
+use cheque_sdk::address::{encode_full_address, parse_address, Network};
+use cheque_sdk::{ChequeArgsExt, Error};
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, EpochNumberWithFraction, TransactionBuilder},
+    packed::*,
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+// The examples of the CKB address format RFC, which all refer to the same secp256k1 lock script
+const SHORT_ADDRESS: &str = "ckb1qyqt8xaupvm8837nv3gtc9x0ekkj64vud3jqfwyw5v";
+const FULL_ADDRESS: &str = "ckb1qzda0cr08m85hc8jlnfp3zer7xulejywt49kt2rr0vthywaa50xwsqdnnw7qkdnnclfkg59uzn8umtfd2kwxceqxwquc4";
+const FULL_TYPE_ADDRESS: &str = "ckb1qjda0cr08m85hc8jlnfp3zer7xulejywt49kt2rr0vthywaa50xw3vumhs9nvu786dj9p0q5elx66t24n3kxgj53qks";
+const ADDRESS_LOCK_ARGS: &str = "b39bbc0b3673c7d36450bc14cfcdad2d559c6c64";
+
+fn always_success_lock_scripts(context: &mut Context) -> (Script, Script, OutPoint) {
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let receiver_lock_script = context
+        .build_script(
+            &always_success_out_point,
+            Bytes::from(hex::decode("f43cc005be4edf45c829363d54799ac4f7aff5a5").unwrap()),
+        )
+        .expect("script");
+    let sender_lock_script = context
+        .build_script(
+            &always_success_out_point,
+            Bytes::from(hex::decode("36c329ed630d6ce750712a477543672adab57f4c").unwrap()),
+        )
+        .expect("script");
+    (
+        receiver_lock_script,
+        sender_lock_script,
+        always_success_out_point,
+    )
+}
+
+#[test]
+fn test_cheque_args_from_lock_scripts_layout() {
+    let mut context = Context::default();
+    let (receiver_lock_script, sender_lock_script, _) = always_success_lock_scripts(&mut context);
+
+    // the layout of the lock args built by hand in the other tests
+    let mut expected_args = receiver_lock_script
+        .calc_script_hash()
+        .as_bytes()
+        .slice(0..20)
+        .to_vec();
+    expected_args.extend_from_slice(
+        &sender_lock_script
+            .calc_script_hash()
+            .as_bytes()
+            .slice(0..20),
+    );
+    let mut cheque_args = ChequeArgs::from_lock_scripts(&receiver_lock_script, &sender_lock_script);
+    assert_eq!(cheque_args.to_bytes(), expected_args);
+
+    let refund_lock_hash = [7u8; 20];
+    cheque_args.flags = FLAG_WITHDRAW_SINCE | FLAG_REFUND;
+    cheque_args.withdraw_since = Some(0xA000_0000_0000_0010);
+    cheque_args.refund_lock_hash = Some(refund_lock_hash);
+    expected_args.extend_from_slice(&(FLAG_WITHDRAW_SINCE | FLAG_REFUND).to_le_bytes());
+    expected_args.extend_from_slice(&0xA000_0000_0000_0010u64.to_le_bytes());
+    expected_args.extend_from_slice(&refund_lock_hash);
+    assert_eq!(cheque_args.to_bytes(), expected_args);
+    assert_eq!(ChequeArgs::from_slice(&expected_args), Ok(cheque_args));
+}
+
+#[test]
+fn test_cheque_args_display_and_parse() {
+    let mut cheque_args = ChequeArgs::from_lock_hashes(&[1u8; 32], &[2u8; 32]);
+    let legacy_args = format!("0x{}{}", "01".repeat(20), "02".repeat(20));
+    assert_eq!(cheque_args.to_string(), legacy_args);
+    assert_eq!(legacy_args.parse::<ChequeArgs>(), Ok(cheque_args.clone()));
+
+    cheque_args.flags = FLAG_CAPACITY | FLAG_VESTING;
+    cheque_args.vesting = Some(Vesting {
+        start_epoch:    EpochNumberWithFraction::new(100, 0, 1).full_value(),
+        end_epoch:      EpochNumberWithFraction::new(200, 0, 1).full_value(),
+        total_capacity: 1000_0000_0000,
+    });
+    let args = cheque_args.to_string();
+    assert_eq!(args.parse::<ChequeArgs>(), Ok(cheque_args.clone()));
+    // the hex without the 0x prefix and in upper case is parsed as well
+    assert_eq!(
+        args[2..].to_uppercase().parse::<ChequeArgs>(),
+        Ok(cheque_args)
+    );
+
+    assert_eq!(
+        "0x1234".parse::<ChequeArgs>(),
+        Err(ArgsError::InvalidArgument)
+    );
+    assert_eq!(
+        format!("0x{}", "zz".repeat(40)).parse::<ChequeArgs>(),
+        Err(ArgsError::InvalidArgument)
+    );
+}
+
+#[test]
+fn test_error_cheque_args_subscription_period_shorter_than_notice() {
+    let mut cheque_args = ChequeArgs::from_lock_hashes(&[1u8; 32], &[2u8; 32]);
+    cheque_args.flags = FLAG_CAPACITY | FLAG_SUBSCRIPTION;
+    let subscription = Subscription {
+        start_epoch:   EpochNumberWithFraction::new(100, 0, 1).full_value(),
+        period_epochs: 6,
+        amount:        100_0000_0000,
+    };
+    cheque_args.subscription = Some(subscription);
+    // the period is as long as the default lock-up period of 6 relative epochs
+    assert_eq!(
+        ChequeArgs::from_slice(&cheque_args.to_bytes()),
+        Ok(cheque_args.clone())
+    );
+
+    // every pull restarts the lock-up period, which would never end within a shorter period
+    cheque_args.subscription = Some(Subscription {
+        period_epochs: 5,
+        ..subscription
+    });
+    assert_eq!(
+        ChequeArgs::from_slice(&cheque_args.to_bytes()),
+        Err(ArgsError::InvalidArgument)
+    );
+    let mut relative_args = cheque_args.clone();
+    relative_args.flags |= FLAG_WITHDRAW_SINCE;
+    relative_args.withdraw_since = Some(0xA000_0000_0000_0010);
+    assert_eq!(
+        ChequeArgs::from_slice(&relative_args.to_bytes()),
+        Err(ArgsError::InvalidArgument)
+    );
+    // the relative withdraw since in blocks cannot be compared with the period in epochs
+    relative_args.withdraw_since = Some(0x8000_0000_0000_0001);
+    assert_eq!(
+        ChequeArgs::from_slice(&relative_args.to_bytes()),
+        Err(ArgsError::InvalidArgument)
+    );
+
+    // an absolute withdraw since is not restarted by the pulls
+    cheque_args.flags |= FLAG_WITHDRAW_SINCE;
+    cheque_args.withdraw_since =
+        Some(SINCE_METRIC_EPOCH | EpochNumberWithFraction::new(200, 0, 1).full_value());
+    assert_eq!(
+        ChequeArgs::from_slice(&cheque_args.to_bytes()),
+        Ok(cheque_args)
+    );
+}
+
+#[test]
+fn test_cheque_args_from_addresses() {
+    let lock_args = Bytes::from(hex::decode(ADDRESS_LOCK_ARGS).unwrap());
+    let lock_script = Script::new_builder()
+        .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
+        .args(lock_args.pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+
+    for address in [SHORT_ADDRESS, FULL_ADDRESS, FULL_TYPE_ADDRESS].iter() {
+        let (network, address_lock_script) = parse_address(address).expect("address");
+        assert_eq!(network, Network::Mainnet);
+        assert_eq!(address_lock_script.as_slice(), lock_script.as_slice());
+    }
+    assert_eq!(
+        encode_full_address(Network::Mainnet, &lock_script),
+        FULL_ADDRESS
+    );
+
+    let cheque_args = ChequeArgs::from_addresses(SHORT_ADDRESS, FULL_ADDRESS).expect("addresses");
+    assert_eq!(
+        cheque_args,
+        ChequeArgs::from_lock_scripts(&lock_script, &lock_script)
+    );
+    assert_eq!(
+        &cheque_args.receiver_lock_hash[..],
+        &lock_script.calc_script_hash().as_slice()[0..20]
+    );
+}
+
+#[test]
+fn test_error_cheque_args_from_invalid_addresses() {
+    let (_, lock_script) = parse_address(FULL_ADDRESS).expect("address");
+    let testnet_address = encode_full_address(Network::Testnet, &lock_script);
+    assert_eq!(
+        parse_address(&testnet_address).map(|(network, _)| network),
+        Ok(Network::Testnet)
+    );
+
+    // the receiver and the sender must be on the same network
+    assert_eq!(
+        ChequeArgs::from_addresses(FULL_ADDRESS, &testnet_address).err(),
+        Some(Error::InvalidAddress)
+    );
+    // the checksum of the address is broken
+    let broken_address = SHORT_ADDRESS.replace("fwyw5v", "fwyw5q");
+    assert_eq!(
+        ChequeArgs::from_addresses(&broken_address, FULL_ADDRESS).err(),
+        Some(Error::InvalidAddress)
+    );
+}
+
+#[test]
+fn test_claim_with_cheque_args_from_lock_scripts() {
+    // deploy cheque script
+    let mut context = Context::default();
+    let cheque_bin: Bytes = Loader::default().load_binary("ckb-cheque-script");
+    let cheque_out_point = context.deploy_cell(cheque_bin);
+    let (receiver_lock_script, sender_lock_script, always_success_out_point) =
+        always_success_lock_scripts(&mut context);
+
+    // the cheque script parses the lock args in entry::main
+    let cheque_args = ChequeArgs::from_lock_scripts(&receiver_lock_script, &sender_lock_script);
+    let cheque_script = context
+        .build_script(&cheque_out_point, Bytes::from(cheque_args.to_bytes()))
+        .expect("script");
+
+    // prepare inputs
+    let cheque_cell_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(200_0000_0000).pack())
+            .lock(cheque_script)
+            .build(),
+        Bytes::new(),
+    );
+    let receiver_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(200_0000_0000).pack())
+            .lock(receiver_lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(cheque_cell_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(receiver_out_point)
+            .build(),
+    ];
+
+    // prepare outputs
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(200_0000_0000).pack())
+            .lock(receiver_lock_script)
+            .build(),
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(200_0000_0000).pack())
+            .lock(sender_lock_script)
+            .build(),
+    ];
+    let outputs_data = vec![Bytes::new(), Bytes::new()];
+
+    let signature = Bytes::from(
+        hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap());
+    let witnesses = vec![Bytes::new(), signature];
+
+    let cheque_script_dep = CellDep::new_builder().out_point(cheque_out_point).build();
+    let always_success_lock_script_dep = CellDep::new_builder()
+        .out_point(always_success_out_point)
+        .build();
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(cheque_script_dep)
+        .cell_dep(always_success_lock_script_dep)
+        .witnesses(witnesses.pack())
+        .build();
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_cheque_args_from_lock_scripts",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
@@ -0,0 +1,302 @@
+use super::helper::{
+    build_cell, placeholder_signature, write_native_setup, write_native_setup_with_headers, Party,
+    TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::FLAG_CLAIM_SINCE;
+use cheque_core::error::Error;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{HeaderBuilder, HeaderView, TransactionView},
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const CLAIM_CHEQUE_INPUT_SINCE_TOO_EARLY: i8 = Error::ClaimChequeInputSinceTooEarly as i8;
+
+// Absolute epoch since: the receiver can claim from the epoch 100 + 1/2
+const CLAIM_SINCE: u64 = 0x2000_0000_0000_0000 | (2 << 40) | (1 << 24) | 100;
+
+// Relative block number since: the receiver can claim 100 blocks after the cheque cell is created
+const RELATIVE_CLAIM_SINCE: u64 = 0x8000_0000_0000_0064;
+const CHEQUE_CELL_BLOCK_NUMBER: u64 = 1000;
+
+fn build_test_context_with_claim_since(since: u64) -> (Context, TransactionView) {
+    let (context, tx, _) = build_test_context_with_claim_since_headers(CLAIM_SINCE, since, None);
+    (context, tx)
+}
+
+// The headers are the header of the block of the cheque cell and the latest header, which prove
+// the elapsed blocks of a relative claim since
+fn build_test_context_with_claim_since_headers(
+    claim_since: u64,
+    since: u64,
+    latest_block_number: Option<u64>,
+) -> (Context, TransactionView, Vec<HeaderView>) {
+    let mut builder = TestTxBuilder::default();
+    let cheque_args = builder.cheque_args(
+        Party::Receiver,
+        Party::Sender,
+        Some(FLAG_CLAIM_SINCE),
+        &claim_since.to_le_bytes(),
+    );
+    let cheque_script = builder.cheque_script(&cheque_args);
+    let receiver_lock_script = builder.lock_script(Party::Receiver);
+    let sender_lock_script = builder.lock_script(Party::Sender);
+
+    // prepare the header of the block in which the cheque cell was created and the latest header
+    let mut headers = Vec::new();
+    if let Some(latest_block_number) = latest_block_number {
+        for number in [CHEQUE_CELL_BLOCK_NUMBER, latest_block_number].iter() {
+            let header = HeaderBuilder::default().number(number.pack()).build();
+            builder.header_dep(&header);
+            headers.push(header);
+        }
+    }
+
+    // prepare inputs
+    let cheque_cell_out_point = builder.input(
+        build_cell(&cheque_script, 162_0000_0000, None),
+        Bytes::new(),
+        since,
+        Bytes::new(),
+    );
+    if let Some(header) = headers.first() {
+        builder
+            .context
+            .link_cell_with_block(cheque_cell_out_point, header.hash(), 0);
+    }
+    builder.input(
+        build_cell(&receiver_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+        0,
+        placeholder_signature(),
+    );
+
+    // prepare outputs
+    builder.output(
+        build_cell(&receiver_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+    );
+    builder.output(
+        build_cell(&sender_lock_script, 162_0000_0000, None),
+        Bytes::new(),
+    );
+
+    let (context, tx) = builder.build();
+    (context, tx, headers)
+}
+
+#[test]
+fn test_claim_with_claim_since() {
+    let (mut context, tx) = build_test_context_with_claim_since(CLAIM_SINCE);
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_claim_since",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_claim_with_later_claim_since() {
+    let since = 0x2000_0000_0000_0000 | (4 << 40) | (3 << 24) | 100;
+    let (mut context, tx) = build_test_context_with_claim_since(since);
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_later_claim_since",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_earlier_claim_since() {
+    let since = 0x2000_0000_0000_0000 | (4 << 40) | (1 << 24) | 100;
+    let (mut context, tx) = build_test_context_with_claim_since(since);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CLAIM_CHEQUE_INPUT_SINCE_TOO_EARLY)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_earlier_claim_since",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_zero_since() {
+    let (mut context, tx) = build_test_context_with_claim_since(0);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CLAIM_CHEQUE_INPUT_SINCE_TOO_EARLY)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_zero_since",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_block_number_since() {
+    let (mut context, tx) = build_test_context_with_claim_since(0x0000_0000_0000_FFFF);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CLAIM_CHEQUE_INPUT_SINCE_TOO_EARLY)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_block_number_since",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_claim_with_relative_claim_since_proved_by_headers() {
+    let (mut context, tx, headers) = build_test_context_with_claim_since_headers(
+        RELATIVE_CLAIM_SINCE,
+        0,
+        Some(CHEQUE_CELL_BLOCK_NUMBER + 100),
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_claim_with_relative_claim_since_proved_by_headers",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &headers,
+        &[Some(headers[0].hash()), None],
+    );
+}
+
+#[test]
+fn test_error_claim_with_relative_claim_since_not_elapsed() {
+    let (mut context, tx, headers) = build_test_context_with_claim_since_headers(
+        RELATIVE_CLAIM_SINCE,
+        0,
+        Some(CHEQUE_CELL_BLOCK_NUMBER + 99),
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CLAIM_CHEQUE_INPUT_SINCE_TOO_EARLY)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_claim_with_relative_claim_since_not_elapsed",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &headers,
+        &[Some(headers[0].hash()), None],
+    );
+}
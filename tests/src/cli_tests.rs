@@ -0,0 +1,123 @@
+use cheque_cli::config::{parse_capacity, Deployment};
+use cheque_cli::inspect::args_json;
+use cheque_cli::{Error, TxFile};
+use cheque_core::args::{ChequeArgs, FLAG_WITHDRAW_SINCE};
+use cheque_core::error::Error as ChequeError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionBuilder},
+    packed::*,
+    prelude::*,
+};
+use std::convert::TryFrom;
+use std::fs;
+
+const DEPLOYMENT_JSON: &str = r#"{
+    "code_hash": "0x0909090909090909090909090909090909090909090909090909090909090909",
+    "hash_type": "type",
+    "cell_dep": {
+        "out_point": {
+            "tx_hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "index": "0x0"
+        },
+        "dep_type": "code"
+    },
+    "secp256k1_data_dep": {
+        "out_point": {
+            "tx_hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "index": "0x1"
+        },
+        "dep_type": "code"
+    }
+}"#;
+
+#[test]
+fn test_parse_capacity_in_ckb() {
+    assert_eq!(parse_capacity("100").unwrap(), 100_0000_0000);
+    assert_eq!(parse_capacity("61.5").unwrap(), 61_5000_0000);
+    assert_eq!(parse_capacity("0.00000001").unwrap(), 1);
+    assert_eq!(parse_capacity("0.001").unwrap(), 10_0000);
+
+    for capacity in ["", ".5", "1.000000001", "-1", "1e3", "184467440738"].iter() {
+        assert!(matches!(
+            parse_capacity(capacity),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
+
+#[test]
+fn test_load_deployment() {
+    let path = std::env::temp_dir().join("cheque-cli-deployment.json");
+    fs::write(&path, DEPLOYMENT_JSON).expect("write");
+    let deployment = Deployment::load(&path).expect("deployment");
+    fs::remove_file(&path).expect("remove");
+
+    let cheque_script = deployment.cheque_script();
+    assert_eq!(cheque_script.code_hash.as_slice(), &[9u8; 32]);
+    assert_eq!(cheque_script.hash_type, ScriptHashType::Type);
+    assert_eq!(
+        cheque_script.secp256k1_data_dep.out_point(),
+        OutPoint::new([1u8; 32].pack(), 1)
+    );
+    assert!(deployment.lock_deps().is_empty());
+
+    let cheque_args = ChequeArgs::from_lock_hashes(&[1u8; 32], &[2u8; 32]);
+    assert!(deployment.is_cheque_lock(&cheque_script.lock_script(&cheque_args)));
+    let data_lock_script = cheque_script
+        .lock_script(&cheque_args)
+        .as_builder()
+        .hash_type(ScriptHashType::Data.into())
+        .build();
+    assert!(!deployment.is_cheque_lock(&data_lock_script));
+}
+
+#[test]
+fn test_ckb_cli_tx_file() {
+    let tx = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::new([1u8; 32].pack(), 0), 0))
+        .output(CellOutput::new_builder().capacity(100u64.pack()).build())
+        .output_data(Bytes::new().pack())
+        .witness(Bytes::from(vec![7u8; 8]).pack())
+        .build();
+
+    // the ckb-cli reads the multisig configs and the signatures besides the transaction
+    let json = TxFile::new(&tx).to_json().expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("value");
+    assert!(value["transaction"]["inputs"].is_array());
+    assert_eq!(value["multisig_configs"], serde_json::json!({}));
+    assert_eq!(value["signatures"], serde_json::json!({}));
+
+    let path = std::env::temp_dir().join(format!("cheque-cli-tx-{}.json", tx.hash()));
+    fs::write(&path, json).expect("write");
+    let tx_file = TxFile::load(&path).expect("tx file");
+    fs::remove_file(&path).expect("remove");
+    assert_eq!(tx_file.tx().hash(), tx.hash());
+}
+
+#[test]
+fn test_inspect_cheque_args() {
+    let mut cheque_args = ChequeArgs::from_lock_hashes(&[1u8; 32], &[2u8; 32]);
+    cheque_args.flags = FLAG_WITHDRAW_SINCE;
+    cheque_args.withdraw_since = Some(0xA000_0000_0000_0001);
+
+    let json = args_json(&cheque_args);
+    assert_eq!(
+        json["receiver_lock_hash"],
+        format!("0x{}", hex::encode([1u8; 20]))
+    );
+    assert_eq!(json["flags"], "0x0004");
+    assert_eq!(json["withdraw_since"], "0xa000000000000001");
+    assert!(json["arbiter_lock_hash"].is_null());
+}
+
+#[test]
+fn test_cheque_error_from_exit_code() {
+    for code in 1..=19i8 {
+        let err = ChequeError::try_from(code).expect("error");
+        assert_eq!(err as i8, code);
+    }
+    assert_eq!(ChequeError::try_from(0), Err(0));
+    assert_eq!(ChequeError::try_from(20), Err(20));
+    assert_eq!(ChequeError::try_from(-1), Err(-1));
+}
@@ -0,0 +1,195 @@
+use super::{helper::MAX_CYCLES, *};
+use cheque_core::args::ChequeArgs;
+use cheque_core::error::Error as ChequeError;
+use cheque_sdk::cycles::CycleEstimator;
+use cheque_sdk::{ChequeScript, ClaimBuilder, ClaimMode, Error, LiveCell};
+use ckb_system_scripts::BUNDLED_CELL;
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, ScriptHashType, TransactionView},
+    packed::*,
+    prelude::*,
+};
+
+const FEE: u64 = 1_0000_0000;
+
+fn create_live_cell(
+    context: &mut Context,
+    lock_script: Script,
+    type_script: Option<Script>,
+    capacity: u64,
+) -> LiveCell {
+    let output = CellOutput::new_builder()
+        .capacity(Capacity::shannons(capacity).pack())
+        .lock(lock_script)
+        .type_(type_script.pack())
+        .build();
+    let data = match output.type_().to_opt() {
+        Some(_) => Bytes::from(1000u128.to_le_bytes().to_vec()),
+        None => Bytes::new(),
+    };
+    let out_point = context.create_cell(output.clone(), data.clone());
+    LiveCell::new(out_point, output, data)
+}
+
+// builds a claim of a udt cheque by a receiver input, whose lock and type scripts are
+// always_success
+fn build_claim_tx() -> (Context, TransactionView) {
+    let mut context = Context::default();
+    let cheque_bin: Bytes = Loader::default().load_binary("ckb-cheque-script");
+    let cheque_out_point = context.deploy_cell(cheque_bin);
+    let cheque_code_hash = context
+        .build_script(&cheque_out_point, Bytes::new())
+        .expect("script")
+        .code_hash();
+    let secp256k1_data_bin = BUNDLED_CELL.get("specs/cells/secp256k1_data").unwrap();
+    let secp256k1_data_out_point = context.deploy_cell(secp256k1_data_bin.to_vec().into());
+    let cheque_script = ChequeScript {
+        code_hash:          cheque_code_hash,
+        hash_type:          ScriptHashType::Data,
+        cell_dep:           CellDep::new_builder().out_point(cheque_out_point).build(),
+        secp256k1_data_dep: CellDep::new_builder()
+            .out_point(secp256k1_data_out_point)
+            .build(),
+    };
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let receiver_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1u8]))
+        .expect("script");
+    let sender_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![2u8]))
+        .expect("script");
+    let udt_type_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![3u8]))
+        .expect("script");
+    let always_success_lock_dep = CellDep::new_builder()
+        .out_point(always_success_out_point)
+        .build();
+
+    let cheque_args = ChequeArgs::from_lock_scripts(&receiver_lock_script, &sender_lock_script);
+    let cheque_cell = create_live_cell(
+        &mut context,
+        cheque_script.lock_script(&cheque_args),
+        Some(udt_type_script),
+        200_0000_0000,
+    );
+    let receiver_cell = create_live_cell(
+        &mut context,
+        receiver_lock_script.clone(),
+        None,
+        200_0000_0000,
+    );
+
+    let tx = ClaimBuilder::new(
+        cheque_script,
+        cheque_args,
+        receiver_lock_script,
+        sender_lock_script,
+        ClaimMode::LockInput(receiver_cell),
+    )
+    .cheque_cell(cheque_cell)
+    .cell_dep(always_success_lock_dep)
+    .fee(FEE)
+    .build()
+    .expect("build");
+    (context, tx)
+}
+
+fn build_estimator(context: &Context, tx: &TransactionView) -> CycleEstimator {
+    let out_points = tx
+        .inputs()
+        .into_iter()
+        .map(|input| input.previous_output())
+        .chain(
+            tx.cell_deps()
+                .into_iter()
+                .map(|cell_dep| cell_dep.out_point()),
+        );
+    out_points.fold(CycleEstimator::new(), |estimator, out_point| {
+        let (output, data) = context.get_cell(&out_point).expect("get cell");
+        estimator.cell(LiveCell::new(out_point, output, data))
+    })
+}
+
+#[test]
+fn test_estimate_cycles_of_claim() {
+    let (context, tx) = build_claim_tx();
+    let groups = build_estimator(&context, &tx)
+        .estimate(&tx)
+        .expect("estimate");
+
+    // the cheque and the receiver lock groups, and the udt type group
+    assert_eq!(groups.len(), 3);
+    assert_eq!(groups[0].index, 0);
+    assert_eq!(groups[1].index, 1);
+    assert!(groups.iter().all(|group| group.cheque_error().is_none()));
+
+    let cycles: u64 = groups
+        .iter()
+        .map(|group| *group.result.as_ref().expect("cycles"))
+        .sum();
+    let expected_cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    assert_eq!(cycles, expected_cycles);
+}
+
+#[test]
+fn test_estimate_cycles_with_cheque_error() {
+    let (context, tx) = build_claim_tx();
+    let outputs = tx
+        .outputs()
+        .into_iter()
+        .enumerate()
+        .map(|(index, output)| {
+            if index == 1 {
+                let capacity: u64 = output.capacity().unpack();
+                output.as_builder().capacity((capacity - 1).pack()).build()
+            } else {
+                output
+            }
+        })
+        .collect::<Vec<_>>();
+    let tx = tx.as_advanced_builder().set_outputs(outputs).build();
+
+    let groups = build_estimator(&context, &tx)
+        .estimate(&tx)
+        .expect("estimate");
+    assert_eq!(
+        groups[0].cheque_error(),
+        Some(ChequeError::SenderCapacityNotSame)
+    );
+    assert!(groups[1].result.is_ok());
+}
+
+#[test]
+fn test_estimate_cycles_without_cell_dep() {
+    let (context, tx) = build_claim_tx();
+    // only the inputs are given, so the cell dep of the cheque lock script is missing
+    let estimator = tx
+        .inputs()
+        .into_iter()
+        .map(|input| input.previous_output())
+        .fold(CycleEstimator::new(), |estimator, out_point| {
+            let (output, data) = context.get_cell(&out_point).expect("get cell");
+            estimator.cell(LiveCell::new(out_point, output, data))
+        });
+    assert!(matches!(
+        estimator.estimate(&tx),
+        Err(Error::CellNotResolved)
+    ));
+}
+
+#[test]
+fn test_estimate_cycles_exceeding_max_cycles() {
+    let (context, tx) = build_claim_tx();
+    let groups = build_estimator(&context, &tx)
+        .max_cycles(1000)
+        .estimate(&tx)
+        .expect("estimate");
+
+    assert!(groups[0].result.is_err());
+    assert_eq!(groups[0].cheque_error(), None);
+}
@@ -0,0 +1,381 @@
+use super::helper::{
+    build_cell, write_native_setup_with_headers, Party, TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::FLAG_DAO;
+use cheque_core::code_hash::{DAO_TYPE_CODE_HASH, HASH_TYPE_TYPE as TYPE};
+use cheque_core::error::Error;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use ckb_system_scripts::BUNDLED_CELL;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, HeaderBuilder, HeaderView, TransactionView},
+    packed::{Byte, CellOutput, Script},
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const CHEQUE_ASSET_NOT_MATCHED: i8 = Error::ChequeAssetNotMatched as i8;
+
+// The Nervos DAO type script is the type id of the genesis DAO cell
+const TYPE_ID_CODE_HASH: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 84, 89, 80, 69, 95,
+    73, 68,
+];
+const DAO_TYPE_ID_ARGS: &str = "b2a8500929d6a1294bf9bf1bf565f549fa4a5f1316a3306ad3d4783e64bcf626";
+
+const DAO_DEPOSIT_DATA: u64 = 0;
+const DAO_DEPOSIT_BLOCK_NUMBER: u64 = 1000;
+
+// (party, capacity, Option<DAO data>)
+type Output = (Party, u64, Option<u64>);
+
+fn build_test_context_with_dao(
+    party: Party,
+    outputs: Vec<Output>,
+    since: u64,
+) -> (Context, TransactionView, HeaderView) {
+    let mut builder = TestTxBuilder::default();
+
+    // deploy the bundled DAO script as the type id cell of the genesis DAO cell, whose type hash is
+    // the code hash of the DAO type script, so the DAO type script checks the outputs as well
+    let dao_type_id_script = Script::new_builder()
+        .code_hash(TYPE_ID_CODE_HASH.pack())
+        .args(Bytes::from(hex::decode(DAO_TYPE_ID_ARGS).unwrap()).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+    assert_eq!(
+        dao_type_id_script.calc_script_hash().as_slice(),
+        DAO_TYPE_CODE_HASH
+    );
+    let dao_out_point = builder.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(100_0000_0000).pack())
+            .type_(Some(dao_type_id_script.clone()).pack())
+            .build(),
+        BUNDLED_CELL.get("specs/cells/dao").unwrap().to_vec().into(),
+    );
+    let dao_type_script = Script::new_builder()
+        .code_hash(dao_type_id_script.calc_script_hash())
+        .hash_type(Byte::new(TYPE))
+        .build();
+
+    // the DAO type script is referenced by type hash, so its cell dep is given explicitly
+    builder.cell_dep(dao_out_point);
+
+    let cheque_args = builder.cheque_args(Party::Receiver, Party::Sender, Some(FLAG_DAO), &[]);
+    let cheque_script = builder.cheque_script(&cheque_args);
+    let build_dao_cell = |lock_script: &Script, capacity: u64, dao_data: Option<u64>| match dao_data
+    {
+        Some(data) => (
+            build_cell(lock_script, capacity, Some(dao_type_script.clone())),
+            Bytes::from(data.to_le_bytes().to_vec()),
+        ),
+        None => (build_cell(lock_script, capacity, None), Bytes::new()),
+    };
+
+    // prepare the header of the block in which the cheque cell was deposited, which the DAO type
+    // script loads to start withdrawing
+    let header = HeaderBuilder::default()
+        .number(DAO_DEPOSIT_BLOCK_NUMBER.pack())
+        .build();
+    builder.header_dep(&header);
+
+    // prepare inputs
+    let (cheque_cell, cheque_cell_data) =
+        build_dao_cell(&cheque_script, 200_0000_0000, Some(DAO_DEPOSIT_DATA));
+    let cheque_cell_out_point = builder.input(cheque_cell, cheque_cell_data, since, Bytes::new());
+    builder
+        .context
+        .link_cell_with_block(cheque_cell_out_point, header.hash(), 0);
+    let party_lock_script = builder.lock_script(party);
+    builder.input(
+        build_cell(&party_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+        0,
+        Bytes::new(),
+    );
+
+    // prepare outputs
+    for (output_party, capacity, dao_data) in outputs.into_iter() {
+        let lock_script = builder.lock_script(output_party);
+        let (output, data) = build_dao_cell(&lock_script, capacity, dao_data);
+        builder.output(output, data);
+    }
+
+    let (context, tx) = builder.build();
+    (context, tx, header)
+}
+
+#[test]
+fn test_claim_dao_withdrawing_with_receiver_input() {
+    let (context, tx, header) = build_test_context_with_dao(
+        Party::Receiver,
+        vec![
+            (
+                Party::Receiver,
+                200_0000_0000,
+                Some(DAO_DEPOSIT_BLOCK_NUMBER),
+            ),
+            (Party::Receiver, 200_0000_0000, None),
+        ],
+        0,
+    );
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_claim_dao_withdrawing_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_claim_dao_deposit_kept_as_deposit() {
+    // the DAO type script only spends a deposit cell into a withdrawing cell at the same index
+    let (context, tx, header) = build_test_context_with_dao(
+        Party::Receiver,
+        vec![
+            (Party::Receiver, 200_0000_0000, Some(DAO_DEPOSIT_DATA)),
+            (Party::Receiver, 200_0000_0000, None),
+        ],
+        0,
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_claim_dao_deposit_kept_as_deposit",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_claim_dao_deposit_without_dao_output() {
+    let (context, tx, header) = build_test_context_with_dao(
+        Party::Receiver,
+        vec![(Party::Receiver, 400_0000_0000, None)],
+        0,
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_claim_dao_deposit_without_dao_output",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_claim_dao_withdrawing_with_capacity_changed() {
+    let (context, tx, header) = build_test_context_with_dao(
+        Party::Receiver,
+        vec![
+            (
+                Party::Receiver,
+                199_0000_0000,
+                Some(DAO_DEPOSIT_BLOCK_NUMBER),
+            ),
+            (Party::Receiver, 201_0000_0000, None),
+        ],
+        0,
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_claim_dao_withdrawing_with_capacity_changed",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_claim_dao_withdrawing_with_other_block_number() {
+    let (context, tx, header) = build_test_context_with_dao(
+        Party::Receiver,
+        vec![
+            (
+                Party::Receiver,
+                200_0000_0000,
+                Some(DAO_DEPOSIT_BLOCK_NUMBER + 1),
+            ),
+            (Party::Receiver, 200_0000_0000, None),
+        ],
+        0,
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_claim_dao_withdrawing_with_other_block_number",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_claim_dao_withdrawing_at_other_index() {
+    // the withdrawing output must be at the same index as the DAO cheque cell
+    let (context, tx, header) = build_test_context_with_dao(
+        Party::Receiver,
+        vec![
+            (Party::Receiver, 200_0000_0000, None),
+            (
+                Party::Receiver,
+                200_0000_0000,
+                Some(DAO_DEPOSIT_BLOCK_NUMBER),
+            ),
+        ],
+        0,
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_claim_dao_withdrawing_at_other_index",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_withdraw_dao_withdrawing_with_sender_input() {
+    let (context, tx, header) = build_test_context_with_dao(
+        Party::Sender,
+        vec![
+            (Party::Sender, 200_0000_0000, Some(DAO_DEPOSIT_BLOCK_NUMBER)),
+            (Party::Sender, 200_0000_0000, None),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_withdraw_dao_withdrawing_with_sender_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
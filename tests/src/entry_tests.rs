@@ -1,7 +1,9 @@
 use super::{
-    helper::{sign_tx, write_native_setup, CODE_HASH_SECP256K1_BLAKE160, TYPE, MAX_CYCLES},
+    helper::{sign_tx, write_native_setup, MAX_CYCLES},
     *,
 };
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE as TYPE};
+use cheque_core::error::Error;
 use ckb_system_scripts::BUNDLED_CELL;
 use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
 use ckb_tool::ckb_crypto::secp::Generator;
@@ -16,9 +18,9 @@ use ckb_tool::ckb_types::{
 use ckb_x64_simulator::RunningSetup;
 use std::collections::HashMap;
 
-const INVALID_ARGUMENT: i8 = 5;
-const NO_MATCHED_INPUTS: i8 = 6;
-const NO_MATCHED_SIGNATURE: i8 = 13;
+const INVALID_ARGUMENT: i8 = Error::InvalidArgument as i8;
+const NO_MATCHED_INPUTS: i8 = Error::NoMatchedInputs as i8;
+const NO_MATCHED_SIGNATURE: i8 = Error::NoMatchedSignature as i8;
 
 fn build_test_context(
     sender_lock_args: Bytes,
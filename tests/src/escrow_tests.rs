@@ -0,0 +1,435 @@
+use super::helper::{
+    build_cell, placeholder_signature, write_native_setup, Party, TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::{FLAG_ARBITER, FLAG_CAPACITY};
+use cheque_core::error::Error;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{bytes::Bytes, core::TransactionView, packed::WitnessArgs, prelude::*};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const NO_MATCHED_INPUTS: i8 = Error::NoMatchedInputs as i8;
+const CHEQUE_ASSET_NOT_MATCHED: i8 = Error::ChequeAssetNotMatched as i8;
+
+const ESCROW_ACTION_RELEASE: u8 = 2;
+const ESCROW_ACTION_REFUND: u8 = 3;
+
+fn build_test_context_with_arbiter(
+    parties: Vec<Party>,
+    action: Option<u8>,
+    since: u64,
+    outputs: Vec<(Party, u64)>,
+) -> (Context, TransactionView) {
+    build_test_context_with_escrow_cheques(vec![Party::Sender], parties, action, since, outputs)
+}
+
+// Every sender holds an escrow cheque cell of 162 CKB to the same receiver in its own group
+fn build_test_context_with_escrow_cheques(
+    senders: Vec<Party>,
+    parties: Vec<Party>,
+    action: Option<u8>,
+    since: u64,
+    outputs: Vec<(Party, u64)>,
+) -> (Context, TransactionView) {
+    let mut builder = TestTxBuilder::default();
+    let arbiter_lock_hash = builder.lock_hash(Party::Arbiter);
+
+    // prepare inputs
+    let cheque_witness = match action {
+        Some(action) => WitnessArgs::new_builder()
+            .input_type(Some(Bytes::from(vec![action])).pack())
+            .build()
+            .as_bytes(),
+        None => Bytes::new(),
+    };
+    for sender in senders.into_iter() {
+        let cheque_args = builder.cheque_args(
+            Party::Receiver,
+            sender,
+            Some(FLAG_CAPACITY | FLAG_ARBITER),
+            &arbiter_lock_hash.as_slice()[0..20],
+        );
+        let cheque_script = builder.cheque_script(&cheque_args);
+        builder.input(
+            build_cell(&cheque_script, 162_0000_0000, None),
+            Bytes::new(),
+            since,
+            cheque_witness.clone(),
+        );
+    }
+    for party in parties.into_iter() {
+        let party_lock_script = builder.lock_script(party);
+        builder.input(
+            build_cell(&party_lock_script, 200_0000_0000, None),
+            Bytes::new(),
+            0,
+            placeholder_signature(),
+        );
+    }
+
+    // prepare outputs
+    for (party, capacity) in outputs.into_iter() {
+        let lock_script = builder.lock_script(party);
+        builder.output(build_cell(&lock_script, capacity, None), Bytes::new());
+    }
+
+    builder.build()
+}
+
+#[test]
+fn test_release_with_receiver_and_arbiter_inputs() {
+    let (mut context, tx) = build_test_context_with_arbiter(
+        vec![Party::Receiver, Party::Arbiter],
+        Some(ESCROW_ACTION_RELEASE),
+        0,
+        vec![(Party::Receiver, 362_0000_0000), (Party::Arbiter, 200_0000_0000)],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_release_with_receiver_and_arbiter_inputs",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_refund_with_sender_and_arbiter_inputs() {
+    let (mut context, tx) = build_test_context_with_arbiter(
+        vec![Party::Sender, Party::Arbiter],
+        Some(ESCROW_ACTION_REFUND),
+        0,
+        vec![(Party::Sender, 362_0000_0000), (Party::Arbiter, 200_0000_0000)],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_refund_with_sender_and_arbiter_inputs",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_release_with_arbiter_input() {
+    let (mut context, tx) = build_test_context_with_arbiter(
+        vec![Party::Arbiter],
+        Some(ESCROW_ACTION_RELEASE),
+        0,
+        vec![(Party::Receiver, 162_0000_0000), (Party::Arbiter, 200_0000_0000)],
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(NO_MATCHED_INPUTS).input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_release_with_arbiter_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_escrow_with_receiver_input() {
+    let (mut context, tx) = build_test_context_with_arbiter(
+        vec![Party::Receiver],
+        None,
+        0,
+        vec![(Party::Receiver, 362_0000_0000)],
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(NO_MATCHED_INPUTS).input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_escrow_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_escrow_with_sender_input() {
+    let (mut context, tx) = build_test_context_with_arbiter(
+        vec![Party::Sender],
+        None,
+        CHEQUE_CELL_SINCE,
+        vec![(Party::Sender, 362_0000_0000)],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_escrow_with_sender_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_refund_with_receiver_and_arbiter_inputs() {
+    let (mut context, tx) = build_test_context_with_arbiter(
+        vec![Party::Receiver, Party::Arbiter],
+        Some(ESCROW_ACTION_REFUND),
+        0,
+        vec![
+            (Party::Sender, 162_0000_0000),
+            (Party::Receiver, 200_0000_0000),
+            (Party::Arbiter, 200_0000_0000),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_refund_with_receiver_and_arbiter_inputs",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_refund_with_capacity_to_other_lock() {
+    // the receiver and the arbiter refund the cheque but send its capacity to another lock
+    let (mut context, tx) = build_test_context_with_arbiter(
+        vec![Party::Receiver, Party::Arbiter],
+        Some(ESCROW_ACTION_REFUND),
+        0,
+        vec![
+            (Party::Receiver, 200_0000_0000),
+            (Party::Arbiter, 200_0000_0000),
+            (Party::Other, 162_0000_0000),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_refund_with_capacity_to_other_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_release_with_capacity_to_other_lock() {
+    // the sender and the arbiter release the cheque but send its capacity to another lock
+    let (mut context, tx) = build_test_context_with_arbiter(
+        vec![Party::Sender, Party::Arbiter],
+        Some(ESCROW_ACTION_RELEASE),
+        0,
+        vec![
+            (Party::Sender, 200_0000_0000),
+            (Party::Arbiter, 200_0000_0000),
+            (Party::Other, 162_0000_0000),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_release_with_capacity_to_other_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_release_escrow_cheques_of_two_senders() {
+    let (mut context, tx) = build_test_context_with_escrow_cheques(
+        vec![Party::Sender, Party::Refund],
+        vec![Party::Sender, Party::Refund, Party::Arbiter],
+        Some(ESCROW_ACTION_RELEASE),
+        0,
+        vec![
+            (Party::Receiver, 324_0000_0000),
+            (Party::Sender, 200_0000_0000),
+            (Party::Refund, 200_0000_0000),
+            (Party::Arbiter, 200_0000_0000),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_release_escrow_cheques_of_two_senders",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_release_escrow_cheques_of_two_senders_with_one_output() {
+    // each escrow cheque cell alone is paid by the receiver output, but both are not
+    let (mut context, tx) = build_test_context_with_escrow_cheques(
+        vec![Party::Sender, Party::Refund],
+        vec![Party::Sender, Party::Refund, Party::Arbiter],
+        Some(ESCROW_ACTION_RELEASE),
+        0,
+        vec![
+            (Party::Receiver, 162_0000_0000),
+            (Party::Sender, 362_0000_0000),
+            (Party::Refund, 200_0000_0000),
+            (Party::Arbiter, 200_0000_0000),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_release_escrow_cheques_of_two_senders_with_one_output",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
@@ -0,0 +1,309 @@
+use super::helper::{
+    build_cell, placeholder_signature, write_native_setup_with_headers, Party, TestTxBuilder,
+    MAX_CYCLES,
+};
+use cheque_core::args::FLAG_WITHDRAW_SINCE;
+use cheque_core::error::Error;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderBuilder, HeaderView, TransactionView},
+    packed::WitnessArgs,
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const FORWARDED_CHEQUE_NOT_MATCHED: i8 = Error::ForwardedChequeNotMatched as i8;
+const INVALID_ARGUMENT: i8 = Error::InvalidArgument as i8;
+
+const CLAIM_ACTION_FORWARD: u8 = 1;
+
+// The cheque cell is created at epoch 100 + 10/1000, so the withdraw deadline is epoch 106 + 10/1000.
+const CHEQUE_CELL_EPOCH: (u64, u64, u64) = (100, 10, 1000);
+const CHEQUE_CELL_DEADLINE: u64 = 0x2000_0000_0000_0000 | (1000 << 40) | (10 << 24) | 106;
+
+// A custom relative withdraw since of 16 epochs ends at epoch 116 + 10/1000.
+const RELATIVE_WITHDRAW_SINCE: u64 = 0xA000_0000_0000_0010;
+const RELATIVE_WITHDRAW_DEADLINE: u64 = 0x2000_0000_0000_0000 | (1000 << 40) | (10 << 24) | 116;
+
+fn build_test_context_with_forward(
+    cheque_withdraw_since: Option<u64>,
+    forwarded_withdraw_since: u64,
+    forwarded_capacity: u64,
+) -> (Context, TransactionView, HeaderView) {
+    let mut builder = TestTxBuilder::default();
+    let cheque_args = match cheque_withdraw_since {
+        Some(withdraw_since) => builder.cheque_args(
+            Party::Receiver,
+            Party::Sender,
+            Some(FLAG_WITHDRAW_SINCE),
+            &withdraw_since.to_le_bytes(),
+        ),
+        None => builder.cheque_args(Party::Receiver, Party::Sender, None, &[]),
+    };
+    let cheque_script = builder.cheque_script(&cheque_args);
+
+    // the cheque is forwarded to the new receiver
+    let forwarded_cheque_args = builder.cheque_args(
+        Party::Other,
+        Party::Sender,
+        Some(FLAG_WITHDRAW_SINCE),
+        &forwarded_withdraw_since.to_le_bytes(),
+    );
+    let forwarded_cheque_script = builder.cheque_script(&forwarded_cheque_args);
+    let receiver_lock_script = builder.lock_script(Party::Receiver);
+
+    // prepare the header of the block in which the cheque cell was created
+    let (number, index, length) = CHEQUE_CELL_EPOCH;
+    let header = HeaderBuilder::default()
+        .epoch(EpochNumberWithFraction::new(number, index, length).full_value().pack())
+        .number((number * 1000).pack())
+        .build();
+    builder.header_dep(&header);
+
+    // prepare inputs
+    let cheque_witness = WitnessArgs::new_builder()
+        .input_type(Some(Bytes::from(vec![CLAIM_ACTION_FORWARD])).pack())
+        .build();
+    let cheque_cell_out_point = builder.input(
+        build_cell(&cheque_script, 162_0000_0000, None),
+        Bytes::new(),
+        0,
+        cheque_witness.as_bytes(),
+    );
+    builder
+        .context
+        .link_cell_with_block(cheque_cell_out_point, header.hash(), 0);
+    builder.input(
+        build_cell(&receiver_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+        0,
+        placeholder_signature(),
+    );
+
+    // prepare outputs
+    builder.output(
+        build_cell(&forwarded_cheque_script, forwarded_capacity, None),
+        Bytes::new(),
+    );
+    builder.output(
+        build_cell(&receiver_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+    );
+
+    let (context, tx) = builder.build();
+    (context, tx, header)
+}
+
+#[test]
+fn test_forward_with_receiver_input() {
+    let (mut context, tx, header) =
+        build_test_context_with_forward(None, CHEQUE_CELL_DEADLINE, 162_0000_0000);
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_forward_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_forward_forwarded_cheque_with_receiver_input() {
+    let withdraw_since = CHEQUE_CELL_DEADLINE + 1;
+    let (mut context, tx, header) =
+        build_test_context_with_forward(Some(withdraw_since), withdraw_since, 162_0000_0000);
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_forward_forwarded_cheque_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_forward_with_capacity_changed() {
+    let (mut context, tx, header) =
+        build_test_context_with_forward(None, CHEQUE_CELL_DEADLINE, 152_0000_0000);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(FORWARDED_CHEQUE_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_forward_with_capacity_changed",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_forward_with_withdraw_since_extended() {
+    let (mut context, tx, header) =
+        build_test_context_with_forward(None, CHEQUE_CELL_DEADLINE + 1, 162_0000_0000);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(FORWARDED_CHEQUE_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_forward_with_withdraw_since_extended",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_forward_cheque_with_relative_withdraw_since() {
+    let (mut context, tx, header) = build_test_context_with_forward(
+        Some(RELATIVE_WITHDRAW_SINCE),
+        RELATIVE_WITHDRAW_DEADLINE,
+        162_0000_0000,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_forward_cheque_with_relative_withdraw_since",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_forward_with_relative_withdraw_since_kept() {
+    // keeping the relative withdraw since would restart the withdraw clock at every forward
+    let (mut context, tx, header) = build_test_context_with_forward(
+        Some(RELATIVE_WITHDRAW_SINCE),
+        RELATIVE_WITHDRAW_SINCE,
+        162_0000_0000,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(FORWARDED_CHEQUE_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_forward_with_relative_withdraw_since_kept",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header.clone()],
+        &[Some(header.hash()), None],
+    );
+}
+
+#[test]
+fn test_error_forward_with_relative_block_withdraw_since() {
+    // a relative withdraw since of 1000 blocks cannot be anchored at the epoch of the cheque cell
+    let withdraw_since = 0x8000_0000_0000_03E8;
+    let (mut context, tx, _) =
+        build_test_context_with_forward(Some(withdraw_since), withdraw_since, 162_0000_0000);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(INVALID_ARGUMENT).input_lock_script(script_cell_index)
+    );
+}
@@ -2,12 +2,12 @@ use super::*;
 use ckb_standalone_debugger::transaction::{
     MockCellDep, MockInfo, MockInput, MockTransaction, ReprMockTransaction,
 };
-use ckb_testtool::context::Context;
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
 use ckb_tool::ckb_crypto::secp::Privkey;
 use ckb_tool::ckb_hash::{blake2b_256, new_blake2b};
 use ckb_tool::ckb_types::{
     bytes::Bytes,
-    core::{DepType, TransactionView},
+    core::{Capacity, DepType, HeaderView, TransactionBuilder, TransactionView},
     packed::{self, *},
     prelude::*,
     H256,
@@ -20,15 +20,6 @@ use std::path::PathBuf;
 #[allow(dead_code)]
 pub const MAX_CYCLES: u64 = 10_000_000;
 
-#[allow(dead_code)]
-pub const TYPE: u8 = 1;
-
-#[allow(dead_code)]
-pub const CODE_HASH_SECP256K1_BLAKE160: [u8; 32] = [
-    155, 215, 224, 111, 62, 207, 75, 224, 242, 252, 210, 24, 139, 35, 241, 185, 252, 200, 142, 93,
-    75, 101, 168, 99, 123, 23, 114, 59, 189, 163, 204, 232,
-];
-
 #[allow(dead_code)]
 pub fn blake160(data: &[u8]) -> [u8; 20] {
     let mut buf = [0u8; 20];
@@ -39,6 +30,12 @@ pub fn blake160(data: &[u8]) -> [u8; 20] {
 
 #[allow(dead_code)]
 pub fn sign_tx(tx: TransactionView, key: &Privkey) -> TransactionView {
+    sign_tx_by_keys(tx, &[key])
+}
+
+// Every key signs the same message, and the witness lock holds the signatures in the order of the keys
+#[allow(dead_code)]
+pub fn sign_tx_by_keys(tx: TransactionView, keys: &[&Privkey]) -> TransactionView {
     const SIGNATURE_SIZE: usize = 65;
     let witnesses_len = tx.witnesses().len();
     let tx_hash = tx.hash();
@@ -50,7 +47,7 @@ pub fn sign_tx(tx: TransactionView, key: &Privkey) -> TransactionView {
     let witness = WitnessArgs::default();
     let zero_lock: Bytes = {
         let mut buf = Vec::new();
-        buf.resize(SIGNATURE_SIZE, 0);
+        buf.resize(SIGNATURE_SIZE * keys.len(), 0);
         buf.into()
     };
     let witness_for_digest = witness
@@ -63,11 +60,15 @@ pub fn sign_tx(tx: TransactionView, key: &Privkey) -> TransactionView {
     blake2b.update(&witness_for_digest.as_bytes());
     blake2b.finalize(&mut message);
     let message = H256::from(message);
-    let sig = key.sign_recoverable(&message).expect("sign");
+    let mut signatures = Vec::new();
+    for key in keys.iter() {
+        let sig = key.sign_recoverable(&message).expect("sign");
+        signatures.extend_from_slice(&sig.serialize());
+    }
     signed_witnesses.push(
         witness
             .as_builder()
-            .lock(Some(Bytes::from(sig.serialize())).pack())
+            .lock(Some(Bytes::from(signatures)).pack())
             .build()
             .as_bytes()
             .pack(),
@@ -80,6 +81,192 @@ pub fn sign_tx(tx: TransactionView, key: &Privkey) -> TransactionView {
         .build()
 }
 
+// The witness of an input locked by the always_success script, which can be any signature
+#[allow(dead_code)]
+pub fn placeholder_signature() -> Bytes {
+    Bytes::from(
+        hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap())
+}
+
+// The parties of the test transactions, which are locked by the always_success script with
+// their own args
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum Party {
+    Receiver,
+    Sender,
+    Arbiter,
+    Refund,
+    Other,
+}
+
+#[allow(dead_code)]
+impl Party {
+    fn lock_args(self) -> Bytes {
+        match self {
+            Party::Receiver => {
+                Bytes::from(hex::decode("f43cc005be4edf45c829363d54799ac4f7aff5a5").unwrap())
+            }
+            Party::Sender => {
+                Bytes::from(hex::decode("36c329ed630d6ce750712a477543672adab57f4c").unwrap())
+            }
+            Party::Arbiter => {
+                Bytes::from(hex::decode("373cc005be4edf45c829363d54799ac4f7aff569").unwrap())
+            }
+            Party::Refund => {
+                Bytes::from(hex::decode("c8328aabcd9b9e8e64fbc566c4385c3bdeb219d7").unwrap())
+            }
+            Party::Other => Bytes::from(vec![4u8]),
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub fn build_cell(lock_script: &Script, capacity: u64, type_script: Option<Script>) -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(Capacity::shannons(capacity).pack())
+        .lock(lock_script.clone())
+        .type_(type_script.pack())
+        .build()
+}
+
+// Builds a test transaction with the cheque script and the always_success script deployed, whose
+// cell deps go first. Every input is created in the context with its own since and witness.
+#[allow(dead_code)]
+pub struct TestTxBuilder {
+    pub context:                  Context,
+    pub cheque_out_point:         OutPoint,
+    pub always_success_out_point: OutPoint,
+    inputs:                       Vec<CellInput>,
+    witnesses:                    Vec<Bytes>,
+    outputs:                      Vec<CellOutput>,
+    outputs_data:                 Vec<Bytes>,
+    cell_deps:                    Vec<CellDep>,
+    header_deps:                  Vec<Byte32>,
+}
+
+impl Default for TestTxBuilder {
+    fn default() -> Self {
+        let mut context = Context::default();
+        let cheque_bin: Bytes = Loader::default().load_binary("ckb-cheque-script");
+        let cheque_out_point = context.deploy_cell(cheque_bin);
+        let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+        let cell_deps = vec![
+            CellDep::new_builder()
+                .out_point(cheque_out_point.clone())
+                .build(),
+            CellDep::new_builder()
+                .out_point(always_success_out_point.clone())
+                .build(),
+        ];
+        TestTxBuilder {
+            context,
+            cheque_out_point,
+            always_success_out_point,
+            inputs: vec![],
+            witnesses: vec![],
+            outputs: vec![],
+            outputs_data: vec![],
+            cell_deps,
+            header_deps: vec![],
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl TestTxBuilder {
+    pub fn always_success_script(&mut self, args: Bytes) -> Script {
+        self.context
+            .build_script(&self.always_success_out_point, args)
+            .expect("script")
+    }
+
+    pub fn lock_script(&mut self, party: Party) -> Script {
+        self.always_success_script(party.lock_args())
+    }
+
+    pub fn lock_hash(&mut self, party: Party) -> Byte32 {
+        self.lock_script(party).calc_script_hash()
+    }
+
+    // The udt type script is the always_success script whose args tell one udt from another
+    pub fn udt_type_script(&mut self, type_args: u8) -> Script {
+        self.always_success_script(Bytes::from(vec![type_args]))
+    }
+
+    // The first 20 bytes of the lock hashes of the receiver and the sender, followed by the flags
+    // and the fields of the flags
+    pub fn cheque_args(
+        &mut self,
+        receiver: Party,
+        sender: Party,
+        flags: Option<u16>,
+        fields: &[u8],
+    ) -> Vec<u8> {
+        let receiver_lock_hash = self.lock_hash(receiver);
+        let sender_lock_hash = self.lock_hash(sender);
+        let mut cheque_args = receiver_lock_hash.as_bytes().slice(0..20).to_vec();
+        cheque_args.extend_from_slice(&sender_lock_hash.as_bytes().slice(0..20));
+        if let Some(flags) = flags {
+            cheque_args.extend_from_slice(&flags.to_le_bytes());
+        }
+        cheque_args.extend_from_slice(fields);
+        cheque_args
+    }
+
+    pub fn cheque_script(&mut self, cheque_args: &[u8]) -> Script {
+        self.context
+            .build_script(&self.cheque_out_point, Bytes::copy_from_slice(cheque_args))
+            .expect("script")
+    }
+
+    pub fn input(
+        &mut self,
+        output: CellOutput,
+        data: Bytes,
+        since: u64,
+        witness: Bytes,
+    ) -> OutPoint {
+        let out_point = self.context.create_cell(output, data);
+        self.inputs.push(
+            CellInput::new_builder()
+                .previous_output(out_point.clone())
+                .since(since.pack())
+                .build(),
+        );
+        self.witnesses.push(witness);
+        out_point
+    }
+
+    pub fn output(&mut self, output: CellOutput, data: Bytes) {
+        self.outputs.push(output);
+        self.outputs_data.push(data);
+    }
+
+    pub fn cell_dep(&mut self, out_point: OutPoint) {
+        self.cell_deps
+            .push(CellDep::new_builder().out_point(out_point).build());
+    }
+
+    // The header is inserted into the context as well, so that the inputs can be linked with it
+    pub fn header_dep(&mut self, header: &HeaderView) {
+        self.context.insert_header(header.clone());
+        self.header_deps.push(header.hash());
+    }
+
+    pub fn build(self) -> (Context, TransactionView) {
+        let tx = TransactionBuilder::default()
+            .inputs(self.inputs)
+            .outputs(self.outputs)
+            .outputs_data(self.outputs_data.pack())
+            .cell_deps(self.cell_deps)
+            .header_deps(self.header_deps)
+            .witnesses(self.witnesses.pack())
+            .build();
+        (self.context, tx)
+    }
+}
+
 fn create_test_folder(name: &str) -> PathBuf {
     let mut path = TX_FOLDER.clone();
     path.push(&name);
@@ -87,11 +274,17 @@ fn create_test_folder(name: &str) -> PathBuf {
     path
 }
 
-fn build_mock_transaction(tx: &TransactionView, context: &Context) -> MockTransaction {
+pub fn build_mock_transaction(
+    tx: &TransactionView,
+    context: &Context,
+    header_deps: &[HeaderView],
+    input_headers: &[Option<Byte32>],
+) -> MockTransaction {
     let mock_inputs = tx
         .inputs()
         .into_iter()
-        .map(|input| {
+        .enumerate()
+        .map(|(index, input)| {
             let (output, data) = context
                 .get_cell(&input.previous_output())
                 .expect("get cell");
@@ -99,7 +292,7 @@ fn build_mock_transaction(tx: &TransactionView, context: &Context) -> MockTransa
                 input,
                 output,
                 data,
-                header: None,
+                header: input_headers.get(index).cloned().flatten(),
             }
         })
         .collect();
@@ -122,7 +315,7 @@ fn build_mock_transaction(tx: &TransactionView, context: &Context) -> MockTransa
     let mock_info = MockInfo {
         inputs:      mock_inputs,
         cell_deps:   mock_cell_deps,
-        header_deps: vec![],
+        header_deps: header_deps.to_vec(),
     };
     MockTransaction {
         mock_info,
@@ -137,9 +330,24 @@ pub fn write_native_setup(
     tx: &TransactionView,
     context: &Context,
     setup: &RunningSetup,
+) {
+    write_native_setup_with_headers(test_name, binary_name, tx, context, setup, &[], &[]);
+}
+
+// The header deps of the transaction and the block hashes of the inputs are not kept by the
+// context, so they are given by the test which needs to load headers.
+#[allow(dead_code)]
+pub fn write_native_setup_with_headers(
+    test_name: &str,
+    binary_name: &str,
+    tx: &TransactionView,
+    context: &Context,
+    setup: &RunningSetup,
+    header_deps: &[HeaderView],
+    input_headers: &[Option<Byte32>],
 ) {
     let folder = create_test_folder(test_name);
-    let mock_tx = build_mock_transaction(&tx, &context);
+    let mock_tx = build_mock_transaction(&tx, &context, header_deps, input_headers);
     let repr_tx: ReprMockTransaction = mock_tx.into();
     let tx_json = to_string_pretty(&repr_tx).expect("serialize to json");
     fs::write(folder.join("tx.json"), tx_json).expect("write tx to local file");
@@ -0,0 +1,257 @@
+use cheque_core::args::{ChequeArgs, FLAG_ARBITER};
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use cheque_indexer::{BlockSource, Error, Indexer, JsonBlockSource, SpendKind};
+use ckb_jsonrpc_types::BlockView as JsonBlockView;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{BlockBuilder, BlockView, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use std::fs;
+
+const RECEIVER_LOCK_HASH: [u8; 20] = [1u8; 20];
+const SENDER_LOCK_HASH: [u8; 20] = [2u8; 20];
+const ARBITER_LOCK_HASH: [u8; 20] = [3u8; 20];
+const OTHER_RECEIVER_LOCK_HASH: [u8; 20] = [4u8; 20];
+
+fn cheque_code_hash() -> Byte32 {
+    [9u8; 32].pack()
+}
+
+fn build_indexer() -> Indexer {
+    let db = sled::Config::new().temporary(true).open().expect("db");
+    Indexer::new(db, cheque_code_hash(), ScriptHashType::Data)
+}
+
+fn build_cheque_args(receiver_lock_hash: [u8; 20]) -> ChequeArgs {
+    let mut cheque_args = ChequeArgs::from_lock_hashes(&[0u8; 32], &[0u8; 32]);
+    cheque_args.receiver_lock_hash = receiver_lock_hash;
+    cheque_args.sender_lock_hash = SENDER_LOCK_HASH;
+    cheque_args
+}
+
+fn build_cheque_output(cheque_args: &ChequeArgs) -> CellOutput {
+    let cheque_script = Script::new_builder()
+        .code_hash(cheque_code_hash())
+        .hash_type(ScriptHashType::Data.into())
+        .args(Bytes::from(cheque_args.to_bytes()).pack())
+        .build();
+    CellOutput::new_builder()
+        .capacity(200_0000_0000u64.pack())
+        .lock(cheque_script)
+        .build()
+}
+
+fn build_other_output() -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(200_0000_0000u64.pack())
+        .lock(Script::new_builder().code_hash([8u8; 32].pack()).build())
+        .build()
+}
+
+// (out point, since, witness)
+fn build_tx(inputs: Vec<(OutPoint, u64, Bytes)>, outputs: Vec<CellOutput>) -> TransactionView {
+    let mut builder = TransactionBuilder::default();
+    for (out_point, since, witness) in inputs {
+        builder = builder
+            .input(CellInput::new(out_point, since))
+            .witness(witness.pack());
+    }
+    for output in outputs {
+        builder = builder.output(output).output_data(Bytes::new().pack());
+    }
+    builder.build()
+}
+
+fn build_block(parent: Option<&BlockView>, txs: Vec<TransactionView>) -> BlockView {
+    let builder = match parent {
+        Some(parent) => BlockBuilder::default()
+            .parent_hash(parent.hash())
+            .number((parent.number() + 1).pack()),
+        None => BlockBuilder::default(),
+    };
+    builder.transactions(txs).build()
+}
+
+fn escrow_witness(action: u8) -> Bytes {
+    WitnessArgs::new_builder()
+        .input_type(Some(Bytes::from(vec![action])).pack())
+        .build()
+        .as_bytes()
+}
+
+#[test]
+fn test_index_claimed_and_withdrawn_cheques() {
+    let mut escrow_args = build_cheque_args(OTHER_RECEIVER_LOCK_HASH);
+    escrow_args.flags = FLAG_ARBITER;
+    escrow_args.arbiter_lock_hash = Some(ARBITER_LOCK_HASH);
+    let create_tx = build_tx(vec![], vec![
+        build_cheque_output(&build_cheque_args(RECEIVER_LOCK_HASH)),
+        build_cheque_output(&build_cheque_args(OTHER_RECEIVER_LOCK_HASH)),
+        build_cheque_output(&escrow_args),
+        build_other_output(),
+    ]);
+    let genesis = build_block(None, vec![create_tx.clone()]);
+
+    // the receiver claims with the since zero, the sender withdraws with the withdraw since, and
+    // the escrow refunds with the action 3
+    let claim_tx = build_tx(
+        vec![(OutPoint::new(create_tx.hash(), 0), 0, Bytes::new())],
+        vec![build_other_output()],
+    );
+    let withdraw_tx = build_tx(
+        vec![
+            (
+                OutPoint::new(create_tx.hash(), 1),
+                CHEQUE_CELL_SINCE,
+                Bytes::new(),
+            ),
+            (OutPoint::new(create_tx.hash(), 2), 0, escrow_witness(3)),
+        ],
+        vec![build_other_output()],
+    );
+    let block = build_block(Some(&genesis), vec![claim_tx.clone(), withdraw_tx.clone()]);
+
+    let indexer = build_indexer();
+    indexer.append(&genesis).expect("append");
+    let cheques = indexer
+        .cheques_by_sender(&SENDER_LOCK_HASH)
+        .expect("cheques");
+    assert_eq!(cheques.len(), 3);
+    assert!(cheques.iter().all(|cheque| cheque.is_live()));
+
+    indexer.append(&block).expect("append");
+    assert_eq!(indexer.tip().expect("tip"), Some((1, block.hash())));
+    let cheques = indexer
+        .cheques_by_receiver(&RECEIVER_LOCK_HASH)
+        .expect("cheques");
+    assert_eq!(cheques.len(), 1);
+    let spend = cheques[0].spend.as_ref().expect("spend");
+    assert_eq!(spend.kind, SpendKind::Claim);
+    assert_eq!(spend.tx_hash, claim_tx.hash());
+    assert_eq!(spend.block_number, 1);
+
+    let cheques = indexer
+        .cheques_by_receiver(&OTHER_RECEIVER_LOCK_HASH)
+        .expect("cheques");
+    assert_eq!(cheques.len(), 2);
+    for cheque in cheques {
+        let spend = cheque.spend.expect("spend");
+        assert_eq!(spend.kind, SpendKind::Withdraw);
+        assert_eq!(spend.tx_hash, withdraw_tx.hash());
+    }
+    assert!(indexer
+        .cheques_by_receiver(&ARBITER_LOCK_HASH)
+        .expect("cheques")
+        .is_empty());
+}
+
+#[test]
+fn test_index_cheque_created_and_claimed_in_one_block() {
+    let create_tx = build_tx(vec![], vec![build_cheque_output(&build_cheque_args(
+        RECEIVER_LOCK_HASH,
+    ))]);
+    let claim_tx = build_tx(
+        vec![(OutPoint::new(create_tx.hash(), 0), 0, Bytes::new())],
+        vec![build_other_output()],
+    );
+    let genesis = build_block(None, vec![create_tx.clone(), claim_tx.clone()]);
+
+    let indexer = build_indexer();
+    indexer.append(&genesis).expect("append");
+    let cheque = indexer
+        .cheque(&OutPoint::new(create_tx.hash(), 0))
+        .expect("cheque")
+        .expect("cheque");
+    assert_eq!(cheque.block_number, 0);
+    assert_eq!(cheque.spend.expect("spend").tx_hash, claim_tx.hash());
+
+    // the cheque cell is removed with the block
+    assert_eq!(indexer.rollback().expect("rollback"), Some(0));
+    assert!(indexer
+        .cheque(&OutPoint::new(create_tx.hash(), 0))
+        .expect("cheque")
+        .is_none());
+    assert!(indexer
+        .cheques_by_receiver(&RECEIVER_LOCK_HASH)
+        .expect("cheques")
+        .is_empty());
+    assert_eq!(indexer.tip().expect("tip"), None);
+}
+
+#[test]
+fn test_rollback_on_reorg() {
+    let create_tx = build_tx(vec![], vec![build_cheque_output(&build_cheque_args(
+        RECEIVER_LOCK_HASH,
+    ))]);
+    let genesis = build_block(None, vec![create_tx.clone()]);
+    let claim_tx = build_tx(
+        vec![(OutPoint::new(create_tx.hash(), 0), 0, Bytes::new())],
+        vec![build_other_output()],
+    );
+    let fork_a = build_block(Some(&genesis), vec![claim_tx]);
+    let mut chain_a = vec![genesis.clone(), fork_a];
+
+    // the other fork is longer and does not claim the cheque cell
+    let other_tx = build_tx(vec![], vec![build_other_output()]);
+    let fork_b = build_block(Some(&genesis), vec![other_tx]);
+    let fork_b_child = build_block(Some(&fork_b), vec![]);
+    let mut chain_b = vec![genesis, fork_b, fork_b_child.clone()];
+
+    let indexer = build_indexer();
+    assert_eq!(indexer.sync(&mut chain_a).expect("sync"), Some(1));
+    let cheque_out_point = OutPoint::new(create_tx.hash(), 0);
+    assert!(!indexer
+        .cheque(&cheque_out_point)
+        .expect("cheque")
+        .expect("cheque")
+        .is_live());
+
+    assert_eq!(indexer.sync(&mut chain_b).expect("sync"), Some(2));
+    assert_eq!(indexer.tip().expect("tip"), Some((2, fork_b_child.hash())));
+    assert!(indexer
+        .cheque(&cheque_out_point)
+        .expect("cheque")
+        .expect("cheque")
+        .is_live());
+
+    // the block not following the tip is refused
+    assert!(matches!(
+        indexer.append(&chain_a[1]),
+        Err(Error::BlockNotLinked)
+    ));
+    assert!(chain_a.block(2).expect("block").is_none());
+}
+
+#[test]
+fn test_sync_from_json_block_dumps() {
+    let create_tx = build_tx(vec![], vec![build_cheque_output(&build_cheque_args(
+        RECEIVER_LOCK_HASH,
+    ))]);
+    let genesis = build_block(None, vec![create_tx.clone()]);
+    let block = build_block(Some(&genesis), vec![]);
+
+    // the dumps of the get_block_by_number RPC
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("cheque-indexer-{}", block.hash()));
+    fs::create_dir_all(&dir).expect("dir");
+    for block in [genesis, block.clone()].iter() {
+        let json = serde_json::to_string_pretty(&JsonBlockView::from(block.clone())).expect("json");
+        fs::write(dir.join(format!("{}.json", block.number())), json).expect("write");
+    }
+
+    let indexer = build_indexer();
+    let mut source = JsonBlockSource::new(&dir);
+    assert_eq!(indexer.sync(&mut source).expect("sync"), Some(1));
+    assert_eq!(indexer.tip().expect("tip"), Some((1, block.hash())));
+    let cheques = indexer
+        .cheques_by_receiver(&RECEIVER_LOCK_HASH)
+        .expect("cheques");
+    assert_eq!(cheques.len(), 1);
+    assert_eq!(
+        cheques[0].out_point.as_slice(),
+        OutPoint::new(create_tx.hash(), 0).as_slice()
+    );
+    fs::remove_dir_all(&dir).expect("remove");
+}
@@ -18,6 +18,72 @@ mod claim_tests;
 #[cfg(test)]
 mod withdraw_tests;
 
+#[cfg(test)]
+mod multi_asset_tests;
+
+#[cfg(test)]
+mod nft_tests;
+
+#[cfg(test)]
+mod forward_tests;
+
+#[cfg(test)]
+mod oracle_tests;
+
+#[cfg(test)]
+mod claim_since_tests;
+
+#[cfg(test)]
+mod salted_tests;
+
+#[cfg(test)]
+mod vesting_tests;
+
+#[cfg(test)]
+mod receiver_group_tests;
+
+#[cfg(test)]
+mod escrow_tests;
+
+#[cfg(test)]
+mod refund_tests;
+
+#[cfg(test)]
+mod otx_tests;
+
+#[cfg(test)]
+mod dao_tests;
+
+#[cfg(test)]
+mod subscription_tests;
+
+#[cfg(test)]
+mod sdk_tests;
+
+#[cfg(test)]
+mod args_tests;
+
+#[cfg(test)]
+mod signer_tests;
+
+#[cfg(test)]
+mod lifecycle_tests;
+
+#[cfg(test)]
+mod indexer_tests;
+
+#[cfg(test)]
+mod natives_tests;
+
+#[cfg(test)]
+mod analyzer_tests;
+
+#[cfg(test)]
+mod cli_tests;
+
+#[cfg(test)]
+mod cycles_tests;
+
 lazy_static! {
     static ref LOADER: Loader = Loader::default();
     static ref TX_FOLDER: PathBuf = {
@@ -0,0 +1,204 @@
+use cheque_core::args::{ChequeArgs, FLAG_ARBITER, FLAG_CLAIM_SINCE, FLAG_WITHDRAW_SINCE};
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use cheque_sdk::lifecycle::since_deadline;
+use cheque_sdk::{ChequeLifecycle, ChequeState, Deadline};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderBuilder, HeaderView},
+    packed::*,
+    prelude::*,
+};
+
+const RELATIVE_EPOCH_FLAGS: u64 = 0xA000_0000_0000_0000;
+const ABSOLUTE_EPOCH_FLAGS: u64 = 0x2000_0000_0000_0000;
+
+fn build_cheque_cell(cheque_args: &ChequeArgs) -> CellOutput {
+    let cheque_script = Script::new_builder()
+        .code_hash([1u8; 32].pack())
+        .args(Bytes::from(cheque_args.to_bytes()).pack())
+        .build();
+    CellOutput::new_builder()
+        .capacity(200_0000_0000u64.pack())
+        .lock(cheque_script)
+        .build()
+}
+
+fn build_header(number: u64, epoch: (u64, u64, u64)) -> HeaderView {
+    let (epoch_number, index, length) = epoch;
+    HeaderBuilder::default()
+        .epoch(
+            EpochNumberWithFraction::new(epoch_number, index, length)
+                .full_value()
+                .pack(),
+        )
+        .number(number.pack())
+        .timestamp((number * 8000).pack())
+        .build()
+}
+
+fn epoch_since(flags: u64, epoch: (u64, u64, u64)) -> u64 {
+    let (number, index, length) = epoch;
+    flags | EpochNumberWithFraction::new(number, index, length).full_value()
+}
+
+#[test]
+fn test_lifecycle_of_default_cheque() {
+    let cheque_cell = build_cheque_cell(&ChequeArgs::from_lock_hashes(&[1u8; 32], &[2u8; 32]));
+    let created_header = build_header(100_000, (100, 300, 1000));
+
+    // the lock-up period of CHEQUE_CELL_SINCE is 6 epochs from the creation
+    let lifecycle = ChequeLifecycle::calculate(
+        &cheque_cell,
+        &created_header,
+        &build_header(106_000, (106, 299, 1000)),
+    )
+    .expect("lifecycle");
+    assert_eq!(lifecycle.state, ChequeState::Claimable);
+    assert_eq!(lifecycle.claim_opens_at, None);
+    assert_eq!(lifecycle.withdraw_opens_at, Deadline::Epoch {
+        number: 106,
+        index:  3,
+        length: 10,
+    });
+    assert!(lifecycle.receiver_can_claim());
+    assert!(!lifecycle.sender_can_withdraw());
+
+    let lifecycle = ChequeLifecycle::calculate(
+        &cheque_cell,
+        &created_header,
+        &build_header(106_001, (106, 300, 1000)),
+    )
+    .expect("lifecycle");
+    assert_eq!(lifecycle.state, ChequeState::ClaimableOrWithdrawable);
+    assert!(lifecycle.receiver_can_claim());
+    assert!(lifecycle.sender_can_withdraw());
+}
+
+#[test]
+fn test_relative_epoch_since_with_fractions() {
+    // the epochs of different lengths are compared by their fractions
+    let created_header = build_header(10_000, (10, 1, 2));
+    let deadline = since_deadline(CHEQUE_CELL_SINCE, &created_header);
+    assert_eq!(deadline, Deadline::Epoch {
+        number: 16,
+        index:  1,
+        length: 2,
+    });
+    assert!(!deadline.is_reached(&build_header(16_000, (16, 499, 1000))));
+    assert!(deadline.is_reached(&build_header(16_001, (16, 500, 1000))));
+    assert!(deadline.is_reached(&build_header(17_000, (17, 0, 1000))));
+
+    // the fractions of the creation and the since are added exactly
+    let since = epoch_since(RELATIVE_EPOCH_FLAGS, (1, 1, 3));
+    assert_eq!(since_deadline(since, &created_header), Deadline::Epoch {
+        number: 11,
+        index:  5,
+        length: 6,
+    });
+    // and carried into the epoch number
+    let since = epoch_since(RELATIVE_EPOCH_FLAGS, (0, 2, 3));
+    assert_eq!(
+        since_deadline(since, &build_header(10_000, (10, 2, 3))),
+        Deadline::Epoch {
+            number: 11,
+            index:  1,
+            length: 3,
+        }
+    );
+}
+
+#[test]
+fn test_lifecycle_with_claim_since_and_absolute_withdraw_since() {
+    let mut cheque_args = ChequeArgs::from_lock_hashes(&[1u8; 32], &[2u8; 32]);
+    cheque_args.flags = FLAG_WITHDRAW_SINCE | FLAG_CLAIM_SINCE;
+    // the forwarded cheque keeps the absolute deadline of the original cheque cell
+    cheque_args.withdraw_since = Some(epoch_since(ABSOLUTE_EPOCH_FLAGS, (120, 1, 4)));
+    // the receiver can claim from the block 110000 on
+    cheque_args.claim_since = Some(110_000);
+    let cheque_cell = build_cheque_cell(&cheque_args);
+    let created_header = build_header(100_000, (100, 0, 1000));
+
+    let lifecycle = |tip_header: HeaderView| {
+        ChequeLifecycle::calculate(&cheque_cell, &created_header, &tip_header).expect("lifecycle")
+    };
+    let locked = lifecycle(build_header(109_999, (109, 999, 1000)));
+    assert_eq!(locked.state, ChequeState::Locked);
+    assert_eq!(locked.claim_opens_at, Some(Deadline::BlockNumber(110_000)));
+    assert_eq!(locked.withdraw_opens_at, Deadline::Epoch {
+        number: 120,
+        index:  1,
+        length: 4,
+    });
+    assert_eq!(
+        lifecycle(build_header(110_000, (110, 0, 1000))).state,
+        ChequeState::Claimable
+    );
+    assert_eq!(
+        lifecycle(build_header(120_250, (120, 250, 1000))).state,
+        ChequeState::ClaimableOrWithdrawable
+    );
+}
+
+#[test]
+fn test_lifecycle_of_escrow_cheque() {
+    let mut cheque_args = ChequeArgs::from_lock_hashes(&[1u8; 32], &[2u8; 32]);
+    cheque_args.flags = FLAG_ARBITER;
+    cheque_args.arbiter_lock_hash = Some([3u8; 20]);
+    let cheque_cell = build_cheque_cell(&cheque_args);
+    let created_header = build_header(100_000, (100, 0, 1000));
+
+    // the receiver never claims the escrow cheque alone
+    let lifecycle = ChequeLifecycle::calculate(
+        &cheque_cell,
+        &created_header,
+        &build_header(101_000, (101, 0, 1000)),
+    )
+    .expect("lifecycle");
+    assert_eq!(lifecycle.state, ChequeState::Locked);
+    assert_eq!(lifecycle.claim_opens_at, Some(Deadline::Never));
+
+    let lifecycle = ChequeLifecycle::calculate(
+        &cheque_cell,
+        &created_header,
+        &build_header(106_000, (106, 0, 1000)),
+    )
+    .expect("lifecycle");
+    assert_eq!(lifecycle.state, ChequeState::Withdrawable);
+    assert!(!lifecycle.receiver_can_claim());
+    assert!(lifecycle.sender_can_withdraw());
+}
+
+#[test]
+fn test_malformed_withdraw_since_never_opens() {
+    let created_header = build_header(100_000, (100, 0, 1000));
+    let tip_header = build_header(1_000_000, (1000, 0, 1000));
+
+    // the index of the epoch fraction must be less than the length
+    let mut cheque_args = ChequeArgs::from_lock_hashes(&[1u8; 32], &[2u8; 32]);
+    cheque_args.flags = FLAG_WITHDRAW_SINCE;
+    cheque_args.withdraw_since = Some(RELATIVE_EPOCH_FLAGS | (4 << 40) | (5 << 24) | 6);
+    let lifecycle = ChequeLifecycle::calculate(
+        &build_cheque_cell(&cheque_args),
+        &created_header,
+        &tip_header,
+    )
+    .expect("lifecycle");
+    assert_eq!(lifecycle.state, ChequeState::Claimable);
+    assert_eq!(lifecycle.withdraw_opens_at, Deadline::Never);
+
+    // the reserved bits of since must be zero and the metric 3 is unknown
+    assert_eq!(
+        since_deadline(CHEQUE_CELL_SINCE | 0x0100_0000_0000_0000, &created_header),
+        Deadline::Never
+    );
+    assert_eq!(
+        since_deadline(0x6000_0000_0000_0006, &created_header),
+        Deadline::Never
+    );
+
+    // the relative timestamp since is in seconds
+    assert_eq!(
+        since_deadline(0xC000_0000_0000_0E10, &created_header),
+        Deadline::Timestamp(800_000_000 + 3600 * 1000)
+    );
+}
@@ -0,0 +1,246 @@
+use super::helper::{
+    build_cell, placeholder_signature, write_native_setup, Party, TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::FLAG_CAPACITY;
+use cheque_core::error::Error;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{CellOutput, Script},
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const CHEQUE_ASSET_NOT_MATCHED: i8 = Error::ChequeAssetNotMatched as i8;
+
+// (capacity, Option<(udt type args, udt amount)>)
+type Asset = (u64, Option<(u8, u128)>);
+
+fn build_asset_cell(
+    builder: &mut TestTxBuilder,
+    lock_script: &Script,
+    asset: &Asset,
+) -> (CellOutput, Bytes) {
+    let (capacity, udt) = asset;
+    match udt {
+        Some((type_args, amount)) => {
+            let udt_type_script = builder.udt_type_script(*type_args);
+            let output = build_cell(lock_script, *capacity, Some(udt_type_script));
+            (output, Bytes::from(amount.to_le_bytes().to_vec()))
+        }
+        None => (build_cell(lock_script, *capacity, None), Bytes::new()),
+    }
+}
+
+fn build_test_context_with_assets(
+    flags: Option<u16>,
+    cheque_assets: Vec<Asset>,
+    party: Party,
+    party_input_capacity: u64,
+    outputs: Vec<(Party, Asset)>,
+    since: u64,
+) -> (Context, TransactionView) {
+    let mut builder = TestTxBuilder::default();
+    let cheque_args = builder.cheque_args(Party::Receiver, Party::Sender, flags, &[]);
+    let cheque_script = builder.cheque_script(&cheque_args);
+
+    // prepare cheque cells and the input of the party
+    for asset in cheque_assets.iter() {
+        let (output, data) = build_asset_cell(&mut builder, &cheque_script, asset);
+        builder.input(output, data, since, Bytes::new());
+    }
+    let party_lock_script = builder.lock_script(party);
+    builder.input(
+        build_cell(&party_lock_script, party_input_capacity, None),
+        Bytes::new(),
+        0,
+        placeholder_signature(),
+    );
+
+    // prepare outputs
+    for (output_party, asset) in outputs.iter() {
+        let lock_script = builder.lock_script(*output_party);
+        let (output, data) = build_asset_cell(&mut builder, &lock_script, asset);
+        builder.output(output, data);
+    }
+
+    builder.build()
+}
+
+#[test]
+fn test_claim_multi_asset_with_receiver_input() {
+    let (mut context, tx) = build_test_context_with_assets(
+        Some(FLAG_CAPACITY),
+        vec![
+            (500_0000_0000, None),
+            (142_0000_0000, Some((1, 1000))),
+            (142_0000_0000, Some((2, 2000))),
+        ],
+        Party::Receiver,
+        200_0000_0000,
+        vec![
+            (Party::Receiver, (142_0000_0000, Some((1, 1000)))),
+            (Party::Receiver, (142_0000_0000, Some((2, 2000)))),
+            (Party::Receiver, (416_0000_0000, None)),
+            (Party::Sender, (284_0000_0000, None)),
+        ],
+        0,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_multi_asset_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_multi_asset_with_udt_amount_not_enough() {
+    let (mut context, tx) = build_test_context_with_assets(
+        Some(FLAG_CAPACITY),
+        vec![
+            (500_0000_0000, None),
+            (142_0000_0000, Some((1, 1000))),
+            (142_0000_0000, Some((2, 2000))),
+        ],
+        Party::Receiver,
+        200_0000_0000,
+        vec![
+            (Party::Receiver, (142_0000_0000, Some((1, 1000)))),
+            (Party::Receiver, (142_0000_0000, Some((2, 1500)))),
+            (Party::Receiver, (416_0000_0000, None)),
+            (Party::Sender, (284_0000_0000, None)),
+        ],
+        0,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_multi_asset_with_udt_amount_not_enough",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_multi_asset_with_sender_input() {
+    let (mut context, tx) = build_test_context_with_assets(
+        Some(FLAG_CAPACITY),
+        vec![
+            (500_0000_0000, None),
+            (142_0000_0000, Some((1, 1000))),
+            (142_0000_0000, Some((2, 2000))),
+        ],
+        Party::Sender,
+        200_0000_0000,
+        vec![
+            (Party::Sender, (142_0000_0000, Some((1, 1000)))),
+            (Party::Sender, (142_0000_0000, Some((2, 2000)))),
+            (Party::Sender, (700_0000_0000, None)),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_multi_asset_with_sender_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_withdraw_multi_asset_with_udt_to_receiver() {
+    let (mut context, tx) = build_test_context_with_assets(
+        Some(FLAG_CAPACITY),
+        vec![
+            (500_0000_0000, None),
+            (142_0000_0000, Some((1, 1000))),
+            (142_0000_0000, Some((2, 2000))),
+        ],
+        Party::Sender,
+        200_0000_0000,
+        vec![
+            (Party::Sender, (142_0000_0000, Some((1, 1000)))),
+            (Party::Receiver, (142_0000_0000, Some((2, 2000)))),
+            (Party::Sender, (700_0000_0000, None)),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_withdraw_multi_asset_with_udt_to_receiver",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
@@ -0,0 +1,75 @@
+use super::{claim_tests::build_test_context_with_receiver_cell, helper::build_mock_transaction};
+use cheque_core::error::Error;
+use ckb_standalone_debugger::transaction::MockTransaction;
+use ckb_tool::ckb_types::bytes::Bytes;
+use ckb_x64_simulator::RunningSetup;
+use natives::{verify_mock_transaction, VerifyError};
+use std::collections::HashMap;
+
+// builds the mock transaction of a claim by the receiver input, the same as
+// `test_claim_with_receiver_input` except for the outputs
+fn build_claim_mock_transaction(outputs_token: Vec<u64>) -> MockTransaction {
+    let (mut context, tx) = build_test_context_with_receiver_cell(
+        Bytes::from(hex::decode("36c329ed630d6ce750712a477543672adab57f4c").unwrap()),
+        Bytes::from(hex::decode("f43cc005be4edf45c829363d54799ac4f7aff5a5").unwrap()),
+        vec![162_0000_0000, 200_0000_0000, 150_0000_0000],
+        outputs_token,
+        Bytes::from(
+            hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap()),
+        0,
+        None,
+    );
+    let tx = context.complete_tx(tx);
+    build_mock_transaction(&tx, &context, &[], &[])
+}
+
+fn cheque_setup() -> RunningSetup {
+    RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    }
+}
+
+#[test]
+fn test_verify_mock_transaction() {
+    let mock_tx = build_claim_mock_transaction(vec![200_0000_0000, 312_0000_0000]);
+
+    assert!(verify_mock_transaction(&mock_tx, &cheque_setup()).is_ok());
+}
+
+#[test]
+fn test_verify_mock_transaction_with_cheque_error() {
+    let mock_tx = build_claim_mock_transaction(vec![210_0000_0000, 292_0000_0000]);
+
+    assert!(matches!(
+        verify_mock_transaction(&mock_tx, &cheque_setup()),
+        Err(VerifyError::Script(Error::SenderCapacityNotSame))
+    ));
+}
+
+#[test]
+fn test_verify_mock_transaction_without_cell_dep() {
+    let mut mock_tx = build_claim_mock_transaction(vec![200_0000_0000, 312_0000_0000]);
+    mock_tx.mock_info.cell_deps.clear();
+
+    assert!(matches!(
+        verify_mock_transaction(&mock_tx, &cheque_setup()),
+        Err(VerifyError::Resolve(_))
+    ));
+}
+
+#[test]
+fn test_verify_mock_transaction_without_script() {
+    let mock_tx = build_claim_mock_transaction(vec![200_0000_0000, 312_0000_0000]);
+    let setup = RunningSetup {
+        script_index: 3,
+        ..cheque_setup()
+    };
+
+    assert!(matches!(
+        verify_mock_transaction(&mock_tx, &setup),
+        Err(VerifyError::ScriptNotFound)
+    ));
+}
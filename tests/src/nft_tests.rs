@@ -0,0 +1,224 @@
+use super::helper::{
+    build_cell, placeholder_signature, write_native_setup, Party, TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::FLAG_NFT;
+use cheque_core::error::Error;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{CellOutput, Script},
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const CHEQUE_ASSET_NOT_MATCHED: i8 = Error::ChequeAssetNotMatched as i8;
+
+// (capacity, Option<(nft type args, nft data)>)
+type Asset = (u64, Option<(u8, &'static [u8])>);
+
+fn build_nft_cell(
+    builder: &mut TestTxBuilder,
+    lock_script: &Script,
+    asset: &Asset,
+) -> (CellOutput, Bytes) {
+    let (capacity, nft) = asset;
+    match nft {
+        Some((type_args, data)) => {
+            let nft_type_script = builder.udt_type_script(*type_args);
+            let output = build_cell(lock_script, *capacity, Some(nft_type_script));
+            (output, Bytes::from(data.to_vec()))
+        }
+        None => (build_cell(lock_script, *capacity, None), Bytes::new()),
+    }
+}
+
+fn build_test_context_with_nfts(
+    flags: Option<u16>,
+    cheque_assets: Vec<Asset>,
+    party: Party,
+    party_input_capacity: u64,
+    outputs: Vec<(Party, Asset)>,
+    since: u64,
+) -> (Context, TransactionView) {
+    let mut builder = TestTxBuilder::default();
+    let cheque_args = builder.cheque_args(Party::Receiver, Party::Sender, flags, &[]);
+    let cheque_script = builder.cheque_script(&cheque_args);
+
+    // prepare cheque cells and the input of the party
+    for asset in cheque_assets.iter() {
+        let (output, data) = build_nft_cell(&mut builder, &cheque_script, asset);
+        builder.input(output, data, since, Bytes::new());
+    }
+    let party_lock_script = builder.lock_script(party);
+    builder.input(
+        build_cell(&party_lock_script, party_input_capacity, None),
+        Bytes::new(),
+        0,
+        placeholder_signature(),
+    );
+
+    // prepare outputs
+    for (output_party, asset) in outputs.iter() {
+        let lock_script = builder.lock_script(*output_party);
+        let (output, data) = build_nft_cell(&mut builder, &lock_script, asset);
+        builder.output(output, data);
+    }
+
+    builder.build()
+}
+
+#[test]
+fn test_claim_nft_with_receiver_input() {
+    let (mut context, tx) = build_test_context_with_nfts(
+        Some(FLAG_NFT),
+        vec![(162_0000_0000, Some((1, &b"mNFT #0001"[..])))],
+        Party::Receiver,
+        200_0000_0000,
+        vec![
+            (Party::Receiver, (200_0000_0000, Some((1, &b"mNFT #0001"[..])))),
+            (Party::Sender, (162_0000_0000, None)),
+        ],
+        0,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_nft_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_nft_with_data_altered() {
+    let (mut context, tx) = build_test_context_with_nfts(
+        Some(FLAG_NFT),
+        vec![(162_0000_0000, Some((1, &b"mNFT #0001"[..])))],
+        Party::Receiver,
+        200_0000_0000,
+        vec![
+            (Party::Receiver, (200_0000_0000, Some((1, &b"mNFT #0002"[..])))),
+            (Party::Sender, (162_0000_0000, None)),
+        ],
+        0,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_nft_with_data_altered",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_nft_with_type_swapped() {
+    let (mut context, tx) = build_test_context_with_nfts(
+        Some(FLAG_NFT),
+        vec![(162_0000_0000, Some((1, &b"mNFT #0001"[..])))],
+        Party::Receiver,
+        200_0000_0000,
+        vec![
+            (Party::Receiver, (200_0000_0000, Some((2, &b"mNFT #0001"[..])))),
+            (Party::Sender, (162_0000_0000, None)),
+        ],
+        0,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_nft_with_type_swapped",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_nft_with_sender_input() {
+    let (mut context, tx) = build_test_context_with_nfts(
+        Some(FLAG_NFT),
+        vec![(162_0000_0000, Some((1, &b"mNFT #0001"[..])))],
+        Party::Sender,
+        200_0000_0000,
+        vec![
+            (Party::Sender, (162_0000_0000, Some((1, &b"mNFT #0001"[..])))),
+            (Party::Sender, (200_0000_0000, None)),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_nft_with_sender_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
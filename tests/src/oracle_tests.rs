@@ -0,0 +1,189 @@
+use super::helper::{
+    build_cell, placeholder_signature, write_native_setup, Party, TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::FLAG_ORACLE;
+use cheque_core::error::Error;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{bytes::Bytes, core::TransactionView, prelude::*};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const ORACLE_MILESTONE_NOT_REACHED: i8 = Error::OracleMilestoneNotReached as i8;
+
+const MILESTONE: u8 = 3;
+
+fn build_test_context_with_oracle(
+    oracle_data: Option<Bytes>,
+    is_claim: bool,
+) -> (Context, TransactionView) {
+    let mut builder = TestTxBuilder::default();
+    let oracle_type_script = builder.always_success_script(Bytes::from(vec![9u8]));
+    let mut oracle_fields = oracle_type_script.calc_script_hash().as_bytes().to_vec();
+    oracle_fields.push(MILESTONE);
+    let cheque_args = builder.cheque_args(
+        Party::Receiver,
+        Party::Sender,
+        Some(FLAG_ORACLE),
+        &oracle_fields,
+    );
+    let cheque_script = builder.cheque_script(&cheque_args);
+    let sender_lock_script = builder.lock_script(Party::Sender);
+    let party_lock_script = if is_claim {
+        builder.lock_script(Party::Receiver)
+    } else {
+        sender_lock_script.clone()
+    };
+
+    // prepare inputs
+    let cheque_since = if is_claim { 0 } else { CHEQUE_CELL_SINCE };
+    builder.input(
+        build_cell(&cheque_script, 162_0000_0000, None),
+        Bytes::new(),
+        cheque_since,
+        Bytes::new(),
+    );
+    builder.input(
+        build_cell(&party_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+        0,
+        placeholder_signature(),
+    );
+
+    // prepare outputs
+    builder.output(
+        build_cell(&party_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+    );
+    builder.output(
+        build_cell(&sender_lock_script, 162_0000_0000, None),
+        Bytes::new(),
+    );
+
+    // deploy oracle cell
+    if let Some(data) = oracle_data {
+        let oracle_out_point = builder.context.create_cell(
+            build_cell(&sender_lock_script, 100_0000_0000, Some(oracle_type_script)),
+            data,
+        );
+        builder.cell_dep(oracle_out_point);
+    }
+
+    builder.build()
+}
+
+#[test]
+fn test_claim_with_oracle_milestone_reached() {
+    let (mut context, tx) =
+        build_test_context_with_oracle(Some(Bytes::from(vec![0b0000_1000u8])), true);
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_oracle_milestone_reached",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_oracle_milestone_not_reached() {
+    let (mut context, tx) =
+        build_test_context_with_oracle(Some(Bytes::from(vec![0b0000_0111u8])), true);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(ORACLE_MILESTONE_NOT_REACHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_oracle_milestone_not_reached",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_without_oracle_cell_dep() {
+    let (mut context, tx) = build_test_context_with_oracle(None, true);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(ORACLE_MILESTONE_NOT_REACHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_without_oracle_cell_dep",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_without_oracle_cell_dep() {
+    let (mut context, tx) = build_test_context_with_oracle(None, false);
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_without_oracle_cell_dep",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
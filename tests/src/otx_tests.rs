@@ -0,0 +1,553 @@
+use super::{
+    helper::{blake160, write_native_setup, MAX_CYCLES},
+    *,
+};
+use cheque_core::args::FLAG_CAPACITY;
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE as TYPE};
+use cheque_core::error::Error;
+use ckb_system_scripts::BUNDLED_CELL;
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+use ckb_tool::ckb_crypto::secp::{Generator, Privkey};
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_hash::new_blake2b;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+    H256,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const SENDER_CAPACITY_NOT_SAME: i8 = Error::SenderCapacityNotSame as i8;
+const NO_MATCHED_SIGNATURE: i8 = Error::NoMatchedSignature as i8;
+const CHEQUE_ASSET_NOT_MATCHED: i8 = Error::ChequeAssetNotMatched as i8;
+
+const SIGHASH_OTX: u8 = 1;
+
+// The OTX signing message of the receiver, see otx.rs of the cheque script
+fn otx_signing_message(tx: &TransactionView, cheque_script: &Script) -> [u8; 32] {
+    let cheque_lock_hash = cheque_script.calc_script_hash();
+    let cheque_args: Bytes = cheque_script.args().unpack();
+    let bound_lock_hashes = [&cheque_args[0..20]];
+
+    let mut blake2b = new_blake2b();
+    blake2b.update(cheque_lock_hash.as_slice());
+
+    // the cheque cell is always the first input in the tests
+    let cheque_inputs = vec![tx.inputs().get(0).unwrap()];
+    blake2b.update(&(cheque_inputs.len() as u64).to_le_bytes());
+    for input in cheque_inputs.iter() {
+        blake2b.update(input.previous_output().as_slice());
+        blake2b.update(input.since().as_slice());
+    }
+
+    let bound_outputs = tx
+        .outputs_with_data_iter()
+        .filter(|(output, _)| {
+            let lock = output.lock();
+            bound_lock_hashes.contains(&&lock.calc_script_hash().as_slice()[0..20])
+                || (lock.code_hash().as_slice() == cheque_script.code_hash().as_slice()
+                    && lock.hash_type().as_slice() == cheque_script.hash_type().as_slice())
+        })
+        .collect::<Vec<_>>();
+    blake2b.update(&(bound_outputs.len() as u64).to_le_bytes());
+    for (output, data) in bound_outputs.iter() {
+        blake2b.update(output.as_slice());
+        blake2b.update(&(data.len() as u64).to_le_bytes());
+        blake2b.update(data);
+    }
+
+    // the cheque witness has no input_type
+    blake2b.update(&0u64.to_le_bytes());
+
+    let mut message = [0u8; 32];
+    blake2b.finalize(&mut message);
+    message
+}
+
+fn sign_otx(tx: TransactionView, cheque_script: &Script, key: &Privkey) -> TransactionView {
+    let message = H256::from(otx_signing_message(&tx, cheque_script));
+    let signature = key.sign_recoverable(&message).expect("sign");
+    let mut witness_lock = vec![SIGHASH_OTX];
+    witness_lock.extend_from_slice(&signature.serialize());
+    let witness = WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(witness_lock)).pack())
+        .build();
+
+    let mut witnesses: Vec<Bytes> = tx.witnesses().into_iter().map(|w| w.unpack()).collect();
+    witnesses[0] = witness.as_bytes();
+    tx.as_advanced_builder()
+        .set_witnesses(witnesses.into_iter().map(|w| w.pack()).collect())
+        .build()
+}
+
+// Another party merges an unrelated input and output into the signed transaction
+fn merge_unrelated_cell(context: &mut Context, tx: TransactionView) -> TransactionView {
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let other_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![2u8]))
+        .expect("script");
+    let other_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(300_0000_0000).pack())
+            .lock(other_lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let tx = tx
+        .as_advanced_builder()
+        .input(
+            CellInput::new_builder()
+                .previous_output(other_out_point)
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(Capacity::shannons(300_0000_0000).pack())
+                .lock(other_lock_script)
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .witness(Bytes::new().pack())
+        .build();
+    context.complete_tx(tx)
+}
+
+// Another party merges two signed transactions into one transaction
+fn merge_otx(
+    context: &mut Context,
+    tx: TransactionView,
+    other_tx: TransactionView,
+) -> TransactionView {
+    let tx = tx
+        .as_advanced_builder()
+        .inputs(other_tx.inputs())
+        .outputs(other_tx.outputs())
+        .outputs_data(other_tx.outputs_data())
+        .witnesses(other_tx.witnesses())
+        .build();
+    context.complete_tx(tx)
+}
+
+fn build_test_context_with_otx(sender_output_capacity: u64) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    let tx = build_otx_claim(&mut context, sender_output_capacity);
+    (context, tx)
+}
+
+// The receiver of a new key pair claims a cheque cell of the same sender with an OTX signature
+fn build_otx_claim(context: &mut Context, sender_output_capacity: u64) -> TransactionView {
+    // generate key pair
+    let private_key = Generator::random_privkey();
+    let public_key = private_key.pubkey().expect("pubkey");
+    let receiver_lock_args = blake160(&public_key.serialize());
+
+    // deploy cheque script
+    let cheque_bin: Bytes = Loader::default().load_binary("ckb-cheque-script");
+    let cheque_out_point = context.deploy_cell(cheque_bin);
+
+    // deploy always_success script
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let secp256k1_data_bin = BUNDLED_CELL.get("specs/cells/secp256k1_data").unwrap();
+    let secp256k1_data_out_point = context.deploy_cell(secp256k1_data_bin.to_vec().into());
+    let secp256k1_data_dep = CellDep::new_builder()
+        .out_point(secp256k1_data_out_point)
+        .build();
+
+    // build lock script
+    let receiver_secp256k1_lock_script = Script::new_builder()
+        .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
+        .args(Bytes::copy_from_slice(&receiver_lock_args).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+    let receiver_secp256k1_lock_hash = receiver_secp256k1_lock_script.calc_script_hash();
+    let sender_always_success_lock_script = context
+        .build_script(
+            &always_success_out_point,
+            Bytes::from(hex::decode("36c329ed630d6ce750712a477543672adab57f4c").unwrap()),
+        )
+        .expect("script");
+    let sender_always_success_lock_hash = sender_always_success_lock_script.calc_script_hash();
+    let fee_always_success_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1u8]))
+        .expect("script");
+    let always_success_lock_script_dep = CellDep::new_builder()
+        .out_point(always_success_out_point)
+        .build();
+
+    let mut cheque_lock_args = receiver_secp256k1_lock_hash
+        .as_bytes()
+        .slice(0..20)
+        .to_vec();
+    cheque_lock_args.extend_from_slice(&sender_always_success_lock_hash.as_bytes().slice(0..20));
+    let cheque_script = context
+        .build_script(&cheque_out_point, Bytes::copy_from_slice(&cheque_lock_args))
+        .expect("script");
+
+    // prepare inputs
+    let cheque_cell_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(162_0000_0000).pack())
+            .lock(cheque_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let fee_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(200_0000_0000).pack())
+            .lock(fee_always_success_lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(cheque_cell_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(fee_out_point)
+            .build(),
+    ];
+
+    // prepare outputs
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(sender_output_capacity).pack())
+            .lock(sender_always_success_lock_script)
+            .build(),
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(100_0000_0000).pack())
+            .lock(receiver_secp256k1_lock_script)
+            .build(),
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(100_0000_0000).pack())
+            .lock(fee_always_success_lock_script)
+            .build(),
+    ];
+    let outputs_data = vec![Bytes::new(), Bytes::new(), Bytes::new()];
+
+    let witnesses = vec![Bytes::new(), Bytes::new()];
+
+    let cheque_script_dep = CellDep::new_builder().out_point(cheque_out_point).build();
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(cheque_script_dep)
+        .cell_dep(secp256k1_data_dep)
+        .cell_dep(always_success_lock_script_dep)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+    sign_otx(tx, &cheque_script, &private_key)
+}
+
+// The receiver of the key pair claims the capacity of a cheque cell of the sender with an OTX
+// signature, and the receiver output holds exactly the capacity of the cheque cell
+fn build_otx_capacity_claim(
+    context: &mut Context,
+    private_key: &Privkey,
+    sender_lock_args: Bytes,
+) -> TransactionView {
+    let public_key = private_key.pubkey().expect("pubkey");
+    let receiver_lock_args = blake160(&public_key.serialize());
+
+    // deploy cheque script
+    let cheque_bin: Bytes = Loader::default().load_binary("ckb-cheque-script");
+    let cheque_out_point = context.deploy_cell(cheque_bin);
+
+    // deploy always_success script
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let secp256k1_data_bin = BUNDLED_CELL.get("specs/cells/secp256k1_data").unwrap();
+    let secp256k1_data_out_point = context.deploy_cell(secp256k1_data_bin.to_vec().into());
+    let secp256k1_data_dep = CellDep::new_builder()
+        .out_point(secp256k1_data_out_point)
+        .build();
+
+    // build lock script
+    let receiver_secp256k1_lock_script = Script::new_builder()
+        .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
+        .args(Bytes::copy_from_slice(&receiver_lock_args).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+    let receiver_secp256k1_lock_hash = receiver_secp256k1_lock_script.calc_script_hash();
+    let sender_always_success_lock_script = context
+        .build_script(&always_success_out_point, sender_lock_args)
+        .expect("script");
+    let sender_always_success_lock_hash = sender_always_success_lock_script.calc_script_hash();
+    let fee_always_success_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1u8]))
+        .expect("script");
+    let always_success_lock_script_dep = CellDep::new_builder()
+        .out_point(always_success_out_point)
+        .build();
+
+    let mut cheque_lock_args = receiver_secp256k1_lock_hash
+        .as_bytes()
+        .slice(0..20)
+        .to_vec();
+    cheque_lock_args.extend_from_slice(&sender_always_success_lock_hash.as_bytes().slice(0..20));
+    cheque_lock_args.extend_from_slice(&FLAG_CAPACITY.to_le_bytes());
+    let cheque_script = context
+        .build_script(&cheque_out_point, Bytes::copy_from_slice(&cheque_lock_args))
+        .expect("script");
+
+    // prepare inputs
+    let cheque_cell_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(162_0000_0000).pack())
+            .lock(cheque_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let fee_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(200_0000_0000).pack())
+            .lock(fee_always_success_lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(cheque_cell_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(fee_out_point)
+            .build(),
+    ];
+
+    // prepare outputs
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(162_0000_0000).pack())
+            .lock(receiver_secp256k1_lock_script)
+            .build(),
+        CellOutput::new_builder()
+            .capacity(Capacity::shannons(100_0000_0000).pack())
+            .lock(fee_always_success_lock_script)
+            .build(),
+    ];
+    let outputs_data = vec![Bytes::new(), Bytes::new()];
+
+    let witnesses = vec![Bytes::new(), Bytes::new()];
+
+    let cheque_script_dep = CellDep::new_builder().out_point(cheque_out_point).build();
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(cheque_script_dep)
+        .cell_dep(secp256k1_data_dep)
+        .cell_dep(always_success_lock_script_dep)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+    sign_otx(tx, &cheque_script, private_key)
+}
+
+#[test]
+fn test_claim_with_receiver_otx_signature() {
+    let (context, tx) = build_test_context_with_otx(162_0000_0000);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_receiver_otx_signature",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_claim_with_receiver_otx_signature_merged() {
+    let (mut context, tx) = build_test_context_with_otx(162_0000_0000);
+    let tx = merge_unrelated_cell(&mut context, tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_receiver_otx_signature_merged",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_receiver_otx_signature_bound_output_changed() {
+    let (context, tx) = build_test_context_with_otx(162_0000_0000);
+
+    // the receiver output is bound to the signature of the receiver, so changing it invalidates
+    // the OTX signature
+    let outputs: Vec<CellOutput> = tx
+        .outputs()
+        .into_iter()
+        .enumerate()
+        .map(|(index, output)| match index {
+            1 => output
+                .as_builder()
+                .capacity(Capacity::shannons(99_0000_0000).pack())
+                .build(),
+            _ => output,
+        })
+        .collect();
+    let tx = tx.as_advanced_builder().set_outputs(outputs).build();
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(NO_MATCHED_SIGNATURE).input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_receiver_otx_signature_bound_output_changed",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_receiver_otx_signature_capacity() {
+    let (context, tx) = build_test_context_with_otx(152_0000_0000);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(SENDER_CAPACITY_NOT_SAME)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_receiver_otx_signature_capacity",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_claim_with_receiver_otx_signatures_of_same_sender_merged() {
+    // two receivers claim the cheque cells of the same sender, and each signature only binds the
+    // outputs of its own receiver
+    let mut context = Context::default();
+    let tx = build_otx_claim(&mut context, 162_0000_0000);
+    let other_tx = build_otx_claim(&mut context, 162_0000_0000);
+    let tx = merge_otx(&mut context, tx, other_tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_receiver_otx_signatures_of_same_sender_merged",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_receiver_otx_signatures_sharing_receiver_output() {
+    // the receiver claims the capacity of the cheque cells of two senders with OTX signatures, and
+    // the merged transaction keeps only the receiver output of the first claim, which is the same
+    // output bound to both signatures
+    let mut context = Context::default();
+    let private_key = Generator::random_privkey();
+    let tx = build_otx_capacity_claim(
+        &mut context,
+        &private_key,
+        Bytes::from(hex::decode("36c329ed630d6ce750712a477543672adab57f4c").unwrap()),
+    );
+    let other_tx = build_otx_capacity_claim(&mut context, &private_key, Bytes::from(vec![3u8]));
+    let other_tx = other_tx
+        .as_advanced_builder()
+        .set_outputs(other_tx.outputs().into_iter().skip(1).collect())
+        .set_outputs_data(other_tx.outputs_data().into_iter().skip(1).collect())
+        .build();
+    let tx = merge_otx(&mut context, tx, other_tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_receiver_otx_signatures_sharing_receiver_output",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
@@ -0,0 +1,236 @@
+use super::helper::{
+    blake160, build_cell, placeholder_signature, sign_tx_by_keys, write_native_setup, Party,
+    TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::FLAG_RECEIVER_GROUP;
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE as TYPE};
+use cheque_core::error::Error;
+use ckb_system_scripts::BUNDLED_CELL;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_crypto::secp::{Generator, Privkey};
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{Byte, Script},
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const NO_MATCHED_INPUTS: i8 = Error::NoMatchedInputs as i8;
+const NO_MATCHED_SIGNATURE: i8 = Error::NoMatchedSignature as i8;
+
+const RECEIVER_GROUP_LEN: usize = 3;
+const THRESHOLD: u8 = 2;
+
+enum Authorization {
+    // the indexes of the receivers of the group which provide inputs
+    Inputs(Vec<usize>),
+    // the indexes of the receivers of the group which sign the cheque cell
+    Signatures(Vec<usize>),
+}
+
+fn build_test_context_with_receiver_group(
+    authorization: Authorization,
+) -> (Context, TransactionView) {
+    // generate key pairs of the receivers
+    let private_keys: Vec<Privkey> = (0..RECEIVER_GROUP_LEN)
+        .map(|_| Generator::random_privkey())
+        .collect();
+
+    let mut builder = TestTxBuilder::default();
+    let secp256k1_data_bin = BUNDLED_CELL.get("specs/cells/secp256k1_data").unwrap();
+    let secp256k1_data_out_point = builder
+        .context
+        .deploy_cell(secp256k1_data_bin.to_vec().into());
+    builder.cell_dep(secp256k1_data_out_point);
+
+    // build lock script
+    let receiver_lock_scripts: Vec<Script> = private_keys
+        .iter()
+        .map(|private_key| {
+            let public_key = private_key.pubkey().expect("pubkey");
+            let receiver_lock_args = Bytes::copy_from_slice(&blake160(&public_key.serialize()));
+            match &authorization {
+                Authorization::Inputs(_) => builder.always_success_script(receiver_lock_args),
+                Authorization::Signatures(_) => Script::new_builder()
+                    .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
+                    .args(receiver_lock_args.pack())
+                    .hash_type(Byte::new(TYPE))
+                    .build(),
+            }
+        })
+        .collect();
+    let sender_lock_script = builder.lock_script(Party::Sender);
+
+    // the receiver lock hash of the lock args commits to the receiver group
+    let mut receiver_group = vec![THRESHOLD, RECEIVER_GROUP_LEN as u8];
+    for receiver_lock_script in receiver_lock_scripts.iter() {
+        let receiver_lock_hash = receiver_lock_script.calc_script_hash();
+        receiver_group.extend_from_slice(&receiver_lock_hash.as_bytes()[0..20]);
+    }
+    let mut cheque_args = builder.cheque_args(
+        Party::Receiver,
+        Party::Sender,
+        Some(FLAG_RECEIVER_GROUP),
+        &receiver_group,
+    );
+    cheque_args[0..20].copy_from_slice(&blake160(&receiver_group));
+    let cheque_script = builder.cheque_script(&cheque_args);
+
+    // prepare inputs
+    builder.input(
+        build_cell(&cheque_script, 162_0000_0000, None),
+        Bytes::new(),
+        0,
+        Bytes::new(),
+    );
+    let input_lock_scripts = match &authorization {
+        Authorization::Inputs(indexes) => indexes
+            .iter()
+            .map(|index| receiver_lock_scripts[*index].clone())
+            .collect(),
+        Authorization::Signatures(_) => vec![builder.always_success_script(Bytes::from(vec![1u8]))],
+    };
+    for input_lock_script in input_lock_scripts.iter() {
+        builder.input(
+            build_cell(input_lock_script, 200_0000_0000, None),
+            Bytes::new(),
+            0,
+            placeholder_signature(),
+        );
+    }
+
+    // prepare outputs
+    builder.output(
+        build_cell(&sender_lock_script, 162_0000_0000, None),
+        Bytes::new(),
+    );
+    for input_lock_script in input_lock_scripts.iter() {
+        builder.output(
+            build_cell(input_lock_script, 200_0000_0000, None),
+            Bytes::new(),
+        );
+    }
+
+    let (mut context, tx) = builder.build();
+    let tx = context.complete_tx(tx);
+    let tx = match &authorization {
+        Authorization::Inputs(_) => tx,
+        Authorization::Signatures(indexes) => {
+            let keys: Vec<&Privkey> = indexes.iter().map(|index| &private_keys[*index]).collect();
+            sign_tx_by_keys(tx, &keys)
+        }
+    };
+    (context, tx)
+}
+
+#[test]
+fn test_claim_with_threshold_receiver_inputs() {
+    let (context, tx) = build_test_context_with_receiver_group(Authorization::Inputs(vec![0, 2]));
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_threshold_receiver_inputs",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_receiver_inputs_below_threshold() {
+    let (context, tx) = build_test_context_with_receiver_group(Authorization::Inputs(vec![1]));
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(NO_MATCHED_INPUTS).input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_receiver_inputs_below_threshold",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_claim_with_threshold_receiver_signatures() {
+    let (context, tx) =
+        build_test_context_with_receiver_group(Authorization::Signatures(vec![1, 2]));
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_threshold_receiver_signatures",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_duplicate_receiver_signatures() {
+    let (context, tx) =
+        build_test_context_with_receiver_group(Authorization::Signatures(vec![0, 0]));
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(NO_MATCHED_SIGNATURE).input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_duplicate_receiver_signatures",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
@@ -0,0 +1,383 @@
+use super::helper::{
+    build_cell, placeholder_signature, write_native_setup, Party, TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::FLAG_REFUND;
+use cheque_core::error::Error;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{CellOutput, Script},
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const SENDER_CAPACITY_NOT_SAME: i8 = Error::SenderCapacityNotSame as i8;
+const CHEQUE_ASSET_NOT_MATCHED: i8 = Error::ChequeAssetNotMatched as i8;
+
+// (capacity, Option<udt amount>)
+type Asset = (u64, Option<u128>);
+
+fn build_test_context_with_refund(
+    cheque_asset: Asset,
+    party: Party,
+    outputs: Vec<(Party, Asset)>,
+    since: u64,
+) -> (Context, TransactionView) {
+    build_test_context_with_refund_cheques(
+        vec![Party::Sender],
+        cheque_asset,
+        vec![party],
+        outputs,
+        since,
+    )
+}
+
+// Every sender holds a cheque cell of the same asset refunded to the same lock in its own group
+fn build_test_context_with_refund_cheques(
+    senders: Vec<Party>,
+    cheque_asset: Asset,
+    parties: Vec<Party>,
+    outputs: Vec<(Party, Asset)>,
+    since: u64,
+) -> (Context, TransactionView) {
+    let mut builder = TestTxBuilder::default();
+    let refund_lock_hash = builder.lock_hash(Party::Refund);
+    let udt_type_script = builder.udt_type_script(1);
+    let build_asset_cell = |lock_script: &Script, asset: Asset| -> (CellOutput, Bytes) {
+        let (capacity, udt_amount) = asset;
+        match udt_amount {
+            Some(amount) => (
+                build_cell(lock_script, capacity, Some(udt_type_script.clone())),
+                Bytes::from(amount.to_le_bytes().to_vec()),
+            ),
+            None => (build_cell(lock_script, capacity, None), Bytes::new()),
+        }
+    };
+
+    // prepare inputs
+    for sender in senders.into_iter() {
+        let cheque_args = builder.cheque_args(
+            Party::Receiver,
+            sender,
+            Some(FLAG_REFUND),
+            &refund_lock_hash.as_slice()[0..20],
+        );
+        let cheque_script = builder.cheque_script(&cheque_args);
+        let (cheque_cell, cheque_cell_data) = build_asset_cell(&cheque_script, cheque_asset);
+        builder.input(cheque_cell, cheque_cell_data, since, Bytes::new());
+    }
+    for party in parties.into_iter() {
+        let party_lock_script = builder.lock_script(party);
+        builder.input(
+            build_cell(&party_lock_script, 200_0000_0000, None),
+            Bytes::new(),
+            0,
+            placeholder_signature(),
+        );
+    }
+
+    // prepare outputs
+    for (output_party, asset) in outputs.into_iter() {
+        let lock_script = builder.lock_script(output_party);
+        let (output, data) = build_asset_cell(&lock_script, asset);
+        builder.output(output, data);
+    }
+
+    builder.build()
+}
+
+#[test]
+fn test_claim_with_capacity_to_refund_lock() {
+    let (mut context, tx) = build_test_context_with_refund(
+        (162_0000_0000, None),
+        Party::Receiver,
+        vec![
+            (Party::Receiver, (200_0000_0000, None)),
+            (Party::Refund, (162_0000_0000, None)),
+        ],
+        0,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_with_capacity_to_refund_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_with_capacity_to_sender_lock() {
+    let (mut context, tx) = build_test_context_with_refund(
+        (162_0000_0000, None),
+        Party::Receiver,
+        vec![
+            (Party::Receiver, (200_0000_0000, None)),
+            (Party::Sender, (162_0000_0000, None)),
+        ],
+        0,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(SENDER_CAPACITY_NOT_SAME)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_with_capacity_to_sender_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_with_udt_to_refund_lock() {
+    let (mut context, tx) = build_test_context_with_refund(
+        (162_0000_0000, Some(1000)),
+        Party::Sender,
+        vec![
+            (Party::Refund, (162_0000_0000, Some(1000))),
+            (Party::Sender, (200_0000_0000, None)),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_with_udt_to_refund_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_withdraw_with_udt_to_sender_lock() {
+    let (mut context, tx) = build_test_context_with_refund(
+        (162_0000_0000, Some(1000)),
+        Party::Sender,
+        vec![
+            (Party::Sender, (162_0000_0000, Some(1000))),
+            (Party::Sender, (200_0000_0000, None)),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_withdraw_with_udt_to_sender_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_with_capacity_to_refund_lock() {
+    let (mut context, tx) = build_test_context_with_refund(
+        (162_0000_0000, None),
+        Party::Sender,
+        vec![
+            (Party::Refund, (162_0000_0000, None)),
+            (Party::Sender, (200_0000_0000, None)),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_with_capacity_to_refund_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_withdraw_with_capacity_to_sender_lock() {
+    // the sender keeps the capacity which belongs to the refund lock
+    let (mut context, tx) = build_test_context_with_refund(
+        (162_0000_0000, None),
+        Party::Sender,
+        vec![(Party::Sender, (362_0000_0000, None))],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_withdraw_with_capacity_to_sender_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_cheques_of_two_senders_to_refund_lock() {
+    let (mut context, tx) = build_test_context_with_refund_cheques(
+        vec![Party::Sender, Party::Other],
+        (162_0000_0000, None),
+        vec![Party::Sender, Party::Other],
+        vec![
+            (Party::Refund, (324_0000_0000, None)),
+            (Party::Sender, (200_0000_0000, None)),
+            (Party::Other, (200_0000_0000, None)),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_cheques_of_two_senders_to_refund_lock",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_withdraw_cheques_of_two_senders_with_one_refund_output() {
+    // each cheque cell alone is paid by the refund output, but both are not
+    let (mut context, tx) = build_test_context_with_refund_cheques(
+        vec![Party::Sender, Party::Other],
+        (162_0000_0000, None),
+        vec![Party::Sender, Party::Other],
+        vec![
+            (Party::Refund, (162_0000_0000, None)),
+            (Party::Sender, (362_0000_0000, None)),
+            (Party::Other, (200_0000_0000, None)),
+        ],
+        CHEQUE_CELL_SINCE,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(CHEQUE_ASSET_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_withdraw_cheques_of_two_senders_with_one_refund_output",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
@@ -0,0 +1,189 @@
+use super::helper::{
+    blake160, build_cell, placeholder_signature, write_native_setup, Party, TestTxBuilder,
+    MAX_CYCLES,
+};
+use cheque_core::args::FLAG_SALTED;
+use cheque_core::error::Error;
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{bytes::Bytes, core::TransactionView, packed::WitnessArgs, prelude::*};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const NO_MATCHED_INPUTS: i8 = Error::NoMatchedInputs as i8;
+
+const CLAIM_ACTION_CLAIM: u8 = 0;
+
+const SALT: [u8; 32] = [7u8; 32];
+
+fn build_test_context_with_salt(
+    revealed_salt: Option<[u8; 32]>,
+    is_claim: bool,
+) -> (Context, TransactionView) {
+    let mut builder = TestTxBuilder::default();
+    let receiver_lock_hash = builder.lock_hash(Party::Receiver);
+
+    // the receiver lock hash of the lock args is the salted commitment
+    let mut commitment_preimage = receiver_lock_hash.as_bytes().slice(0..20).to_vec();
+    commitment_preimage.extend_from_slice(&SALT);
+    let mut cheque_args =
+        builder.cheque_args(Party::Receiver, Party::Sender, Some(FLAG_SALTED), &[]);
+    cheque_args[0..20].copy_from_slice(&blake160(&commitment_preimage));
+    let cheque_script = builder.cheque_script(&cheque_args);
+    let sender_lock_script = builder.lock_script(Party::Sender);
+    let party_lock_script = if is_claim {
+        builder.lock_script(Party::Receiver)
+    } else {
+        sender_lock_script.clone()
+    };
+
+    // prepare inputs, where the receiver reveals the salt after the claim action
+    let cheque_witness = match revealed_salt {
+        Some(salt) => {
+            let mut input_type = vec![CLAIM_ACTION_CLAIM];
+            input_type.extend_from_slice(&salt);
+            WitnessArgs::new_builder()
+                .input_type(Some(Bytes::from(input_type)).pack())
+                .build()
+                .as_bytes()
+        }
+        None => Bytes::new(),
+    };
+    let cheque_since = if is_claim { 0 } else { CHEQUE_CELL_SINCE };
+    builder.input(
+        build_cell(&cheque_script, 162_0000_0000, None),
+        Bytes::new(),
+        cheque_since,
+        cheque_witness,
+    );
+    builder.input(
+        build_cell(&party_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+        0,
+        placeholder_signature(),
+    );
+
+    // prepare outputs
+    builder.output(
+        build_cell(&party_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+    );
+    builder.output(
+        build_cell(&sender_lock_script, 162_0000_0000, None),
+        Bytes::new(),
+    );
+
+    builder.build()
+}
+
+#[test]
+fn test_claim_salted_cheque_with_receiver_input() {
+    let (mut context, tx) = build_test_context_with_salt(Some(SALT), true);
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_salted_cheque_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_salted_cheque_with_wrong_salt() {
+    let (mut context, tx) = build_test_context_with_salt(Some([8u8; 32]), true);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(NO_MATCHED_INPUTS).input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_salted_cheque_with_wrong_salt",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_salted_cheque_without_salt() {
+    let (mut context, tx) = build_test_context_with_salt(None, true);
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(NO_MATCHED_INPUTS).input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_salted_cheque_without_salt",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_salted_cheque_with_sender_input() {
+    let (mut context, tx) = build_test_context_with_salt(None, false);
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_salted_cheque_with_sender_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
@@ -0,0 +1,474 @@
+use super::{
+    helper::{blake160, sign_tx, write_native_setup, MAX_CYCLES},
+    *,
+};
+use cheque_core::args::{ChequeArgs, FLAG_CAPACITY, FLAG_REFUND, FLAG_SALTED};
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE as TYPE};
+use cheque_core::since::CHEQUE_CELL_SINCE;
+use cheque_sdk::args::lock_hash_160;
+use cheque_sdk::{
+    ChequeArgsExt, ChequeScript, ClaimBuilder, ClaimMode, CreateChequeBuilder, Error, LiveCell,
+    WithdrawBuilder, WithdrawMode,
+};
+use ckb_system_scripts::BUNDLED_CELL;
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+use ckb_tool::ckb_crypto::secp::Generator;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, ScriptHashType, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const FEE: u64 = 1_0000_0000;
+
+struct SdkContext {
+    context:                 Context,
+    cheque_script:           ChequeScript,
+    receiver_lock_script:    Script,
+    sender_lock_script:      Script,
+    udt_type_script:         Script,
+    always_success_lock_dep: CellDep,
+}
+
+fn build_test_context_with_sdk() -> SdkContext {
+    // deploy cheque script
+    let mut context = Context::default();
+    let cheque_bin: Bytes = Loader::default().load_binary("ckb-cheque-script");
+    let cheque_out_point = context.deploy_cell(cheque_bin);
+    let cheque_code_hash = context
+        .build_script(&cheque_out_point, Bytes::new())
+        .expect("script")
+        .code_hash();
+
+    let secp256k1_data_bin = BUNDLED_CELL.get("specs/cells/secp256k1_data").unwrap();
+    let secp256k1_data_out_point = context.deploy_cell(secp256k1_data_bin.to_vec().into());
+
+    // the context deploys the scripts by data hash
+    let cheque_script = ChequeScript {
+        code_hash:          cheque_code_hash,
+        hash_type:          ScriptHashType::Data,
+        cell_dep:           CellDep::new_builder().out_point(cheque_out_point).build(),
+        secp256k1_data_dep: CellDep::new_builder()
+            .out_point(secp256k1_data_out_point)
+            .build(),
+    };
+
+    // deploy always_success script
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    // build lock and type scripts
+    let receiver_lock_script = context
+        .build_script(
+            &always_success_out_point,
+            Bytes::from(hex::decode("f43cc005be4edf45c829363d54799ac4f7aff5a5").unwrap()),
+        )
+        .expect("script");
+    let sender_lock_script = context
+        .build_script(
+            &always_success_out_point,
+            Bytes::from(hex::decode("36c329ed630d6ce750712a477543672adab57f4c").unwrap()),
+        )
+        .expect("script");
+    let udt_type_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![3u8]))
+        .expect("script");
+    let always_success_lock_dep = CellDep::new_builder()
+        .out_point(always_success_out_point)
+        .build();
+
+    SdkContext {
+        context,
+        cheque_script,
+        receiver_lock_script,
+        sender_lock_script,
+        udt_type_script,
+        always_success_lock_dep,
+    }
+}
+
+fn build_cheque_args(
+    receiver_lock_script: &Script,
+    sender_lock_script: &Script,
+    flags: u16,
+) -> ChequeArgs {
+    let mut cheque_args = ChequeArgs::from_lock_scripts(receiver_lock_script, sender_lock_script);
+    cheque_args.flags = flags;
+    cheque_args
+}
+
+fn create_live_cell(
+    context: &mut Context,
+    lock_script: Script,
+    type_script: Option<Script>,
+    capacity: u64,
+    data: Bytes,
+) -> LiveCell {
+    let output = CellOutput::new_builder()
+        .capacity(Capacity::shannons(capacity).pack())
+        .lock(lock_script)
+        .type_(type_script.pack())
+        .build();
+    let out_point = context.create_cell(output.clone(), data.clone());
+    LiveCell::new(out_point, output, data)
+}
+
+fn output_capacities(tx: &TransactionView) -> Vec<u64> {
+    tx.outputs()
+        .into_iter()
+        .map(|output| output.capacity().unpack())
+        .collect()
+}
+
+fn write_sdk_native_setup(name: &str, tx: &TransactionView, context: &Context) {
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(name, "ckb-cheque-script-sim", tx, context, &setup);
+}
+
+#[test]
+fn test_create_cheque_with_sdk() {
+    let SdkContext {
+        mut context,
+        cheque_script,
+        receiver_lock_script,
+        sender_lock_script,
+        always_success_lock_dep,
+        ..
+    } = build_test_context_with_sdk();
+    let cheque_args = build_cheque_args(&receiver_lock_script, &sender_lock_script, FLAG_CAPACITY);
+    let sender_cell = create_live_cell(
+        &mut context,
+        sender_lock_script.clone(),
+        None,
+        500_0000_0000,
+        Bytes::new(),
+    );
+
+    let tx = CreateChequeBuilder::new(
+        cheque_script.clone(),
+        cheque_args.clone(),
+        sender_lock_script.clone(),
+    )
+    .capacity(200_0000_0000)
+    .input(sender_cell)
+    .cell_dep(always_success_lock_dep)
+    .fee(FEE)
+    .build()
+    .expect("build");
+
+    assert_eq!(
+        tx.output(0).unwrap().lock().as_slice(),
+        cheque_script.lock_script(&cheque_args).as_slice()
+    );
+    assert_eq!(
+        tx.output(1).unwrap().lock().as_slice(),
+        sender_lock_script.as_slice()
+    );
+    assert_eq!(output_capacities(&tx), vec![200_0000_0000, 299_0000_0000]);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_claim_udt_with_receiver_lock_input_with_sdk() {
+    let SdkContext {
+        mut context,
+        cheque_script,
+        receiver_lock_script,
+        sender_lock_script,
+        udt_type_script,
+        always_success_lock_dep,
+    } = build_test_context_with_sdk();
+    let cheque_args = build_cheque_args(&receiver_lock_script, &sender_lock_script, 0);
+    let cheque_cell = create_live_cell(
+        &mut context,
+        cheque_script.lock_script(&cheque_args),
+        Some(udt_type_script.clone()),
+        200_0000_0000,
+        Bytes::from(1000u128.to_le_bytes().to_vec()),
+    );
+    let receiver_cell = create_live_cell(
+        &mut context,
+        receiver_lock_script.clone(),
+        None,
+        200_0000_0000,
+        Bytes::new(),
+    );
+
+    let tx = ClaimBuilder::new(
+        cheque_script,
+        cheque_args,
+        receiver_lock_script.clone(),
+        sender_lock_script.clone(),
+        ClaimMode::LockInput(receiver_cell),
+    )
+    .cheque_cell(cheque_cell)
+    .cell_dep(always_success_lock_dep)
+    .fee(FEE)
+    .build()
+    .expect("build");
+
+    let receiver_udt_output = tx.output(0).unwrap();
+    let receiver_udt_capacity: u64 = receiver_udt_output.capacity().unpack();
+    assert_eq!(
+        receiver_udt_output.lock().as_slice(),
+        receiver_lock_script.as_slice()
+    );
+    assert_eq!(
+        receiver_udt_output.type_().as_slice(),
+        Some(udt_type_script).pack().as_slice()
+    );
+    assert_eq!(
+        tx.output(1).unwrap().lock().as_slice(),
+        sender_lock_script.as_slice()
+    );
+    assert_eq!(output_capacities(&tx), vec![
+        receiver_udt_capacity,
+        200_0000_0000,
+        200_0000_0000 - receiver_udt_capacity - FEE
+    ]);
+    assert_eq!(tx.witnesses().get(0).unwrap().raw_data().len(), 0);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    write_sdk_native_setup(
+        "test_claim_udt_with_receiver_lock_input_with_sdk",
+        &tx,
+        &context,
+    );
+}
+
+#[test]
+fn test_claim_capacity_with_receiver_signature_with_sdk() {
+    let SdkContext {
+        mut context,
+        cheque_script,
+        sender_lock_script,
+        ..
+    } = build_test_context_with_sdk();
+
+    // generate key pair
+    let private_key = Generator::random_privkey();
+    let public_key = private_key.pubkey().expect("pubkey");
+    let receiver_lock_script = Script::new_builder()
+        .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
+        .args(Bytes::copy_from_slice(&blake160(&public_key.serialize())).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+
+    let cheque_args = build_cheque_args(&receiver_lock_script, &sender_lock_script, FLAG_CAPACITY);
+    let cheque_cell = create_live_cell(
+        &mut context,
+        cheque_script.lock_script(&cheque_args),
+        None,
+        200_0000_0000,
+        Bytes::new(),
+    );
+
+    let tx = ClaimBuilder::new(
+        cheque_script,
+        cheque_args,
+        receiver_lock_script.clone(),
+        sender_lock_script,
+        ClaimMode::Signature,
+    )
+    .cheque_cell(cheque_cell)
+    .fee(FEE)
+    .build()
+    .expect("build");
+
+    assert_eq!(
+        tx.output(0).unwrap().lock().as_slice(),
+        receiver_lock_script.as_slice()
+    );
+    assert_eq!(output_capacities(&tx), vec![200_0000_0000 - FEE]);
+    assert_eq!(tx.cell_deps().len(), 2);
+
+    let tx = sign_tx(tx, &private_key);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    write_sdk_native_setup(
+        "test_claim_capacity_with_receiver_signature_with_sdk",
+        &tx,
+        &context,
+    );
+}
+
+#[test]
+fn test_withdraw_with_sender_lock_input_with_sdk() {
+    let SdkContext {
+        mut context,
+        cheque_script,
+        receiver_lock_script,
+        sender_lock_script,
+        always_success_lock_dep,
+        ..
+    } = build_test_context_with_sdk();
+    let cheque_args = build_cheque_args(&receiver_lock_script, &sender_lock_script, 0);
+    let cheque_cell = create_live_cell(
+        &mut context,
+        cheque_script.lock_script(&cheque_args),
+        None,
+        200_0000_0000,
+        Bytes::new(),
+    );
+    let sender_cell = create_live_cell(
+        &mut context,
+        sender_lock_script.clone(),
+        None,
+        100_0000_0000,
+        Bytes::new(),
+    );
+
+    let tx = WithdrawBuilder::new(
+        cheque_script,
+        cheque_args,
+        sender_lock_script.clone(),
+        WithdrawMode::LockInput(sender_cell),
+    )
+    .cheque_cell(cheque_cell)
+    .cell_dep(always_success_lock_dep)
+    .fee(FEE)
+    .build()
+    .expect("build");
+
+    let cheque_input_since: u64 = tx.inputs().get(0).unwrap().since().unpack();
+    assert_eq!(cheque_input_since, CHEQUE_CELL_SINCE);
+    assert_eq!(
+        tx.output(0).unwrap().lock().as_slice(),
+        sender_lock_script.as_slice()
+    );
+    assert_eq!(output_capacities(&tx), vec![299_0000_0000]);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    write_sdk_native_setup(
+        "test_withdraw_with_sender_lock_input_with_sdk",
+        &tx,
+        &context,
+    );
+}
+
+#[test]
+fn test_withdraw_to_refund_lock_with_sdk() {
+    let SdkContext {
+        mut context,
+        cheque_script,
+        receiver_lock_script,
+        sender_lock_script,
+        udt_type_script,
+        always_success_lock_dep,
+    } = build_test_context_with_sdk();
+    // another always_success lock than the sender lock
+    let refund_lock_script = udt_type_script
+        .as_builder()
+        .args(Bytes::from(vec![4u8]).pack())
+        .build();
+    let mut cheque_args =
+        build_cheque_args(&receiver_lock_script, &sender_lock_script, FLAG_REFUND);
+    cheque_args.refund_lock_hash = Some(lock_hash_160(&refund_lock_script));
+    let cheque_cell = create_live_cell(
+        &mut context,
+        cheque_script.lock_script(&cheque_args),
+        None,
+        200_0000_0000,
+        Bytes::new(),
+    );
+    let sender_cell = create_live_cell(
+        &mut context,
+        sender_lock_script.clone(),
+        None,
+        100_0000_0000,
+        Bytes::new(),
+    );
+
+    let tx = WithdrawBuilder::new(
+        cheque_script,
+        cheque_args,
+        refund_lock_script.clone(),
+        WithdrawMode::LockInput(sender_cell),
+    )
+    .cheque_cell(cheque_cell)
+    .change_lock(sender_lock_script.clone())
+    .cell_dep(always_success_lock_dep)
+    .fee(FEE)
+    .build()
+    .expect("build");
+
+    // the refund lock gets the whole cheque capacity and the sender input pays the fee
+    assert_eq!(
+        tx.output(0).unwrap().lock().as_slice(),
+        refund_lock_script.as_slice()
+    );
+    assert_eq!(
+        tx.output(1).unwrap().lock().as_slice(),
+        sender_lock_script.as_slice()
+    );
+    assert_eq!(output_capacities(&tx), vec![
+        200_0000_0000,
+        100_0000_0000 - FEE
+    ]);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    write_sdk_native_setup("test_withdraw_to_refund_lock_with_sdk", &tx, &context);
+}
+
+#[test]
+fn test_error_claim_salted_cheque_with_sdk() {
+    let SdkContext {
+        mut context,
+        cheque_script,
+        receiver_lock_script,
+        sender_lock_script,
+        ..
+    } = build_test_context_with_sdk();
+    let cheque_args = build_cheque_args(&receiver_lock_script, &sender_lock_script, FLAG_SALTED);
+    let cheque_cell = create_live_cell(
+        &mut context,
+        cheque_script.lock_script(&cheque_args),
+        None,
+        200_0000_0000,
+        Bytes::new(),
+    );
+
+    let result = ClaimBuilder::new(
+        cheque_script,
+        cheque_args,
+        receiver_lock_script,
+        sender_lock_script,
+        ClaimMode::Signature,
+    )
+    .cheque_cell(cheque_cell)
+    .build();
+    assert_eq!(result.err(), Some(Error::UnsupportedFlags(FLAG_SALTED)));
+}
@@ -0,0 +1,379 @@
+use super::{
+    helper::{blake160, write_native_setup, MAX_CYCLES},
+    *,
+};
+use cheque_core::args::{ChequeArgs, FLAG_CAPACITY};
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE as TYPE};
+use cheque_sdk::signer::{lock_script_groups, sighash_all_message};
+use cheque_sdk::{ChequeArgsExt, ChequeScript, ClaimBuilder, ClaimMode, LiveCell, Signer};
+use ckb_system_scripts::BUNDLED_CELL;
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+use ckb_tool::ckb_crypto::secp::{Generator, Privkey};
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, ScriptHashType, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const FEE: u64 = 1_0000_0000;
+
+// The secp256k1_blake160_sighash_all lock script is the type id of the genesis code cell
+const TYPE_ID_CODE_HASH: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 84, 89, 80, 69, 95,
+    73, 68,
+];
+const SECP256K1_BLAKE160_TYPE_ID_ARGS: &str =
+    "8536c9d5d908bd89fc70099e4284870708b6632356aad98734fcf43f6f71c304";
+
+struct SignerContext {
+    context:            Context,
+    cheque_script:      ChequeScript,
+    sender_lock_script: Script,
+    secp256k1_lock_dep: CellDep,
+}
+
+fn build_test_context_with_signer() -> SignerContext {
+    // deploy cheque script
+    let mut context = Context::default();
+    let cheque_bin: Bytes = Loader::default().load_binary("ckb-cheque-script");
+    let cheque_out_point = context.deploy_cell(cheque_bin);
+    let cheque_code_hash = context
+        .build_script(&cheque_out_point, Bytes::new())
+        .expect("script")
+        .code_hash();
+
+    let secp256k1_data_bin = BUNDLED_CELL.get("specs/cells/secp256k1_data").unwrap();
+    let secp256k1_data_out_point = context.deploy_cell(secp256k1_data_bin.to_vec().into());
+
+    // deploy the secp256k1_blake160_sighash_all script with the type id of the genesis code cell,
+    // so the ordinary secp256k1 lock scripts are run together with the cheque lock script
+    let secp256k1_type_id_script = Script::new_builder()
+        .code_hash(TYPE_ID_CODE_HASH.pack())
+        .args(Bytes::from(hex::decode(SECP256K1_BLAKE160_TYPE_ID_ARGS).unwrap()).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+    assert_eq!(
+        secp256k1_type_id_script.calc_script_hash().as_slice(),
+        CODE_HASH_SECP256K1_BLAKE160
+    );
+    let secp256k1_bin = BUNDLED_CELL
+        .get("specs/cells/secp256k1_blake160_sighash_all")
+        .unwrap();
+    let secp256k1_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(Capacity::bytes(secp256k1_bin.len() + 200).unwrap().pack())
+            .type_(Some(secp256k1_type_id_script).pack())
+            .build(),
+        secp256k1_bin.to_vec().into(),
+    );
+
+    // the context deploys the cheque script by data hash
+    let cheque_script = ChequeScript {
+        code_hash:          cheque_code_hash,
+        hash_type:          ScriptHashType::Data,
+        cell_dep:           CellDep::new_builder().out_point(cheque_out_point).build(),
+        secp256k1_data_dep: CellDep::new_builder()
+            .out_point(secp256k1_data_out_point)
+            .build(),
+    };
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let sender_lock_script = context
+        .build_script(
+            &always_success_out_point,
+            Bytes::from(hex::decode("36c329ed630d6ce750712a477543672adab57f4c").unwrap()),
+        )
+        .expect("script");
+
+    SignerContext {
+        context,
+        cheque_script,
+        sender_lock_script,
+        secp256k1_lock_dep: CellDep::new_builder()
+            .out_point(secp256k1_out_point)
+            .build(),
+    }
+}
+
+fn secp256k1_lock_script(private_key: &Privkey) -> Script {
+    let public_key = private_key.pubkey().expect("pubkey");
+    Script::new_builder()
+        .code_hash(CODE_HASH_SECP256K1_BLAKE160.pack())
+        .args(Bytes::copy_from_slice(&blake160(&public_key.serialize())).pack())
+        .hash_type(Byte::new(TYPE))
+        .build()
+}
+
+fn create_live_cell(context: &mut Context, lock_script: Script, capacity: u64) -> LiveCell {
+    let output = CellOutput::new_builder()
+        .capacity(Capacity::shannons(capacity).pack())
+        .lock(lock_script)
+        .build();
+    let out_point = context.create_cell(output.clone(), Bytes::new());
+    LiveCell::new(out_point, output, Bytes::new())
+}
+
+// Claims the cheque cells by the receiver signature, where the fee is paid by the secp256k1 cells
+// of the payer and a witness beyond the inputs is appended
+fn build_claim_tx(
+    signer_context: &mut SignerContext,
+    receiver_key: &Privkey,
+    payer_key: &Privkey,
+    cheque_cells_len: usize,
+    payer_cells_len: usize,
+) -> (TransactionView, Vec<CellOutput>) {
+    let receiver_lock_script = secp256k1_lock_script(receiver_key);
+    let payer_lock_script = secp256k1_lock_script(payer_key);
+    let mut cheque_args =
+        ChequeArgs::from_lock_scripts(&receiver_lock_script, &signer_context.sender_lock_script);
+    cheque_args.flags = FLAG_CAPACITY;
+
+    let mut builder = ClaimBuilder::new(
+        signer_context.cheque_script.clone(),
+        cheque_args.clone(),
+        receiver_lock_script,
+        signer_context.sender_lock_script.clone(),
+        ClaimMode::Signature,
+    )
+    .change_lock(payer_lock_script.clone())
+    .fee(FEE);
+    let mut input_cells = Vec::new();
+    for _ in 0..cheque_cells_len {
+        let cell = create_live_cell(
+            &mut signer_context.context,
+            signer_context.cheque_script.lock_script(&cheque_args),
+            200_0000_0000,
+        );
+        input_cells.push(cell.output.clone());
+        builder = builder.cheque_cell(cell);
+    }
+    for _ in 0..payer_cells_len {
+        let cell = create_live_cell(
+            &mut signer_context.context,
+            payer_lock_script.clone(),
+            100_0000_0000,
+        );
+        input_cells.push(cell.output.clone());
+        builder = builder.input(cell);
+    }
+
+    let tx = builder
+        .build()
+        .expect("build")
+        .as_advanced_builder()
+        .cell_dep(signer_context.secp256k1_lock_dep.clone())
+        .witness(Bytes::from(vec![7u8; 32]).pack())
+        .build();
+    (tx, input_cells)
+}
+
+fn write_signer_native_setup(name: &str, tx: &TransactionView, context: &Context) {
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(name, "ckb-cheque-script-sim", tx, context, &setup);
+}
+
+#[test]
+fn test_sign_cheque_and_secp256k1_groups_with_signer() {
+    let mut signer_context = build_test_context_with_signer();
+    let receiver_key = Generator::random_privkey();
+    let payer_key = Generator::random_privkey();
+    let (tx, input_cells) = build_claim_tx(&mut signer_context, &receiver_key, &payer_key, 1, 1);
+    assert_eq!(tx.witnesses().len(), 3);
+
+    let tx = Signer::new(signer_context.cheque_script.clone())
+        .key(receiver_key)
+        .expect("key")
+        .key(payer_key)
+        .expect("key")
+        .sign(tx, &input_cells)
+        .expect("sign");
+
+    // run
+    let cycles = signer_context
+        .context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    write_signer_native_setup(
+        "test_sign_cheque_and_secp256k1_groups_with_signer",
+        &tx,
+        &signer_context.context,
+    );
+}
+
+#[test]
+fn test_sign_multiple_inputs_groups_with_signer() {
+    let mut signer_context = build_test_context_with_signer();
+    let receiver_key = Generator::random_privkey();
+    let payer_key = Generator::random_privkey();
+    let (tx, input_cells) = build_claim_tx(&mut signer_context, &receiver_key, &payer_key, 2, 2);
+
+    let groups = lock_script_groups(&input_cells);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].input_indices, vec![0, 1]);
+    assert_eq!(groups[1].input_indices, vec![2, 3]);
+
+    let tx = Signer::new(signer_context.cheque_script.clone())
+        .key(payer_key)
+        .expect("key")
+        .key(receiver_key)
+        .expect("key")
+        .sign(tx, &input_cells)
+        .expect("sign");
+
+    // run
+    let cycles = signer_context
+        .context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    write_signer_native_setup(
+        "test_sign_multiple_inputs_groups_with_signer",
+        &tx,
+        &signer_context.context,
+    );
+}
+
+#[test]
+fn test_signer_leaves_groups_without_keys() {
+    let mut signer_context = build_test_context_with_signer();
+    let receiver_key = Generator::random_privkey();
+    let payer_key = Generator::random_privkey();
+    let (tx, input_cells) = build_claim_tx(&mut signer_context, &receiver_key, &payer_key, 1, 1);
+
+    let signed_tx = Signer::new(signer_context.cheque_script.clone())
+        .key(payer_key)
+        .expect("key")
+        .sign(tx.clone(), &input_cells)
+        .expect("sign");
+
+    // the cheque witness keeps the placeholder and only the payer witness is signed
+    assert_eq!(
+        signed_tx.witnesses().get(0).unwrap().as_slice(),
+        tx.witnesses().get(0).unwrap().as_slice()
+    );
+    assert_ne!(
+        signed_tx.witnesses().get(1).unwrap().as_slice(),
+        tx.witnesses().get(1).unwrap().as_slice()
+    );
+    assert_eq!(
+        signed_tx.witnesses().get(2).unwrap().as_slice(),
+        tx.witnesses().get(2).unwrap().as_slice()
+    );
+    assert!(signer_context
+        .context
+        .verify_tx(&signed_tx, MAX_CYCLES)
+        .is_err());
+}
+
+#[test]
+fn test_error_with_witness_changed_after_signing() {
+    let mut signer_context = build_test_context_with_signer();
+    let receiver_key = Generator::random_privkey();
+    let payer_key = Generator::random_privkey();
+    let (tx, input_cells) = build_claim_tx(&mut signer_context, &receiver_key, &payer_key, 1, 1);
+
+    let signed_tx = Signer::new(signer_context.cheque_script.clone())
+        .key(receiver_key)
+        .expect("key")
+        .key(payer_key)
+        .expect("key")
+        .sign(tx, &input_cells)
+        .expect("sign");
+
+    // every group digests the witnesses beyond the inputs
+    let witnesses: Vec<Bytes> = signed_tx
+        .witnesses()
+        .into_iter()
+        .map(|witness| witness.unpack())
+        .collect();
+    let groups = lock_script_groups(&input_cells);
+    let message =
+        sighash_all_message(&signed_tx.hash(), &witnesses, &groups[0], 2, 1).expect("message");
+    let mut changed_witnesses = witnesses.clone();
+    changed_witnesses[2] = Bytes::from(vec![8u8; 32]);
+    assert_ne!(
+        sighash_all_message(&signed_tx.hash(), &changed_witnesses, &groups[0], 2, 1)
+            .expect("message"),
+        message
+    );
+
+    let tx = signed_tx
+        .as_advanced_builder()
+        .set_witnesses(changed_witnesses.into_iter().map(|w| w.pack()).collect())
+        .build();
+    assert!(signer_context.context.verify_tx(&tx, MAX_CYCLES).is_err());
+}
+
+#[test]
+fn test_sign_interleaved_groups_with_signer() {
+    let mut signer_context = build_test_context_with_signer();
+    let receiver_key = Generator::random_privkey();
+    let payer_key = Generator::random_privkey();
+    let another_payer_key = Generator::random_privkey();
+    let (tx, input_cells) = build_claim_tx(&mut signer_context, &receiver_key, &payer_key, 2, 1);
+    let another_payer_cell = create_live_cell(
+        &mut signer_context.context,
+        secp256k1_lock_script(&another_payer_key),
+        100_0000_0000,
+    );
+
+    // the payer input is between the cheque inputs and the witnesses only hold the cheque
+    // placeholder, so the group of the last input fills the witness of the second cheque input
+    let inputs = tx.inputs();
+    let tx = tx
+        .as_advanced_builder()
+        .set_inputs(vec![
+            inputs.get(0).unwrap(),
+            inputs.get(2).unwrap(),
+            inputs.get(1).unwrap(),
+            CellInput::new(another_payer_cell.out_point, 0),
+        ])
+        .set_witnesses(vec![tx.witnesses().get(0).unwrap()])
+        .build();
+    let input_cells = vec![
+        input_cells[0].clone(),
+        input_cells[2].clone(),
+        input_cells[1].clone(),
+        another_payer_cell.output,
+    ];
+    let groups = lock_script_groups(&input_cells);
+    assert_eq!(groups.len(), 3);
+    assert_eq!(groups[0].input_indices, vec![0, 2]);
+    assert_eq!(groups[2].input_indices, vec![3]);
+
+    let tx = Signer::new(signer_context.cheque_script.clone())
+        .key(receiver_key)
+        .expect("key")
+        .key(payer_key)
+        .expect("key")
+        .key(another_payer_key)
+        .expect("key")
+        .sign(tx, &input_cells)
+        .expect("sign");
+    assert_eq!(tx.witnesses().len(), 4);
+
+    // run
+    let cycles = signer_context
+        .context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    write_signer_native_setup(
+        "test_sign_interleaved_groups_with_signer",
+        &tx,
+        &signer_context.context,
+    );
+}
@@ -0,0 +1,304 @@
+use super::helper::{
+    self, build_cell, placeholder_signature, write_native_setup, TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::{FLAG_CAPACITY, FLAG_SUBSCRIPTION};
+use cheque_core::error::Error;
+use cheque_core::since::{CHEQUE_CELL_SINCE, SINCE_METRIC_EPOCH};
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, TransactionView},
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const SUBSCRIPTION_NOT_MATCHED: i8 = Error::SubscriptionNotMatched as i8;
+
+// The receiver pulls 100 CKB every 10 epochs from epoch 100
+const SUBSCRIPTION_START_EPOCH: u64 = 100;
+const SUBSCRIPTION_PERIOD_EPOCHS: u64 = 10;
+const SUBSCRIPTION_AMOUNT: u64 = 100_0000_0000;
+
+fn absolute_epoch_since(number: u64) -> u64 {
+    SINCE_METRIC_EPOCH | EpochNumberWithFraction::new(number, 0, 1).full_value()
+}
+
+#[derive(Clone, Copy)]
+enum Party {
+    Receiver,
+    Sender,
+    // The recreated subscription cheque cell
+    Cheque,
+}
+
+// (party, capacity, Option<period counter>)
+type Output = (Party, u64, Option<u64>);
+
+fn build_test_context_with_subscription(
+    party: Party,
+    cheque_capacity: u64,
+    period_counter: Option<u64>,
+    since: u64,
+    outputs: Vec<Output>,
+) -> (Context, TransactionView) {
+    let mut builder = TestTxBuilder::default();
+    let mut subscription_fields = EpochNumberWithFraction::new(SUBSCRIPTION_START_EPOCH, 0, 1)
+        .full_value()
+        .to_le_bytes()
+        .to_vec();
+    subscription_fields.extend_from_slice(&SUBSCRIPTION_PERIOD_EPOCHS.to_le_bytes());
+    subscription_fields.extend_from_slice(&SUBSCRIPTION_AMOUNT.to_le_bytes());
+    let cheque_args = builder.cheque_args(
+        helper::Party::Receiver,
+        helper::Party::Sender,
+        Some(FLAG_CAPACITY | FLAG_SUBSCRIPTION),
+        &subscription_fields,
+    );
+    let cheque_script = builder.cheque_script(&cheque_args);
+
+    let mut build_party_cell = |party: Party, capacity: u64, period_counter: Option<u64>| {
+        let lock_script = match party {
+            Party::Receiver => builder.lock_script(helper::Party::Receiver),
+            Party::Sender => builder.lock_script(helper::Party::Sender),
+            Party::Cheque => cheque_script.clone(),
+        };
+        let data = match period_counter {
+            Some(counter) => Bytes::from(counter.to_le_bytes().to_vec()),
+            None => Bytes::new(),
+        };
+        (build_cell(&lock_script, capacity, None), data)
+    };
+
+    // prepare the cells of the inputs and the outputs
+    let cheque_cell = build_party_cell(Party::Cheque, cheque_capacity, period_counter);
+    let party_cell = build_party_cell(party, 200_0000_0000, None);
+    let outputs = outputs
+        .into_iter()
+        .map(|(output_party, capacity, period_counter)| {
+            build_party_cell(output_party, capacity, period_counter)
+        })
+        .collect::<Vec<_>>();
+    builder.input(cheque_cell.0, cheque_cell.1, since, Bytes::new());
+    builder.input(party_cell.0, party_cell.1, 0, placeholder_signature());
+    for (output, data) in outputs.into_iter() {
+        builder.output(output, data);
+    }
+
+    builder.build()
+}
+
+#[test]
+fn test_pull_first_period_with_receiver_input() {
+    let (mut context, tx) = build_test_context_with_subscription(
+        Party::Receiver,
+        1000_0000_0000,
+        None,
+        absolute_epoch_since(100),
+        vec![
+            (Party::Cheque, 900_0000_0000, Some(1)),
+            (Party::Receiver, 300_0000_0000, None),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_pull_first_period_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_pull_third_period_with_receiver_input() {
+    let (mut context, tx) = build_test_context_with_subscription(
+        Party::Receiver,
+        800_0000_0000,
+        Some(2),
+        absolute_epoch_since(120),
+        vec![
+            (Party::Cheque, 700_0000_0000, Some(3)),
+            (Party::Receiver, 300_0000_0000, None),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_pull_third_period_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_pull_before_period_start() {
+    let (mut context, tx) = build_test_context_with_subscription(
+        Party::Receiver,
+        800_0000_0000,
+        Some(2),
+        absolute_epoch_since(115),
+        vec![
+            (Party::Cheque, 700_0000_0000, Some(3)),
+            (Party::Receiver, 300_0000_0000, None),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(SUBSCRIPTION_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_pull_before_period_start",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_pull_without_period_counter_bumped() {
+    let (mut context, tx) = build_test_context_with_subscription(
+        Party::Receiver,
+        1000_0000_0000,
+        None,
+        absolute_epoch_since(100),
+        vec![
+            (Party::Cheque, 900_0000_0000, Some(0)),
+            (Party::Receiver, 300_0000_0000, None),
+        ],
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(SUBSCRIPTION_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_pull_without_period_counter_bumped",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_pull_rest_capacity_with_receiver_input() {
+    let (mut context, tx) = build_test_context_with_subscription(
+        Party::Receiver,
+        150_0000_0000,
+        Some(8),
+        absolute_epoch_since(180),
+        vec![(Party::Receiver, 350_0000_0000, None)],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_pull_rest_capacity_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_subscription_with_sender_input() {
+    let (mut context, tx) = build_test_context_with_subscription(
+        Party::Sender,
+        800_0000_0000,
+        Some(2),
+        CHEQUE_CELL_SINCE,
+        vec![(Party::Sender, 1000_0000_0000, None)],
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_subscription_with_sender_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
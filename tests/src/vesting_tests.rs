@@ -0,0 +1,362 @@
+use super::helper::{
+    build_cell, placeholder_signature, write_native_setup, write_native_setup_with_headers, Party,
+    TestTxBuilder, MAX_CYCLES,
+};
+use cheque_core::args::{FLAG_CAPACITY, FLAG_VESTING, FLAG_WITHDRAW_SINCE};
+use cheque_core::error::Error;
+use cheque_core::since::SINCE_METRIC_EPOCH;
+use ckb_testtool::context::Context;
+use ckb_tool::ckb_error::assert_error_eq;
+use ckb_tool::ckb_script::ScriptError;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderBuilder, HeaderView, TransactionView},
+    prelude::*,
+};
+use ckb_x64_simulator::RunningSetup;
+use std::collections::HashMap;
+
+const VESTING_CAPACITY_NOT_MATCHED: i8 = Error::VestingCapacityNotMatched as i8;
+
+// 1000 CKB vests from epoch 100 to epoch 110
+const VESTING_START_EPOCH: u64 = 100;
+const VESTING_END_EPOCH: u64 = 110;
+const VESTING_TOTAL_CAPACITY: u64 = 1000_0000_0000;
+
+// The sender can withdraw the unvested capacity after epoch 104
+const WITHDRAW_EPOCH: u64 = 104;
+
+fn epoch(number: u64) -> u64 {
+    EpochNumberWithFraction::new(number, 0, 1).full_value()
+}
+
+fn absolute_epoch_since(number: u64) -> u64 {
+    SINCE_METRIC_EPOCH | epoch(number)
+}
+
+enum Output {
+    // the vesting cheque with the same lock args
+    VestingCheque(u64),
+    // the cheque without vesting for the vested capacity
+    VestedCheque(u64),
+    Party(Party, u64),
+}
+
+fn build_test_context_with_vesting(
+    party: Party,
+    cheque_since: u64,
+    outputs: Vec<Output>,
+    header_dep: Option<&HeaderView>,
+) -> (Context, TransactionView) {
+    let mut builder = TestTxBuilder::default();
+    let mut vesting_fields = absolute_epoch_since(WITHDRAW_EPOCH).to_le_bytes().to_vec();
+    vesting_fields.extend_from_slice(&epoch(VESTING_START_EPOCH).to_le_bytes());
+    vesting_fields.extend_from_slice(&epoch(VESTING_END_EPOCH).to_le_bytes());
+    vesting_fields.extend_from_slice(&VESTING_TOTAL_CAPACITY.to_le_bytes());
+    let cheque_args = builder.cheque_args(
+        Party::Receiver,
+        Party::Sender,
+        Some(FLAG_CAPACITY | FLAG_WITHDRAW_SINCE | FLAG_VESTING),
+        &vesting_fields,
+    );
+    let cheque_script = builder.cheque_script(&cheque_args);
+    let vested_cheque_args =
+        builder.cheque_args(Party::Receiver, Party::Sender, Some(FLAG_CAPACITY), &[]);
+    let vested_cheque_script = builder.cheque_script(&vested_cheque_args);
+
+    // prepare inputs
+    builder.input(
+        build_cell(&cheque_script, VESTING_TOTAL_CAPACITY, None),
+        Bytes::new(),
+        cheque_since,
+        Bytes::new(),
+    );
+    let party_lock_script = builder.lock_script(party);
+    builder.input(
+        build_cell(&party_lock_script, 200_0000_0000, None),
+        Bytes::new(),
+        0,
+        placeholder_signature(),
+    );
+
+    // prepare outputs
+    for output in outputs.into_iter() {
+        let (lock_script, capacity) = match output {
+            Output::VestingCheque(capacity) => (cheque_script.clone(), capacity),
+            Output::VestedCheque(capacity) => (vested_cheque_script.clone(), capacity),
+            Output::Party(party, capacity) => (builder.lock_script(party), capacity),
+        };
+        builder.output(build_cell(&lock_script, capacity, None), Bytes::new());
+    }
+
+    if let Some(header) = header_dep {
+        builder.header_dep(header);
+    }
+    builder.build()
+}
+
+fn build_header(number: u64) -> HeaderView {
+    HeaderBuilder::default()
+        .epoch(epoch(number).pack())
+        .number((number * 1000).pack())
+        .build()
+}
+
+#[test]
+fn test_claim_vested_capacity_with_receiver_input() {
+    // half of the capacity has vested at epoch 105
+    let (mut context, tx) = build_test_context_with_vesting(
+        Party::Receiver,
+        absolute_epoch_since(105),
+        vec![
+            Output::VestingCheque(500_0000_0000),
+            Output::Party(Party::Receiver, 700_0000_0000),
+        ],
+        None,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_claim_vested_capacity_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_unvested_capacity_with_receiver_input() {
+    let (mut context, tx) = build_test_context_with_vesting(
+        Party::Receiver,
+        absolute_epoch_since(105),
+        vec![
+            Output::VestingCheque(400_0000_0000),
+            Output::Party(Party::Receiver, 800_0000_0000),
+        ],
+        None,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VESTING_CAPACITY_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_unvested_capacity_with_receiver_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_claim_vesting_cheque_without_epoch_since() {
+    let (mut context, tx) = build_test_context_with_vesting(
+        Party::Receiver,
+        0,
+        vec![
+            Output::VestingCheque(1000_0000_0000),
+            Output::Party(Party::Receiver, 200_0000_0000),
+        ],
+        None,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VESTING_CAPACITY_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_claim_vesting_cheque_without_epoch_since",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_withdraw_unvested_capacity_with_sender_input() {
+    // 400 CKB has vested at the withdraw deadline and goes to a cheque for the receiver
+    let (mut context, tx) = build_test_context_with_vesting(
+        Party::Sender,
+        absolute_epoch_since(WITHDRAW_EPOCH),
+        vec![
+            Output::VestedCheque(400_0000_0000),
+            Output::Party(Party::Sender, 800_0000_0000),
+        ],
+        None,
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_withdraw_unvested_capacity_with_sender_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_error_withdraw_vested_capacity_with_sender_input() {
+    let (mut context, tx) = build_test_context_with_vesting(
+        Party::Sender,
+        absolute_epoch_since(WITHDRAW_EPOCH),
+        vec![Output::Party(Party::Sender, 1200_0000_0000)],
+        None,
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VESTING_CAPACITY_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup(
+        "test_error_withdraw_vested_capacity_with_sender_input",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+    );
+}
+
+#[test]
+fn test_claim_vested_capacity_with_header_dep() {
+    // the header dep proves that half of the capacity has vested
+    let header = build_header(105);
+    let (mut context, tx) = build_test_context_with_vesting(
+        Party::Receiver,
+        0,
+        vec![
+            Output::VestingCheque(500_0000_0000),
+            Output::Party(Party::Receiver, 700_0000_0000),
+        ],
+        Some(&header),
+    );
+    let tx = context.complete_tx(tx);
+
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_claim_vested_capacity_with_header_dep",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header],
+        &[],
+    );
+}
+
+#[test]
+fn test_error_claim_unvested_capacity_with_header_dep() {
+    let header = build_header(104);
+    let (mut context, tx) = build_test_context_with_vesting(
+        Party::Receiver,
+        0,
+        vec![
+            Output::VestingCheque(500_0000_0000),
+            Output::Party(Party::Receiver, 700_0000_0000),
+        ],
+        Some(&header),
+    );
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let script_cell_index = 0;
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VESTING_CAPACITY_NOT_MATCHED)
+            .input_lock_script(script_cell_index)
+    );
+
+    // dump raw test tx files
+    let setup = RunningSetup {
+        is_lock_script:  true,
+        is_output:       false,
+        script_index:    0,
+        native_binaries: HashMap::default(),
+    };
+    write_native_setup_with_headers(
+        "test_error_claim_unvested_capacity_with_header_dep",
+        "ckb-cheque-script-sim",
+        &tx,
+        &context,
+        &setup,
+        &[header],
+        &[],
+    );
+}
@@ -1,7 +1,9 @@
 use super::{
-    helper::{blake160, sign_tx, write_native_setup, CODE_HASH_SECP256K1_BLAKE160, TYPE, MAX_CYCLES},
+    helper::{blake160, sign_tx, write_native_setup, MAX_CYCLES},
     *,
 };
+use cheque_core::code_hash::{CODE_HASH_SECP256K1_BLAKE160, HASH_TYPE_TYPE as TYPE};
+use cheque_core::error::Error;
 use ckb_system_scripts::BUNDLED_CELL;
 use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
 use ckb_tool::ckb_crypto::secp::Generator;
@@ -16,9 +18,9 @@ use ckb_tool::ckb_types::{
 use ckb_x64_simulator::RunningSetup;
 use std::collections::HashMap;
 
-const NO_MATCHED_INPUTS: i8 = 6;
-const WITNESS_SIGNATURE_WRONG: i8 = 7;
-const WITHDRAW_CHEQUE_INPUT_SINCE_ERROR: i8 = 11;
+const NO_MATCHED_INPUTS: i8 = Error::NoMatchedInputs as i8;
+const WITNESS_SIGNATURE_WRONG: i8 = Error::WitnessSignatureWrong as i8;
+const WITHDRAW_CHEQUE_INPUT_SINCE_ERROR: i8 = Error::WithdrawChequeInputSinceError as i8;
 
 fn build_test_context_with_sender_input(
     sender_lock_args: Bytes,